@@ -0,0 +1,559 @@
+use std::io;
+use std::path::{Path, PathBuf};
+
+use clap::{Args, Parser, Subcommand};
+
+use crate::composer_json::ComposerJson;
+use crate::constraint::ConstraintStyle;
+use crate::diff::DEFAULT_CONTEXT_LINES;
+use crate::lock::LockFile;
+use crate::log::{debug, info, set_info_to_stderr, set_verbosity, Verbosity};
+use crate::fs::{resolve_composer_json_path, set_max_file_size, DEFAULT_MAX_FILE_SIZE};
+use crate::merge::{handle_merge, ArrayStrategy};
+use crate::modify_composer_json::{ModifyComposerJson, PackagePattern};
+use crate::parse_handler::{load_all, ParseFile, ParseFileType};
+use crate::modify::{handle_modify, handle_modify_all, BatchResult, handle_purge_package, Conversion, Indent, ModifyOptions, OnConflict, OutputFormat};
+use crate::report::{self, MapSelection, Report, ReportFormat};
+use crate::validate::{handle_check_platform_reqs, handle_validate, ValidateOptions};
+
+#[derive(Parser)]
+#[command(author, version, about, long_about = None)]
+struct Cli {
+    /// Sets a custom config file
+    #[arg(short, long, value_name = "FILE")]
+    config: Option<PathBuf>,
+
+    /// Turn debugging information on
+    #[arg(short, long, action = clap::ArgAction::Count)]
+    debug: u8,
+
+    /// Only print errors, warnings and the requested output, not informational messages
+    #[arg(short, long, default_value="false", conflicts_with="debug")]
+    quiet: bool,
+
+    /// Refuse to read files larger than this many bytes
+    #[arg(long, value_name = "bytes", default_value_t = DEFAULT_MAX_FILE_SIZE)]
+    max_file_size: u64,
+
+    #[command(subcommand)]
+    command: Option<Commands>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Commands {
+    /// Parse a composer.json or other JSON config file, e.g. modify-composer.json
+    #[command(subcommand)]
+    Parse(ParseCommands),
+
+    /// Modify a composer.json file
+    #[command(subcommand)]
+    Modify(ModifyCommands),
+
+    /// Validate a composer.json file
+    Validate {
+        /// Path to the composer.json file to validate
+        #[arg(value_name="composer-json")]
+        file: String,
+
+        /// Fail if require and require-dev are not sorted the way Composer sorts packages
+        #[arg(long, default_value="false")]
+        strict_order: bool,
+
+        /// Fail if the package has no valid vendor/project name, as required for publishing
+        #[arg(long, alias="abort-if-no-name", default_value="false")]
+        require_name: bool,
+
+        /// Fail instead of warning if a license is not an SPDX license identifier, a LicenseRef- identifier or "proprietary"
+        #[arg(long, default_value="false")]
+        strict_license: bool,
+
+        /// Report only the first validation error instead of all of them
+        #[arg(long, default_value="false")]
+        fail_fast: bool,
+    },
+
+    /// Check the platform requirements of a composer.json file against its config.platform
+    CheckPlatformReqs {
+        /// Path to the composer.json file to check
+        #[arg(value_name="composer-json")]
+        file: String,
+
+        /// Skip the platform requirements of require-dev
+        #[arg(long, default_value="false")]
+        no_dev: bool,
+    },
+
+    /// Deep-merge an overlay composer.json into a base composer.json
+    Merge {
+        /// Path to the base composer.json file
+        #[arg(value_name="base")]
+        base: String,
+
+        /// Path to the composer.json file whose values take precedence over the base
+        #[arg(value_name="overlay")]
+        overlay: String,
+
+        /// Write the merged composer.json to this file instead of printing it
+        #[arg(short, long, value_name="FILE")]
+        output: Option<String>,
+
+        /// How to combine arrays, e.g. authors, present in both files
+        #[arg(long, value_enum, value_name="strategy", default_value_t=ArrayStrategy::Append)]
+        arrays: ArrayStrategy,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum ParseCommands {
+    /// Parse a composer.json file
+    ComposerJson {
+        /// Name of the composer.json file to parse
+        #[arg(value_name="composer-json")]
+        file: String,
+
+        /// Print the parsed ComposerJson struct to stdout
+        #[arg(short, long, default_value="false")]
+        print: bool,
+
+        /// List the require entries matching the given package pattern, e.g. "symfony/*"
+        #[arg(short, long = "match", value_name="pattern")]
+        matching: Option<String>,
+
+        /// List all require entries
+        #[arg(long, default_value="false", conflicts_with="matching")]
+        require_only: bool,
+
+        /// Group listed require entries under their vendor
+        #[arg(long, default_value="false")]
+        group_by_vendor: bool,
+
+        /// Use composer.json.dist if the given composer.json file does not exist
+        #[arg(long, default_value="false")]
+        allow_dist: bool,
+    },
+
+    /// Parse a modify-composer.json file
+    Modify {
+        /// Name of the modify-composer.json file to parse, or a modify-composer.yaml file
+        #[arg(value_name="composer-json")]
+        file: String,
+
+        /// Print the parsed ModifyComposerJson struct to stdout
+        #[arg(short, long, default_value="false")]
+        print: bool,
+    },
+
+    /// Parse a composer.lock file, listing its number of packages
+    Lock {
+        /// Name of the composer.lock file to parse
+        #[arg(value_name="composer-lock")]
+        file: String,
+
+        /// Print the parsed LockFile struct to stdout
+        #[arg(short, long, default_value="false")]
+        print: bool,
+    },
+}
+
+/// The options of `modify run` that make up its [ModifyOptions].
+#[derive(Args, Debug)]
+struct RunArgs {
+    /// Whether to apply resulting changes to target file
+    #[arg(short, long, default_value="false")]
+    dry_run: bool,
+
+    /// Print the changes without applying them, and exit with status 1 if there are any
+    #[arg(long, default_value="false", conflicts_with="dry_run")]
+    check: bool,
+
+    /// Print a report of the changed dependencies in the given format
+    #[arg(long, value_enum, value_name="format")]
+    report_format: Option<ReportFormat>,
+
+    /// Require added package names to strictly match Composer's package name pattern
+    #[arg(long, default_value="false")]
+    validate_names: bool,
+
+    /// Record the applied directives and a timestamp in extra._modifier of the modified file
+    #[arg(long, default_value="false")]
+    annotate: bool,
+
+    /// Remove platform requirements from require that are already satisfied by config.platform
+    #[arg(long, default_value="false")]
+    prune_platform_duplicates: bool,
+
+    /// Ensure a package is required with the given constraint, adding or replacing it (repeatable)
+    #[arg(long, value_name="package=constraint")]
+    ensure: Vec<String>,
+
+    /// Remove packages matching the given pattern from require and require-dev (repeatable)
+    #[arg(long, value_name="pattern")]
+    drop: Vec<String>,
+
+    /// Write the duration of each phase of the run (parse, each operation, write) to the given JSON file
+    #[arg(long, value_name="path")]
+    profile: Option<String>,
+
+    /// Use composer.json.dist if the given composer.json file does not exist
+    #[arg(long, default_value="false")]
+    allow_dist: bool,
+
+    /// Write a minimal, unresolved composer.lock stub listing the required packages and constraints to the given path, when the modified composer.json is written
+    #[arg(long, value_name="path")]
+    emit_lock_stub: Option<String>,
+
+    /// Rewrite repository URLs, replacing a substring (or a regex, when prefixed with "regex:") with another (repeatable)
+    #[arg(long, value_name="from=to")]
+    replace_url: Vec<String>,
+
+    /// Also apply --replace-url to homepage and support.source
+    #[arg(long, default_value="false")]
+    replace_metadata_urls: bool,
+
+    /// Prepend a UTF-8 byte order mark to the written composer.json, for toolchains that expect one
+    #[arg(long, alias="encoding-bom", default_value="false")]
+    write_bom: bool,
+
+    /// Copy the composer.json file to <file>.bak before overwriting it
+    #[arg(long, default_value="false")]
+    backup: bool,
+
+    /// With --backup, keep an existing backup and write to <file>.bak.1, <file>.bak.2, ... instead
+    #[arg(long, default_value="false", requires="backup")]
+    backup_numbered: bool,
+
+    /// Exit with an error instead of only warning when a remove pattern matches no package
+    #[arg(long, default_value="false")]
+    error_on_unmatched: bool,
+
+    /// Fail if the composer.json file has a key given more than once in an object, e.g. a package in require
+    #[arg(long, default_value="false")]
+    reject_duplicate_keys: bool,
+
+    /// Rewrite plain single-version constraints (e.g. 1.2.3) to the given style, leaving ranges and dev constraints as they are
+    #[arg(long, value_enum, value_name="style")]
+    constraint_style: Option<ConstraintStyle>,
+
+    /// Require each package listed in the given file, one "name constraint" pair per line
+    #[arg(long, value_name="file")]
+    from_package_list: Option<String>,
+
+    /// Require the packages given with --from-package-list in require-dev instead of require
+    #[arg(long, default_value="false", requires="from_package_list")]
+    dev: bool,
+
+    /// Write the modified composer.json, or print a JSON Patch (RFC 6902) of the changes instead
+    #[arg(long, value_enum, value_name="format", default_value_t=OutputFormat::File)]
+    output_format: OutputFormat,
+
+    /// Indent the written composer.json with this many spaces, or with tabs when given "tab"
+    #[arg(long, value_name="N|tab", default_value_t=Indent::default())]
+    indent: Indent,
+
+    /// Print each directive of the modify file and how many entries it affected
+    #[arg(long, default_value="false")]
+    print_applied: bool,
+
+    /// What to do when adding a package that is already required with another constraint
+    #[arg(long, value_enum, value_name="policy", default_value_t=OnConflict::Overwrite)]
+    on_conflict: OnConflict,
+
+    /// Migrate deprecated features after modifying, e.g. PSR-0 autoload mappings to PSR-4
+    #[arg(long, value_enum, value_name="conversion")]
+    convert: Vec<Conversion>,
+
+    /// Number of unchanged lines to show around each change in the --dry-run diff
+    #[arg(long, value_name="N", default_value_t=DEFAULT_CONTEXT_LINES)]
+    diff_context: usize,
+
+    /// Write the modified composer.json to this file instead of overwriting the original
+    #[arg(short, long, value_name="FILE")]
+    output: Option<String>,
+
+    /// Edit require and require-dev in the parsed JSON directly, leaving all other keys untouched
+    #[arg(long, default_value="false", conflicts_with_all=["annotate", "prune_platform_duplicates", "replace_url", "constraint_style", "from_package_list", "convert", "print_applied"])]
+    lossless: bool,
+}
+
+impl From<&RunArgs> for ModifyOptions {
+    fn from(args: &RunArgs) -> ModifyOptions {
+        ModifyOptions {
+            dry_run: args.dry_run,
+            report_format: args.report_format,
+            validate_names: args.validate_names,
+            annotate: args.annotate,
+            prune_platform_duplicates: args.prune_platform_duplicates,
+            ensure: args.ensure.clone(),
+            drop: args.drop.clone(),
+            profile: args.profile.clone(),
+            allow_dist: args.allow_dist,
+            emit_lock_stub: args.emit_lock_stub.clone(),
+            replace_url: args.replace_url.clone(),
+            replace_metadata_urls: args.replace_metadata_urls,
+            write_bom: args.write_bom,
+            backup: args.backup,
+            backup_numbered: args.backup_numbered,
+            constraint_style: args.constraint_style,
+            from_package_list: args.from_package_list.clone(),
+            dev: args.dev,
+            output_format: args.output_format,
+            indent: args.indent,
+            print_applied: args.print_applied,
+            on_conflict: args.on_conflict,
+            convert: args.convert.clone(),
+            output: args.output.clone(),
+            check: args.check,
+            diff_context: Some(args.diff_context),
+            lossless: args.lossless,
+            error_on_unmatched: args.error_on_unmatched,
+            reject_duplicate_keys: args.reject_duplicate_keys,
+        }
+    }
+}
+
+#[derive(Subcommand, Debug)]
+enum ModifyCommands {
+    /// Modify a composer.json file
+    Run {
+        /// Path to the composer.json file to modify
+        #[arg(value_name="composer-json")]
+        composer_json: String,
+
+        /// Path to the modify-composer.json configuration file, or a modify-composer.yaml file
+        #[arg(value_name="modify", required_unless_present_any=["ensure", "drop", "from_package_list"])]
+        modify: Option<String>,
+
+        /// Print the modified ComposerJson struct to stdout
+        #[arg(short, long, default_value="false")]
+        print: bool,
+
+        #[command(flatten)]
+        args: Box<RunArgs>,
+    },
+
+    /// Show the dependency changes between two composer.json files
+    Diff {
+        /// Path to the original composer.json file
+        #[arg(value_name="old")]
+        old: String,
+
+        /// Path to the changed composer.json file
+        #[arg(value_name="new")]
+        new: String,
+
+        /// The package link maps to compare
+        #[arg(long, value_enum, value_name="map", default_value="both")]
+        select_map: MapSelection,
+
+        /// Print the changes in the given format instead of as a summary
+        #[arg(long, value_enum, value_name="format")]
+        report_format: Option<ReportFormat>,
+    },
+
+    /// Remove a package from every package link section (require, require-dev, conflict, replace, provide and suggest)
+    PurgePackage {
+        /// Path to the composer.json file to modify
+        #[arg(value_name="composer-json")]
+        composer_json: String,
+
+        /// Name of the package to remove, or a package pattern, e.g. "symfony/*"
+        #[arg(value_name="package")]
+        package: String,
+
+        /// Whether to apply resulting changes to target file
+        #[arg(short, long, default_value="false")]
+        dry_run: bool,
+    },
+
+    /// Modify several composer.json files using the same modify-composer.json file
+    RunAll {
+        /// Path to the modify-composer.json configuration file, or a modify-composer.yaml file
+        #[arg(value_name="modify")]
+        modify: String,
+
+        /// Paths to the composer.json files to modify, or directories to search for composer.json files
+        #[arg(value_name="composer-json", required=true)]
+        composer_json: Vec<String>,
+
+        /// Names of directories not to search for composer.json files
+        #[arg(long, value_name="dir", default_values=["vendor", "node_modules"])]
+        skip: Vec<String>,
+
+        /// Whether to apply resulting changes to target files
+        #[arg(short, long, default_value="false")]
+        dry_run: bool,
+
+        /// Print a report of the changed dependencies in the given format
+        #[arg(long, value_enum, value_name="format")]
+        report_format: Option<ReportFormat>,
+
+        /// Abort on the first file that cannot be modified instead of continuing with the rest
+        #[arg(long, default_value="false")]
+        fail_fast: bool,
+    },
+}
+
+/// Parses the command line arguments and runs the given command, exiting with status 1 if it fails.
+pub fn run() {
+    let cli = Cli::parse();
+
+    set_max_file_size(cli.max_file_size);
+    set_verbosity(Verbosity::from_flags(cli.quiet, cli.debug));
+
+    if let Some(config_path) = cli.config.as_deref() {
+        debug!("Value for config: {}", config_path.display());
+    }
+
+    // You can check for the existence of subcommands, and if found use their
+    // matches just as you would the top level cmd
+    match &cli.command {
+        Some(c) => {
+            match handle(c) {
+                Ok(_) => {}
+                Err(e) => {
+                    eprintln!("{}", e);
+                    std::process::exit(1);
+                }
+            };
+        }
+        None => {}
+    }
+}
+
+fn handle(cmds: &Commands) -> io::Result<()> {
+    match cmds {
+        Commands::Parse (commands) => handle_parse_commands(commands),
+        Commands::Modify (commands) => handle_modify_commands(commands),
+        Commands::Validate { file, strict_order, require_name, strict_license, fail_fast } => {
+            let options = ValidateOptions {
+                strict_order: *strict_order,
+                require_name: *require_name,
+                strict_license: *strict_license,
+                fail_fast: *fail_fast,
+            };
+
+            Ok(handle_validate(file, &options)?)
+        },
+        Commands::CheckPlatformReqs { file, no_dev } => Ok(handle_check_platform_reqs(file, no_dev)?),
+        Commands::Merge { base, overlay, output, arrays } => handle_merge(base, overlay, output.as_deref(), *arrays),
+    }?;
+
+    Ok(())
+}
+
+fn handle_parse_commands(cmds: &ParseCommands) -> io::Result<()> {
+    match cmds {
+        ParseCommands::ComposerJson { file, print, matching, require_only, group_by_vendor, allow_dist } => {
+            let file = &resolve_composer_json_path(file, allow_dist);
+
+            match (matching, require_only) {
+                (Some(pattern), _) => return handle_match(file, pattern, group_by_vendor),
+                (None, true) => return handle_match(file, "*", group_by_vendor),
+                (None, false) => ComposerJson::parse_file_type().handle_parse(file, print),
+            }
+        },
+        ParseCommands::Modify { file, print } => ModifyComposerJson::parse_file_type().handle_parse(file, print),
+        ParseCommands::Lock { file, print } => {
+            let lock = LockFile::parse_file_type()._handle_parse_and_return::<LockFile>(file)?;
+
+            info!("successfully parsed {} file: {}", LockFile::parse_file_type(), file);
+            println!("{} packages, {} dev packages", lock.packages.len(), lock.packages_dev.len());
+
+            if *print {
+                println!("\n{}:\n{}", file, serde_json::to_string_pretty(&lock)?);
+            }
+        },
+    }
+
+    Ok(())
+}
+
+fn handle_match(file: &str, pattern: &str, group_by_vendor: &bool) -> io::Result<()> {
+    let c = ParseFileType::ComposerJson._handle_parse_and_return::<ComposerJson>(file)?;
+    let pattern = PackagePattern::new(pattern)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, format!("invalid package pattern: {}", e)))?;
+    let entries = c.require_entries_matching(&pattern);
+
+    if *group_by_vendor {
+        print!("{}", report::group_by_vendor(&entries));
+
+        return Ok(());
+    }
+
+    for (name, constraint) in entries {
+        println!("{} {}", name, constraint);
+    }
+
+    Ok(())
+}
+
+fn handle_modify_commands(cmds: &ModifyCommands) -> io::Result<()> {
+    match cmds {
+        ModifyCommands::Run { composer_json, modify, print, args } => {
+            let options = ModifyOptions::from(args.as_ref());
+
+            set_info_to_stderr(options.prints_patch());
+
+            let using = modify.as_deref().unwrap_or("command line options");
+
+            if options.check {
+                info!("Checking {} using {}", composer_json, using)
+            } else if options.dry_run {
+                info!("Modifying {} using {} (in dry-run mode)", composer_json, using)
+            } else {
+                info!("Modifying {} using {}", composer_json, using)
+            }
+
+            let modified = handle_modify(composer_json, modify.as_deref(), &options)?;
+
+            if *print {
+                let pretty = serde_json::to_string_pretty(&modified)?;
+
+                println!("\n{}:\n{}", composer_json, pretty);
+            }
+        }
+        ModifyCommands::Diff { old, new, select_map, report_format } => {
+            let old = ParseFileType::ComposerJson._handle_parse_and_return::<ComposerJson>(old)?;
+            let new = ParseFileType::ComposerJson._handle_parse_and_return::<ComposerJson>(new)?;
+            let report = Report::new(&old, &new, *select_map);
+
+            match report_format {
+                Some(format) => println!("{}", report.render(*format)),
+                None => print!("{}", report.to_text()),
+            }
+        }
+        ModifyCommands::PurgePackage { composer_json, package, dry_run } => {
+            handle_purge_package(composer_json, package, dry_run)?;
+        }
+        ModifyCommands::RunAll { modify, composer_json, skip, dry_run, report_format, fail_fast } => {
+            let options = ModifyOptions {
+                dry_run: *dry_run,
+                report_format: *report_format,
+                ..Default::default()
+            };
+
+            let mut files = Vec::new();
+            let mut unparsed = Vec::new();
+
+            for path in composer_json.iter() {
+                if !Path::new(path).is_dir() {
+                    files.push(path.clone());
+                    continue;
+                }
+
+                for (file, parsed) in load_all(Path::new(path), skip)? {
+                    let file = file.to_string_lossy().to_string();
+
+                    match parsed {
+                        Ok(_) => files.push(file),
+                        Err(e) => unparsed.push(BatchResult { file, result: Err(e) }),
+                    }
+                }
+            }
+
+            return Ok(handle_modify_all(&files, unparsed, Some(modify), &options, fail_fast)?);
+        }
+    }
+
+    Ok(())
+}
\ No newline at end of file
@@ -1,9 +1,23 @@
-use std::collections::HashMap;
-
-use serde::{Deserialize, Serialize};
-use serde_json::Value;
+use std::cmp::Ordering;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::OnceLock;
+
+use chrono::{NaiveDate, NaiveDateTime};
+use indexmap::IndexMap;
+use regex::Regex;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::{Map, Value};
+use crate::constraint::{restyle, satisfies, ConstraintStyle};
+use crate::modify_composer_json::PackagePattern;
 use crate::parse_handler::{ParseFile,ParseFileType};
 
+/// A `composer.json` file.
+///
+/// Fields are declared in the order of the composer.json schema, which is the order Composer itself
+/// writes them in, so a serialized file does not change order when Composer later rewrites it.
+///
+/// Reference: [The composer.json schema](https://getcomposer.org/doc/04-schema.md).
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ComposerJson {
     /// The name of the package. It consists of vendor name and project name, separated by `/`.
@@ -127,8 +141,7 @@ pub struct ComposerJson {
     ///
     /// Reference: [The composer.json schema (time)](https://getcomposer.org/doc/04-schema.md#time).
     #[serde(skip_serializing_if = "Option::is_none")]
-    // FIXME: Should be an optional date-time!
-    pub time: Option<String>,
+    pub time: Option<ReleaseTime>,
 
     /// The license of the package. This can be either a string or an array of strings.
     ///
@@ -336,7 +349,7 @@ pub struct ComposerJson {
     ///
     /// Reference: [The composer.json schema (include-path)](https://getcomposer.org/doc/04-schema.md#include-path).
     #[deprecated]
-    #[serde(rename = "target-dir")]
+    #[serde(rename = "include-path")]
     #[serde(skip_serializing_if = "Option::is_none")]
     pub include_path: Option<Vec<String>>,
 
@@ -391,6 +404,7 @@ pub struct ComposerJson {
     /// - stable
     ///
     /// Reference: [The composer.json schema (minimum stability)](https://getcomposer.org/doc/04-schema.md#minimum-stability).
+    #[serde(rename = "minimum-stability")]
     #[serde(skip_serializing_if = "Option::is_none")]
     pub minimum_stability: Option<Stability>,
 
@@ -402,6 +416,7 @@ pub struct ComposerJson {
     /// Use `"prefer-stable": true` to enable.
     ///
     /// Reference: [The composer.json schema (prefer stable)](https://getcomposer.org/doc/04-schema.md#prefer-stable).
+    #[serde(rename = "prefer-stable")]
     #[serde(skip_serializing_if = "Option::is_none")]
     pub prefer_stable: Option<bool>, // root-only
 
@@ -622,6 +637,11 @@ pub struct ComposerJson {
     #[serde(rename = "non-feature-branches")]
     #[serde(skip_serializing_if = "Option::is_none")]
     pub non_feature_branches: Option<Vec<String>>,
+
+    /// Top-level keys not modelled above, such as custom fields or fields from newer versions of
+    /// the schema, kept as they are so they survive a round-trip. Written after all known fields.
+    #[serde(flatten)]
+    pub unknown: IndexMap<String, Value>,
 }
 
 impl ParseFile for ComposerJson {
@@ -630,33 +650,581 @@ impl ParseFile for ComposerJson {
     }
 }
 
-/// Marks a field as only available in the root-level `composer.json` file.
-///
-/// The root package is the package defined by the `composer.json` at the root
-/// of your project.
-/// It is the main `composer.json` that defines your project requirements.
-///
-/// Certain fields only apply when in the root package context.
-/// One example of this is the `config` field. Only the root package can
-/// define configuration. The config of dependencies is ignored.
-/// This makes the `config` field `root-only`.
+impl ComposerJson {
+    /// The platform packages (PHP and extensions) this package effectively requires.
+    ///
+    /// Starts from the platform packages listed in `require` and layers the versions faked
+    /// through `config.platform` on top of them, since those take precedence when Composer
+    /// resolves dependencies.
+    ///
+    /// Reference: [Config (platform)](https://getcomposer.org/doc/06-config.md#platform).
+    pub fn effective_platform(&self) -> IndexMap<PlatformPackage, PlatformConstraint> {
+        let mut required: Vec<(&String, &String)> = self.package_links.require.iter()
+            .filter(|(name, _)| is_platform_package(name))
+            .collect();
+        required.sort();
+
+        let mut platform: IndexMap<PlatformPackage, PlatformConstraint> = required.into_iter()
+            .map(|(name, constraint)| (name.clone(), constraint.clone()))
+            .collect();
+
+        if let Some(faked) = self.config.as_ref().and_then(|c| c.platform.as_ref()) {
+            let mut faked: Vec<(&String, &String)> = faked.iter().collect();
+            faked.sort();
+
+            for (name, version) in faked {
+                platform.insert(name.clone(), version.clone());
+            }
+        }
+
+        platform
+    }
+
+    /// The `minimum-stability` of this package, which defaults to `stable` when not set.
+    pub fn effective_minimum_stability(&self) -> Stability {
+        self.minimum_stability.unwrap_or(Stability::Stable)
+    }
+
+    /// The `require` entries whose package name matches `pattern`, sorted by package name.
+    ///
+    /// Useful for previewing which packages a modify directive using `pattern` would touch.
+    pub fn require_entries_matching(&self, pattern: &PackagePattern) -> Vec<(&str, &str)> {
+        let mut entries: Vec<(&str, &str)> = self.package_links.require.iter()
+            .filter(|(name, _)| pattern.matches(name))
+            .map(|(name, constraint)| (name.as_str(), constraint.as_str()))
+            .collect();
+        entries.sort();
+
+        entries
+    }
+
+    /// Platform packages in `require` whose constraint is satisfied by the version faked for
+    /// them through `config.platform`, making the requirement redundant for local development.
+    ///
+    /// Requirements whose constraint or faked version cannot be parsed are never reported.
+    pub fn redundant_platform_requirements(&self) -> Vec<PlatformPackage> {
+        let platform = match self.config.as_ref().and_then(|c| c.platform.as_ref()) {
+            Some(platform) => platform,
+            None => return Vec::new(),
+        };
+
+        let mut redundant: Vec<PlatformPackage> = self.package_links.require.iter()
+            .filter(|(name, constraint)| {
+                is_platform_package(name) && match platform.get(*name) {
+                    Some(version) => satisfies(version, constraint).unwrap_or(false),
+                    None => false,
+                }
+            })
+            .map(|(name, _)| name.clone())
+            .collect();
+        redundant.sort();
+
+        redundant
+    }
+
+    /// Removes the requirements reported by [ComposerJson::redundant_platform_requirements],
+    /// returning the removed package names.
+    pub fn prune_platform_duplicates(&mut self) -> Vec<PlatformPackage> {
+        let redundant = self.redundant_platform_requirements();
+
+        for name in redundant.iter() {
+            self.package_links.require.shift_remove(name);
+        }
+
+        redundant
+    }
+
+    /// Checks the platform packages of `require`, and of `require-dev` if `include_dev` is set,
+    /// against the versions faked for them through `config.platform`, the way Composer's
+    /// `check-platform-reqs` checks them against the actual platform.
+    ///
+    /// Returns each platform package with its constraint and the outcome, in the order required.
+    ///
+    /// Reference: [check-platform-reqs](https://getcomposer.org/doc/03-cli.md#check-platform-reqs).
+    pub fn check_platform_requirements(&self, include_dev: bool) -> Vec<(&str, &str, PlatformCheck)> {
+        let platform = self.config.as_ref().and_then(|c| c.platform.as_ref());
+        let mut links = vec![&self.package_links.require];
+
+        if include_dev {
+            links.extend(self.package_links.require_dev.as_ref());
+        }
+
+        links.into_iter()
+            .flatten()
+            .filter(|(name, _)| is_platform_package(name))
+            .map(|(name, constraint)| {
+                let check = match platform.and_then(|p| p.get(name)) {
+                    None => PlatformCheck::Missing,
+                    Some(version) => match satisfies(version, constraint) {
+                        Ok(true) => PlatformCheck::Satisfied(version.clone()),
+                        Ok(false) => PlatformCheck::Unsatisfied(version.clone()),
+                        Err(e) => PlatformCheck::Invalid(e.to_string()),
+                    },
+                };
+
+                (name.as_str(), constraint.as_str(), check)
+            })
+            .collect()
+    }
+
+    /// Sorts the packages of `require` and `require-dev` by [compare_package_names], as Composer
+    /// does with `config.sort-packages` enabled.
+    ///
+    /// Reference: [sort-packages](https://getcomposer.org/doc/06-config.md#sort-packages).
+    pub fn sort_packages(&mut self) {
+        self.package_links.require.sort_by(|a, _, b, _| compare_package_names(a, b));
+
+        if let Some(require_dev) = self.package_links.require_dev.as_mut() {
+            require_dev.sort_by(|a, _, b, _| compare_package_names(a, b));
+        }
+    }
+
+    /// All PSR-4 and PSR-0 namespace prefixes declared in `autoload`, followed by those
+    /// declared in `autoload-dev` if `include_dev` is set.
+    pub fn autoload_namespaces(&self, include_dev: bool) -> Vec<String> {
+        let mut autoloads = vec![self.autoload.as_ref()];
+
+        if include_dev {
+            autoloads.push(self.autoload_dev.as_ref());
+        }
+
+        let mut namespaces: Vec<String> = Vec::new();
+
+        for autoload in autoloads.into_iter().flatten() {
+            for map in [&autoload.psr_4, &autoload.psr_0].into_iter().flatten() {
+                let mut prefixes: Vec<&String> = map.keys().collect();
+                prefixes.sort();
+
+                for prefix in prefixes {
+                    if !namespaces.contains(prefix) {
+                        namespaces.push(prefix.clone());
+                    }
+                }
+            }
+        }
+
+        namespaces
+    }
+
+    /// Whether this is a library, which is also the case when no `type` is given.
+    pub fn is_library(&self) -> bool {
+        matches!(self.package_type, None | Some(PackageType::Library))
+    }
+
+    /// Whether this is a project, e.g. an application rather than a library.
+    pub fn is_project(&self) -> bool {
+        matches!(self.package_type, Some(PackageType::Project))
+    }
+
+    /// Whether this is a metapackage, i.e. a package containing only requirements.
+    pub fn is_metapackage(&self) -> bool {
+        matches!(self.package_type, Some(PackageType::Metapackage))
+    }
+
+    /// Whether this is a Composer plugin.
+    pub fn is_plugin(&self) -> bool {
+        matches!(self.package_type, Some(PackageType::ComposerPlugin))
+    }
+
+    /// Applies Composer's documented normalizations that are enabled in `options`.
+    pub fn normalize(&mut self, options: &NormalizeOptions) {
+        if options.explicit_type && self.package_type.is_none() {
+            self.package_type = Some(PackageType::Library);
+        }
+
+        if options.lowercase_name {
+            self.name = self.name.to_lowercase();
+        }
+
+        if options.omit_version {
+            self.version = None;
+        }
+
+        if options.prune_empty {
+            self.prune_empty();
+        }
+    }
+
+    /// Drops optional lists and maps that are present but empty.
+    fn prune_empty(&mut self) {
+        fn none_if_empty<T>(field: &mut Option<T>, is_empty: impl Fn(&T) -> bool) {
+            if field.as_ref().is_some_and(is_empty) {
+                *field = None;
+            }
+        }
+
+        none_if_empty(&mut self.keywords, Vec::is_empty);
+        none_if_empty(&mut self.authors, Vec::is_empty);
+        none_if_empty(&mut self.funding, Vec::is_empty);
+        none_if_empty(&mut self.repositories, Vec::is_empty);
+        none_if_empty(&mut self.bin, Vec::is_empty);
+        none_if_empty(&mut self.non_feature_branches, Vec::is_empty);
+        none_if_empty(&mut self.package_links.require_dev, IndexMap::is_empty);
+        none_if_empty(&mut self.package_links.conflict, IndexMap::is_empty);
+        none_if_empty(&mut self.package_links.replace, IndexMap::is_empty);
+        none_if_empty(&mut self.package_links.provide, IndexMap::is_empty);
+        none_if_empty(&mut self.package_links.suggest, IndexMap::is_empty);
+        none_if_empty(&mut self.extra, |extra| extra.as_object().is_some_and(Map::is_empty));
+    }
+
+    /// Rewrites the constraints in `require` and `require-dev` to `policy` where they are plain
+    /// single-version constraints, see [restyle].
+    pub fn apply_version_policy(&mut self, policy: ConstraintStyle) {
+        let links = std::iter::once(&mut self.package_links.require)
+            .chain(self.package_links.require_dev.as_mut());
+
+        for packages in links {
+            for constraint in packages.values_mut() {
+                if let Some(restyled) = restyle(constraint, policy) {
+                    *constraint = restyled;
+                }
+            }
+        }
+    }
+
+    /// Moves the PSR-0 mappings of `autoload` that have a direct PSR-4 equivalent to PSR-4, returning
+    /// the namespace prefixes that were left as PSR-0.
+    ///
+    /// Only namespace prefixes, ending in `\\`, are converted. As PSR-0 includes the namespace in
+    /// the path, `"Foo\\": "src/"` becomes `"Foo\\": "src/Foo/"`, unless `target-dir` is the
+    /// namespace directory. `target-dir` is cleared once no PSR-0 mappings remain.
+    ///
+    /// Reference: [The composer.json schema (PSR-4)](https://getcomposer.org/doc/04-schema.md#psr-4).
+    #[allow(deprecated)]
+    pub fn convert_psr0_to_psr4(&mut self) -> Vec<String> {
+        let target_dir = self.target_dir.as_deref().map(|dir| dir.trim_matches('/').to_string());
+        let autoload = match self.autoload.as_mut() {
+            Some(autoload) => autoload,
+            None => return Vec::new(),
+        };
+        let psr_0 = match autoload.psr_0.take() {
+            Some(psr_0) => psr_0,
+            None => return Vec::new(),
+        };
+
+        let psr_4 = autoload.psr_4.get_or_insert_with(IndexMap::new);
+        let mut kept = IndexMap::new();
+
+        for (prefix, dir) in psr_0 {
+            let namespace_dir = prefix.trim_end_matches('\\').replace('\\', "/");
+            let path = match &target_dir {
+                Some(target_dir) if *target_dir == namespace_dir => Some(dir.clone()),
+                Some(_) => None,
+                None if dir.is_empty() => Some(format!("{}/", namespace_dir)),
+                None => Some(format!("{}/{}/", dir.trim_end_matches('/'), namespace_dir)),
+            };
+
+            match path {
+                Some(path) if prefix.ends_with('\\') && prefix.len() > 1 && !psr_4.contains_key(&prefix) => {
+                    psr_4.insert(prefix, path);
+                },
+                _ => {
+                    kept.insert(prefix, dir);
+                },
+            }
+        }
+
+        let unconverted: Vec<String> = kept.keys().cloned().collect();
+
+        if psr_4.is_empty() {
+            autoload.psr_4 = None;
+        }
+
+        if kept.is_empty() {
+            self.target_dir = None;
+        } else {
+            autoload.psr_0 = Some(kept);
+        }
+
+        unconverted
+    }
+
+    /// Normalizes the PSR-4 mappings of `autoload` and `autoload-dev`, so each namespace prefix
+    /// ends with a single `\\` and each path with a single `/`, keeping the order of the mappings.
+    ///
+    /// The fallback prefix `""` and empty paths, meaning the package root, are left as they are,
+    /// as is a prefix whose normalized form is already mapped. Returns a description of each fixed
+    /// entry, e.g. `autoload.psr-4 "Foo" -> "Foo\\"`.
+    ///
+    /// Reference: [The composer.json schema (PSR-4)](https://getcomposer.org/doc/04-schema.md#psr-4).
+    pub fn normalize_psr4(&mut self) -> Vec<String> {
+        let mut fixed = Vec::new();
+
+        for (path, autoload) in [("autoload.psr-4", &mut self.autoload), ("autoload-dev.psr-4", &mut self.autoload_dev)] {
+            let psr_4 = match autoload.as_mut().and_then(|a| a.psr_4.as_mut()) {
+                Some(psr_4) => psr_4,
+                None => continue,
+            };
+            let prefixes: Vec<String> = psr_4.keys().cloned().collect();
+            let mut normalized = IndexMap::with_capacity(psr_4.len());
+
+            for (prefix, dir) in std::mem::take(psr_4) {
+                let new_prefix = match prefix.is_empty() {
+                    true => prefix.clone(),
+                    false => format!("{}\\", prefix.trim_end_matches('\\')),
+                };
+                let new_prefix = match new_prefix != prefix && (prefixes.contains(&new_prefix) || normalized.contains_key(&new_prefix)) {
+                    true => prefix.clone(),
+                    false => new_prefix,
+                };
+                let new_dir = match dir.is_empty() {
+                    true => dir.clone(),
+                    false => format!("{}/", dir.trim_end_matches('/')),
+                };
+
+                if new_prefix != prefix {
+                    fixed.push(format!("{} \"{}\" -> \"{}\"", path, prefix, new_prefix));
+                }
+
+                if new_dir != dir {
+                    fixed.push(format!("{} \"{}\": \"{}\" -> \"{}\"", path, new_prefix, dir, new_dir));
+                }
+
+                normalized.insert(new_prefix, new_dir);
+            }
+
+            *psr_4 = normalized;
+        }
+
+        fixed
+    }
+
+    /// Whether the `conflict` links would block installing `version` of the package `name`.
+    ///
+    /// Conflict constraints or versions that cannot be parsed are not considered conflicting.
+    pub fn conflicts_with(&self, name: &str, version: &str) -> bool {
+        self.package_links.conflict.iter()
+            .flatten()
+            .filter(|(package, _)| package.eq_ignore_ascii_case(name))
+            .any(|(_, constraint)| satisfies(version, constraint).unwrap_or(false))
+    }
+
+    /// The `require-dev` links, inserting an empty map if there are none.
+    ///
+    /// An inserted map that stays empty can be removed again with [NormalizeOptions::prune_empty].
+    pub fn require_dev_or_default(&mut self) -> &mut IndexMap<String, String> {
+        self.package_links.require_dev.get_or_insert_with(IndexMap::new)
+    }
+
+    /// Requires `name` with `constraint` in `require-dev` if `dev` is set, or in `require` otherwise,
+    /// adding the package or updating its constraint as needed.
+    pub fn ensure_require(&mut self, name: &str, constraint: &str, dev: bool) -> EnsureResult {
+        let packages = match dev {
+            true => self.require_dev_or_default(),
+            false => &mut self.package_links.require,
+        };
+
+        match packages.insert(name.to_string(), constraint.to_string()) {
+            None => EnsureResult::Added,
+            Some(old) if old == constraint => EnsureResult::Unchanged,
+            Some(_) => EnsureResult::Updated,
+        }
+    }
+
+    /// Removes the packages matching `pattern` from every package link section, dropping optional
+    /// sections that end up empty, and returns the names of the sections packages were removed from.
+    pub fn purge_package(&mut self, pattern: &PackagePattern) -> Vec<&'static str> {
+        let links = &mut self.package_links;
+        let mut purged = Vec::new();
+
+        let before = links.require.len();
+        links.require.retain(|name, _| !pattern.matches(name));
+
+        if links.require.len() != before {
+            purged.push("require");
+        }
+
+        let optional = [
+            ("require-dev", &mut links.require_dev),
+            ("conflict", &mut links.conflict),
+            ("replace", &mut links.replace),
+            ("provide", &mut links.provide),
+            ("suggest", &mut links.suggest),
+        ];
+
+        for (section, packages) in optional {
+            if let Some(map) = packages.as_mut() {
+                let before = map.len();
+                map.retain(|name, _| !pattern.matches(name));
+
+                if map.len() != before {
+                    purged.push(section);
+                }
+
+                if map.is_empty() {
+                    *packages = None;
+                }
+            }
+        }
+
+        purged
+    }
+}
+
+/// The normalizations applied by [ComposerJson::normalize], each of which can be toggled individually.
+#[derive(Debug, Clone, Default)]
+pub struct NormalizeOptions {
+    /// Set `type` to `library` when it is not given, making Composer's default explicit.
+    pub explicit_type: bool,
+
+    /// Lowercase `name`, as package names are case-insensitive.
+    pub lowercase_name: bool,
+
+    /// Remove `version`, which Composer recommends omitting since it is inferred from the VCS.
+    pub omit_version: bool,
+
+    /// Remove optional lists and maps that are empty, e.g. `"require-dev": {}`.
+    pub prune_empty: bool,
+}
+
+/// Outcome of [ComposerJson::ensure_require].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EnsureResult {
+    /// The package was not required before.
+    Added,
+
+    /// The package was required with a different constraint.
+    Updated,
+
+    /// The package was already required with the same constraint.
+    Unchanged,
+}
+
+/// Outcome of checking a platform requirement, see [ComposerJson::check_platform_requirements].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PlatformCheck {
+    /// The version faked in `config.platform` satisfies the constraint.
+    Satisfied(Version),
+
+    /// The version faked in `config.platform` does not satisfy the constraint.
+    Unsatisfied(Version),
+
+    /// No version is faked for the package in `config.platform`.
+    Missing,
+
+    /// The constraint or the faked version cannot be parsed.
+    Invalid(String),
+}
+
+/// Whether `name` refers to a platform package, i.e. PHP itself, a PHP extension,
+/// a system library or Composer's own runtime/plugin API, rather than an installable package.
 ///
-/// **Note:**
-/// A package can be the root package or not, depending on the context.
-/// For example, if your project depends on the `monolog` library,
-/// your project is the root package.
-/// However, if you clone `monolog` from GitHub in order to fix a bug
-/// in it, then `monolog` is the root package.
+/// Reference: [Package links (platform packages)](https://getcomposer.org/doc/01-basic-usage.md#platform-packages).
+pub fn is_platform_package(name: &str) -> bool {
+    static PLATFORM_PACKAGE: OnceLock<Regex> = OnceLock::new();
+
+    PLATFORM_PACKAGE
+        .get_or_init(|| Regex::new(
+            r"(?i)^(?:php(?:-64bit|-ipv6|-zts|-debug)?|hhvm|(?:ext|lib)-[a-z0-9](?:[_.-]?[a-z0-9]+)*|composer(?:-(?:plugin|runtime)-api)?)$"
+        ).unwrap())
+        .is_match(name)
+}
+
+/// The canonical form of a package name: trimmed and lowercased, as package names are case-insensitive.
+pub fn canonical_name(name: &str) -> String {
+    name.trim().to_lowercase()
+}
+
+/// Compares two package names the way Composer orders them when `sort-packages` is enabled:
+/// platform packages first (`php`, then `hhvm`, `ext-*`, `lib-*` and any other platform package),
+/// followed by all other packages in alphabetical order.
 ///
-/// Reference: [Root Package](https://getcomposer.org/doc/04-schema.md#root-package).
-trait RootOnly {}
+/// Reference: [Config (sort-packages)](https://getcomposer.org/doc/06-config.md#sort-packages).
+pub fn compare_package_names(a: &str, b: &str) -> Ordering {
+    fn sort_group(name: &str) -> u8 {
+        if !is_platform_package(name) {
+            return 5;
+        }
+
+        match name {
+            n if n.starts_with("php") => 0,
+            n if n.starts_with("hhvm") => 1,
+            n if n.starts_with("ext") => 2,
+            n if n.starts_with("lib") => 3,
+            _ => 4,
+        }
+    }
+
+    (sort_group(a), a.to_lowercase()).cmp(&(sort_group(b), b.to_lowercase()))
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
 pub enum OneOrMany<T> {
     One(T),
     Many(Vec<T>),
 }
 
+impl<T> OneOrMany<T> {
+    pub fn as_slice(&self) -> &[T] {
+        match self {
+            OneOrMany::One(value) => std::slice::from_ref(value),
+            OneOrMany::Many(values) => values,
+        }
+    }
+}
+
+/// Serializes a [OneOrMany] as an array, also when it was given as a single value.
+fn serialize_as_many<S, T>(value: &Option<OneOrMany<T>>, serializer: S) -> Result<S::Ok, S::Error>
+    where S: Serializer, T: Serialize
+{
+    match value {
+        Some(v) => v.as_slice().serialize(serializer),
+        None => serializer.serialize_none(),
+    }
+}
+
+/// Release date of a version, as given in the `time` field.
+///
+/// Either a date (`YYYY-MM-DD`) or a date and time (`YYYY-MM-DD HH:MM:SS`), serialized in the form it was given in.
+///
+/// Reference: [The composer.json schema (time)](https://getcomposer.org/doc/04-schema.md#time).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReleaseTime {
+    Date(NaiveDate),
+    DateTime(NaiveDateTime),
+}
+
+impl ReleaseTime {
+    const DATE_FORMAT: &'static str = "%Y-%m-%d";
+    const DATE_TIME_FORMAT: &'static str = "%Y-%m-%d %H:%M:%S";
+}
+
+impl fmt::Display for ReleaseTime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReleaseTime::Date(date) => write!(f, "{}", date.format(ReleaseTime::DATE_FORMAT)),
+            ReleaseTime::DateTime(date_time) => write!(f, "{}", date_time.format(ReleaseTime::DATE_TIME_FORMAT)),
+        }
+    }
+}
+
+impl FromStr for ReleaseTime {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Ok(date_time) = NaiveDateTime::parse_from_str(s, ReleaseTime::DATE_TIME_FORMAT) {
+            return Ok(ReleaseTime::DateTime(date_time));
+        }
+
+        NaiveDate::parse_from_str(s, ReleaseTime::DATE_FORMAT)
+            .map(ReleaseTime::Date)
+            .map_err(|_| format!("invalid time \"{}\", expected YYYY-MM-DD or YYYY-MM-DD HH:MM:SS", s))
+    }
+}
+
+impl Serialize for ReleaseTime {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error> where S: Serializer {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for ReleaseTime {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error> where D: Deserializer<'de> {
+        String::deserialize(deserializer)?.parse().map_err(serde::de::Error::custom)
+    }
+}
+
 /// The type of the package. It defaults to `library`.
 ///
 /// Package types are used for custom installation logic. If you have a package that needs some special logic, you can define a custom type.
@@ -681,10 +1249,9 @@ pub enum OneOrMany<T> {
 /// It is recommended to omit this field and have it default to `library`.
 ///
 /// Reference: [The composer.json schema (type)](https://getcomposer.org/doc/04-schema.md#type).
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum PackageType {
     /// This is the default. It will copy the files to `vendor`.
-    #[serde(rename = "library")]
     Library,
 
     /// This denotes a project rather than a library.
@@ -692,18 +1259,15 @@ pub enum PackageType {
     /// CMSs like the (Silverstripe installer)[https://github.com/silverstripe/silverstripe-installer] or full fledged
     /// applications distributed as packages.
     /// This can for example be used by IDEs to provide listings of projects to initialize when creating a new workspace.
-    #[serde(rename = "project")]
     Project,
 
     /// An empty package that contains requirements and will trigger their installation,
     /// but contains no files and will not write anything to the filesystem. As such,
     /// it does not require a dist or source key to be installable.
-    #[serde(rename = "metapackage")]
     Metapackage,
 
     /// A package of type `composer-plugin` may provide an installer for other packages
     /// that have a custom type. Read more in the [dedicated article](https://getcomposer.org/doc/articles/custom-installers.md).
-    #[serde(rename = "composer-plugin")]
     ComposerPlugin,
 
     /// Custom package type.
@@ -711,6 +1275,49 @@ pub enum PackageType {
     Custom(String),
 }
 
+impl PackageType {
+    /// The name of the type, as written in the `type` field.
+    pub fn as_str(&self) -> &str {
+        match self {
+            PackageType::Library => "library",
+            PackageType::Project => "project",
+            PackageType::Metapackage => "metapackage",
+            PackageType::ComposerPlugin => "composer-plugin",
+            PackageType::Custom(name) => name,
+        }
+    }
+}
+
+impl From<&str> for PackageType {
+    fn from(name: &str) -> Self {
+        match name {
+            "library" => PackageType::Library,
+            "project" => PackageType::Project,
+            "metapackage" => PackageType::Metapackage,
+            "composer-plugin" => PackageType::ComposerPlugin,
+            custom => PackageType::Custom(custom.to_string()),
+        }
+    }
+}
+
+impl fmt::Display for PackageType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl Serialize for PackageType {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error> where S: Serializer {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for PackageType {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error> where D: Deserializer<'de> {
+        Ok(PackageType::from(String::deserialize(deserializer)?.as_str()))
+    }
+}
+
 /// All of the following take an object which maps package names to versions of the package via version constraints.
 /// Read more about versions [here](https://getcomposer.org/doc/articles/versions.md).
 ///
@@ -800,7 +1407,7 @@ pub struct PackageLinks {
     /// Map of packages required by this package. The package will not be installed unless those requirements can be met.
     ///
     /// See [The composer.json schema](https://getcomposer.org/doc/04-schema.md#require) for details.
-    pub require: HashMap<String, String>,
+    pub require: IndexMap<String, String>,
 
     /// Map of packages required for developing this package, or running tests, etc.
     /// The dev requirements of the root package are installed by default.
@@ -809,7 +1416,7 @@ pub struct PackageLinks {
     /// See [The composer.json schema](https://getcomposer.org/doc/04-schema.md#require-dev) for details.
     #[serde(rename = "require-dev")]
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub require_dev: Option<HashMap<String, String>>, // root-only
+    pub require_dev: Option<IndexMap<String, String>>, // root-only
 
     /// Map of packages that conflict with this version of this package.
     /// They will not be allowed to be installed together with your package.
@@ -820,7 +1427,7 @@ pub struct PackageLinks {
     ///
     /// See [The composer.json schema](https://getcomposer.org/doc/04-schema.md#conflict) for details.
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub conflict: Option<HashMap<String, String>>,
+    pub conflict: Option<IndexMap<String, String>>,
 
     /// Map of packages that are replaced by this package.
     /// This allows you to fork a package, publish it under a different name with its own version numbers,
@@ -839,7 +1446,7 @@ pub struct PackageLinks {
     ///
     /// See [The composer.json schema](https://getcomposer.org/doc/04-schema.md#replace) for details.
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub replace: Option<HashMap<String, String>>,
+    pub replace: Option<IndexMap<String, String>>,
 
     /// Map of packages that are provided by this package.
     /// This is mostly useful for implementations of common interfaces.
@@ -855,7 +1462,7 @@ pub struct PackageLinks {
     ///
     /// See [The composer.json schema](https://getcomposer.org/doc/04-schema.md#provide) for details.
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub provide: Option<HashMap<String, String>>,
+    pub provide: Option<IndexMap<String, String>>,
 
     /// Suggested packages that can enhance or work well with this package.
     /// These are informational and are displayed after the package is installed, to give your users
@@ -875,7 +1482,7 @@ pub struct PackageLinks {
     ///
     /// See [The composer.json schema](https://getcomposer.org/doc/04-schema.md#suggest) for details.
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub suggest: Option<HashMap<String, String>>,
+    pub suggest: Option<IndexMap<String, String>>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -962,12 +1569,17 @@ pub struct Support {
 /// }
 /// ```
 ///
+/// Any `type` is accepted, and additional informational keys are kept as they are.
+///
 /// See [The composer.json schema](https://getcomposer.org/doc/04-schema.md#funding) for details.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Funding {
     #[serde(rename = "type")]
     pub platform: String,
     pub url: String,
+
+    #[serde(flatten)]
+    pub extra: IndexMap<String, Value>,
 }
 
 /// See [The composer.json schema](https://getcomposer.org/doc/04-schema.md#autoload) for details.
@@ -1013,7 +1625,7 @@ pub struct Autoload {
     /// See [The composer.json schema](https://getcomposer.org/doc/04-schema.md#psr-4) for details.
     #[serde(rename = "psr-4")]
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub psr_4: Option<HashMap<String, String>>,
+    pub psr_4: Option<IndexMap<String, String>>,
 
     /// Under the `psr-0` key you define a mapping from namespaces to paths, relative to the package root.
     /// Note that this also supports the PEAR-style non-namespaced convention.
@@ -1061,7 +1673,7 @@ pub struct Autoload {
     /// See [The composer.json schema](https://getcomposer.org/doc/04-schema.md#psr-0) for details.
     #[serde(rename = "psr-0")]
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub psr_0: Option<HashMap<String, String>>,
+    pub psr_0: Option<IndexMap<String, String>>,
 
     /// The `classmap` references are all combined, during install/update, into a single key => value array
     /// which may be found in the generated file `vendor/composer/autoload_classmap.php`.
@@ -1084,9 +1696,12 @@ pub struct Autoload {
     ///     ["src/addons/*/lib/", "3rd-party/*", "Something.php"]
     /// ```
     ///
+    /// A single string is tolerated as well, and written back as an array.
+    ///
     /// See [The composer.json schema](https://getcomposer.org/doc/04-schema.md#classmap) for details.
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub classmap: Option<Vec<String>>,
+    #[serde(serialize_with = "serialize_as_many")]
+    pub classmap: Option<OneOrMany<String>>,
 
     /// If you want to require certain files explicitly on every request then you can use the `files` autoloading mechanism.
     /// This is useful if your package includes PHP functions that cannot be autoloaded by PHP.
@@ -1110,9 +1725,12 @@ pub struct Autoload {
     /// If you want to achieve that we recommend you include your own functions before
     /// including Composer's `vendor/autoload.php`.
     ///
+    /// A single string is tolerated as well, and written back as an array.
+    ///
     /// See [The composer.json schema](https://getcomposer.org/doc/04-schema.md#files) for details.
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub files: Option<Vec<String>>,
+    #[serde(serialize_with = "serialize_as_many")]
+    pub files: Option<OneOrMany<String>>,
 
     /// If you want to exclude some files or folders from the classmap you can use the `exclude-from-classmap` property.
     /// This might be useful to exclude test classes in your live environment, for example, as those
@@ -1145,7 +1763,7 @@ pub struct Autoload {
 /// - stable
 ///
 /// See [The composer.json schema](https://getcomposer.org/doc/04-schema.md#minimum-stability) for details.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Stability {
     #[serde(rename = "dev")]
     Dev,
@@ -1156,6 +1774,7 @@ pub enum Stability {
     #[serde(rename = "beta")]
     Beta,
 
+    #[serde(rename = "RC")]
     RC,
 
     #[serde(rename = "stable")]
@@ -1179,12 +1798,44 @@ pub enum Stability {
 /// article for more details and to see how to change this behavior.
 ///
 /// Reference: [Repository](https://getcomposer.org/doc/05-repositories.md#repository).
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Repository {
+    /// The type of the repository. Not given for the `{"packagist.org": false}` entry, see [Repository::packagist_org].
     #[serde(rename = "type")]
-    pub repository_type: RepositoryType,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub repository_type: Option<RepositoryType>,
 
+    /// Location of the repository. Not given for `package` repositories, which define their package inline.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "String::is_empty")]
     pub url: String,
+
+    /// Options for the underlying transport of a `composer` repository, e.g. `ssl` or `http` stream context options.
+    ///
+    /// Reference: [Composer (options)](https://getcomposer.org/doc/05-repositories.md#options).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub options: Option<Value>,
+
+    /// The inline `composer.json` object of the package of a `package` repository.
+    ///
+    /// Reference: [Package](https://getcomposer.org/doc/05-repositories.md#package-2).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub package: Option<Value>,
+
+    /// `false` for the `{"packagist.org": false}` entry, which disables the implicitly added Packagist repository.
+    ///
+    /// Reference: [Disabling Packagist.org](https://getcomposer.org/doc/05-repositories.md#disabling-packagist-org).
+    #[serde(rename = "packagist.org")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub packagist_org: Option<bool>,
+}
+
+impl Repository {
+    /// Whether this is the `{"packagist.org": false}` entry that disables Packagist.
+    pub fn disables_packagist(&self) -> bool {
+        self.packagist_org == Some(false)
+    }
 }
 
 /// Valid type for a repository.
@@ -1197,7 +1848,7 @@ pub struct Repository {
 /// - [path](https://getcomposer.org/doc/05-repositories.md#path)
 ///
 /// See: [Repositories](https://getcomposer.org/doc/05-repositories.md).
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum RepositoryType {
     /// The main repository type is the `composer` repository.
     /// It uses a single `packages.json` file that contains all of the package metadata.
@@ -1300,8 +1951,8 @@ type GitlabToken = String;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum GitlabTokenConfig {
-    Simple(HashMap<GitlabHost, GitlabToken>),
-    Detailed(HashMap<GitlabHost, GitlabTokenDetails>),
+    Simple(IndexMap<GitlabHost, GitlabToken>),
+    Detailed(IndexMap<GitlabHost, GitlabTokenDetails>),
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -1342,7 +1993,7 @@ pub struct BasicAuth {
     pub password: String,
 }
 
-pub type HttpBasicAuth = HashMap<Host, BasicAuth>;
+pub type HttpBasicAuth = IndexMap<Host, BasicAuth>;
 pub type PlatformPackage = String;
 pub type Version = String;
 pub type PlatformConstraint = String;
@@ -1365,11 +2016,15 @@ pub enum BinaryCompatibility {
     Proxy,
 }
 
+/// How to handle dirty updates in non-interactive mode: `"stash"`, or `true` or `false`.
+///
+/// Reference: [Config (discard-changes)](https://getcomposer.org/doc/06-config.md#discard-changes).
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum DiscardChangesMode {
     #[serde(rename = "stash")]
     Stash,
 
+    #[serde(untagged)]
     Toggle(bool),
 }
 
@@ -1383,15 +2038,26 @@ pub enum PlatformCheckMode {
     Toggle(bool),
 }
 
+/// Where to install packages from: `"source"`, `"dist"` or `"auto"` for all packages, or a map of
+/// package patterns to one of those.
+///
+/// Reference: [Config (preferred-install)](https://getcomposer.org/doc/06-config.md#preferred-install).
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum PreferredInstall {
     #[serde(rename = "dist")]
     Dist,
 
-    Map(HashMap<String, PackageSourceConfig>),
+    #[serde(rename = "source")]
+    Source,
+
+    #[serde(rename = "auto")]
+    Auto,
+
+    #[serde(untagged)]
+    Map(IndexMap<String, PackageSourceConfig>),
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Config {
     /// The timeout in seconds for process executions, defaults to 300 (5 minutes).
     /// The duration processes like git clones can run before Composer assumes they died out.
@@ -1413,7 +2079,7 @@ pub struct Config {
     /// Reference: [Config (process-timeout)](https://getcomposer.org/doc/06-config.md#process-timeout).
     #[serde(rename = "process-timeout")]
     #[serde(skip_serializing_if = "Option::is_none")]
-    process_timeout: Option<u32>,
+    pub process_timeout: Option<u32>,
 
     /// Defaults to `{}` which does not allow any plugins to be loaded.
     ///
@@ -1456,7 +2122,7 @@ pub struct Config {
     /// Reference: [Config (allow-plugins)](https://getcomposer.org/doc/06-config.md#allow-plugins).
     #[serde(rename = "allow-plugins")]
     #[serde(skip_serializing_if = "Option::is_none")]
-    allow_plugins: Option<AllowPlugins>,
+    pub allow_plugins: Option<AllowPlugins>,
 
     /// Defaults to `false`.
     /// If `true`, the Composer autoloader will also look for classes in the PHP include path.
@@ -1464,7 +2130,7 @@ pub struct Config {
     /// Reference: [Config (use-include-path)](https://getcomposer.org/doc/06-config.md#use-include-path).
     #[serde(rename = "use-include-path")]
     #[serde(skip_serializing_if = "Option::is_none")]
-    use_include_path: Option<bool>,
+    pub use_include_path: Option<bool>,
 
     /// Defaults to `dist` and can be any of `source`, `dist` or `auto`.
     /// This option allows you to set the install method Composer will prefer to use.
@@ -1499,14 +2165,14 @@ pub struct Config {
     /// Reference: [Config (preferred-install)](https://getcomposer.org/doc/06-config.md#preferred-install).
     #[serde(rename = "preferred-install")]
     #[serde(skip_serializing_if = "Option::is_none")]
-    preferred_install: Option<PreferredInstall>,
+    pub preferred_install: Option<PreferredInstall>,
 
     /// Security audit configuration options.
     ///
     /// Reference: [Config (audit)](https://getcomposer.org/doc/06-config.md#audit).
     #[serde(rename = "audit")]
     #[serde(skip_serializing_if = "Option::is_none")]
-    audit: Option<Audit>,
+    pub audit: Option<Audit>,
 
     /// When running Composer in a directory where there is no composer.json, if there is one
     /// present in a directory above Composer will by default ask you whether you want to use
@@ -1522,7 +2188,7 @@ pub struct Config {
     /// Reference: [Config (use-parent-dir)](https://getcomposer.org/doc/06-config.md#use-parent-dir).
     #[serde(rename = "use-parent-dir")]
     #[serde(skip_serializing_if = "Option::is_none")]
-    use_parent_dir: Option<bool>,
+    pub use_parent_dir: Option<bool>,
 
     /// What to do after prompting for authentication, one of:
     /// - `true` (always store),
@@ -1534,7 +2200,7 @@ pub struct Config {
     /// Reference: [Config (store-auths)](https://getcomposer.org/doc/06-config.md#store-auths).
     #[serde(rename = "store-auths")]
     #[serde(skip_serializing_if = "Option::is_none")]
-    store_auths: Option<ConfigStoreAuths>,
+    pub store_auths: Option<ConfigStoreAuths>,
 
     /// A list of protocols to use when cloning from github.com, in priority order.
     /// By default `git` is present but only if [secure-http](https://getcomposer.org/doc/06-config.md#secure-http)
@@ -1548,7 +2214,7 @@ pub struct Config {
     /// Reference: [Config (github-protocols)](https://getcomposer.org/doc/06-config.md#github-protocols).
     #[serde(rename = "github-protocols")]
     #[serde(skip_serializing_if = "Option::is_none")]
-    github_protocols: Option<Vec<String>>,
+    pub github_protocols: Option<Vec<String>>,
 
     /// A list of domain names and oauth keys.
     /// For example using `{"github.com": "oauthtoken"}` as the value of this
@@ -1560,7 +2226,7 @@ pub struct Config {
     /// Reference: [Config (github-oauth)](https://getcomposer.org/doc/06-config.md#github-oauth).
     #[serde(rename = "github-oauth")]
     #[serde(skip_serializing_if = "Option::is_none")]
-    github_oauth: Option<HashMap<String, String>>,
+    pub github_oauth: Option<IndexMap<String, String>>,
 
     /// Defaults to ["gitlab.com"].
     /// A list of domains of GitLab servers.
@@ -1569,7 +2235,7 @@ pub struct Config {
     /// Reference: [Config (gitlab-domains)](https://getcomposer.org/doc/06-config.md#gitlab-domains).
     #[serde(rename = "gitlab-domains")]
     #[serde(skip_serializing_if = "Option::is_none")]
-    gitlab_domains: Option<Vec<String>>,
+    pub gitlab_domains: Option<Vec<String>>,
 
     /// A list of domain names and oauth keys.
     ///
@@ -1584,7 +2250,7 @@ pub struct Config {
     /// Reference: [Config (gitlab-oauth)](https://getcomposer.org/doc/06-config.md#gitlab-oauth).
     #[serde(rename = "gitlab-oauth")]
     #[serde(skip_serializing_if = "Option::is_none")]
-    gitlab_oauth: Option<HashMap<String, String>>,
+    pub gitlab_oauth: Option<IndexMap<String, String>>,
 
     /// A list of domain names and private tokens.
     /// Private token can be either simple string, or array with username and token.
@@ -1601,7 +2267,7 @@ pub struct Config {
     /// Reference: [Config (gitlab-token)](https://getcomposer.org/doc/06-config.md#gitlab-token).
     #[serde(rename = "gitlab-token")]
     #[serde(skip_serializing_if = "Option::is_none")]
-    gitlab_token: Option<GitlabTokenConfig>,
+    pub gitlab_token: Option<GitlabTokenConfig>,
 
     /// A protocol to force use of when creating a repository URL for the `source` value
     /// of the package metadata.
@@ -1614,7 +2280,7 @@ pub struct Config {
     /// Reference: [Config (gitlab-protocol)](https://getcomposer.org/doc/06-config.md#gitlab-protocol).
     #[serde(rename = "gitlab-protocol")]
     #[serde(skip_serializing_if = "Option::is_none")]
-    gitlab_protocol: Option<GitProtocol>,
+    pub gitlab_protocol: Option<GitProtocol>,
 
     /// Defaults to `false`.
     /// If set to `true` all HTTPS URLs will be tried with HTTP instead and no
@@ -1626,7 +2292,7 @@ pub struct Config {
     /// Reference: [Config (disable-tls)](https://getcomposer.org/doc/06-config.md#disable-tls).
     #[serde(rename = "disable-tls")]
     #[serde(skip_serializing_if = "Option::is_none")]
-    disable_tls: Option<bool>,
+    pub disable_tls: Option<bool>,
 
     /// Defaults to `true`.
     /// If set to `true` only HTTPS URLs are allowed to be downloaded via Composer.
@@ -1636,7 +2302,7 @@ pub struct Config {
     /// Reference: [Config (secure-http)](https://getcomposer.org/doc/06-config.md#secure-http).
     #[serde(rename = "secure-http")]
     #[serde(skip_serializing_if = "Option::is_none")]
-    secure_http: Option<bool>,
+    pub secure_http: Option<bool>,
 
     /// A list of domain names and consumers.
     ///
@@ -1655,7 +2321,7 @@ pub struct Config {
     /// Reference: [Config (bitbucket-oauth)](https://getcomposer.org/doc/06-config.md#bitbucket-oauth).
     #[serde(rename = "bitbucket-oauth")]
     #[serde(skip_serializing_if = "Option::is_none")]
-    bitbucket_oauth: Option<HashMap<BitbucketHost, BitbucketOauth>>,
+    pub bitbucket_oauth: Option<IndexMap<BitbucketHost, BitbucketOauth>>,
 
     /// Location of Certificate Authority file on local filesystem.
     /// In PHP 5.6+ you should rather set this via openssl.cafile in php.ini,
@@ -1664,7 +2330,7 @@ pub struct Config {
     /// Reference: [Config ("cafile")](https://getcomposer.org/doc/06-config.md#"cafile").
     #[serde(rename = "cafile")]
     #[serde(skip_serializing_if = "Option::is_none")]
-    cafile: Option<String>,
+    pub cafile: Option<String>,
 
     /// If cafile is not specified or if the certificate is not found there,
     /// the directory pointed to by capath is searched for a suitable certificate.
@@ -1673,7 +2339,7 @@ pub struct Config {
     /// Reference: [Config ("capath")](https://getcomposer.org/doc/06-config.md#"capath").
     #[serde(rename = "capath")]
     #[serde(skip_serializing_if = "Option::is_none")]
-    capath: Option<String>,
+    pub capath: Option<String>,
 
     /// A list of domain names and username/passwords to authenticate against them.
     ///
@@ -1693,7 +2359,7 @@ pub struct Config {
     /// Reference: [Config (http-basic)](https://getcomposer.org/doc/06-config.md#http-basic).
     #[serde(rename = "http-basic")]
     #[serde(skip_serializing_if = "Option::is_none")]
-    http_basic: Option<HttpBasicAuth>,
+    pub http_basic: Option<HttpBasicAuth>,
 
     /// A list of domain names and tokens to authenticate against them.
     ///
@@ -1709,7 +2375,7 @@ pub struct Config {
     /// Reference: [Config ("bearer")](https://getcomposer.org/doc/06-config.md#"bearer").
     #[serde(rename = "bearer")]
     #[serde(skip_serializing_if = "Option::is_none")]
-    bearer: Option<HashMap<Host, String>>,
+    pub bearer: Option<IndexMap<Host, String>>,
 
     /// Lets you fake platform packages (PHP and extensions) so that you can emulate
     /// a production env or define your target platform in the config.
@@ -1744,7 +2410,7 @@ pub struct Config {
     /// Reference: [Config ("platform")](https://getcomposer.org/doc/06-config.md#"platform").
     #[serde(rename = "platform")]
     #[serde(skip_serializing_if = "Option::is_none")]
-    platform: Option<HashMap<PlatformPackage, PlatformConstraint>>,
+    pub platform: Option<IndexMap<PlatformPackage, PlatformConstraint>>,
 
     /// Defaults to `vendor`.
     /// You can install dependencies into a different directory if you want to.
@@ -1754,7 +2420,7 @@ pub struct Config {
     /// Reference: [Config (vendor-dir)](https://getcomposer.org/doc/06-config.md#vendor-dir).
     #[serde(rename = "vendor-dir")]
     #[serde(skip_serializing_if = "Option::is_none")]
-    vendor_dir: Option<String>,
+    pub vendor_dir: Option<String>,
 
     /// Defaults to `vendor/bin`.
     /// If a project includes binaries, they will be symlinked into this directory.
@@ -1762,7 +2428,7 @@ pub struct Config {
     /// Reference: [Config (bin-dir)](https://getcomposer.org/doc/06-config.md#bin-dir).
     #[serde(rename = "bin-dir")]
     #[serde(skip_serializing_if = "Option::is_none")]
-    bin_dir: Option<String>,
+    pub bin_dir: Option<String>,
 
     /// Defaults to `C:\Users\<user>\AppData\Roaming\Composer` on Windows,
     /// `$XDG_DATA_HOME/composer` on unix systems that follow the XDG Base Directory Specifications,
@@ -1773,7 +2439,7 @@ pub struct Config {
     /// Reference: [Config (data-dir)](https://getcomposer.org/doc/06-config.md#data-dir).
     #[serde(rename = "data-dir")]
     #[serde(skip_serializing_if = "Option::is_none")]
-    data_dir: Option<String>,
+    pub data_dir: Option<String>,
 
     /// Defaults to `C:\Users\<user>\AppData\Local\Composer` on Windows,
     /// `/Users/<user>/Library/Caches/composer` on macOS,
@@ -1785,7 +2451,7 @@ pub struct Config {
     /// Reference: [Config (cache-dir)](https://getcomposer.org/doc/06-config.md#cache-dir).
     #[serde(rename = "cache-dir")]
     #[serde(skip_serializing_if = "Option::is_none")]
-    cache_dir: Option<String>,
+    pub cache_dir: Option<String>,
 
     /// Defaults to `$cache-dir/files`.
     /// Stores the zip archives of packages.
@@ -1793,7 +2459,7 @@ pub struct Config {
     /// Reference: [Config (cache-files-dir)](https://getcomposer.org/doc/06-config.md#cache-files-dir).
     #[serde(rename = "cache-files-dir")]
     #[serde(skip_serializing_if = "Option::is_none")]
-    cache_files_dir: Option<String>,
+    pub cache_files_dir: Option<String>,
 
     /// Defaults to `$cache-dir/repo`.
     /// Stores repository metadata for the `composer` type and the VCS repos of
@@ -1802,7 +2468,7 @@ pub struct Config {
     /// Reference: [Config (cache-repo-dir)](https://getcomposer.org/doc/06-config.md#cache-repo-dir).
     #[serde(rename = "cache-repo-dir")]
     #[serde(skip_serializing_if = "Option::is_none")]
-    cache_repo_dir: Option<String>,
+    pub cache_repo_dir: Option<String>,
 
     /// Defaults to `$cache-dir/vcs`.
     /// Stores VCS clones for loading VCS repository metadata for the `git`/`hg`
@@ -1811,7 +2477,7 @@ pub struct Config {
     /// Reference: [Config (cache-vcs-dir)](https://getcomposer.org/doc/06-config.md#cache-vcs-dir).
     #[serde(rename = "cache-vcs-dir")]
     #[serde(skip_serializing_if = "Option::is_none")]
-    cache_vcs_dir: Option<String>,
+    pub cache_vcs_dir: Option<String>,
 
     /// Defaults to `15552000` (6 months).
     /// Composer caches all dist (zip, tar, ...) packages that it downloads.
@@ -1821,7 +2487,7 @@ pub struct Config {
     /// Reference: [Config (cache-files-ttl)](https://getcomposer.org/doc/06-config.md#cache-files-ttl).
     #[serde(rename = "cache-files-ttl")]
     #[serde(skip_serializing_if = "Option::is_none")]
-    cache_files_ttl: Option<u32>,
+    pub cache_files_ttl: Option<u32>,
 
     /// Defaults to `300MiB`. Composer caches all dist (zip, tar, ...) packages that it downloads.
     /// When the garbage collection is periodically ran, this is the maximum size the cache will
@@ -1831,7 +2497,7 @@ pub struct Config {
     /// Reference: [Config (cache-files-maxsize)](https://getcomposer.org/doc/06-config.md#cache-files-maxsize).
     #[serde(rename = "cache-files-maxsize")]
     #[serde(skip_serializing_if = "Option::is_none")]
-    cache_files_maxsize: Option<String>,
+    pub cache_files_maxsize: Option<String>,
 
     /// Defaults to `false`.
     /// Whether to use the Composer cache in read-only mode.
@@ -1839,7 +2505,7 @@ pub struct Config {
     /// Reference: [Config (cache-read-only)](https://getcomposer.org/doc/06-config.md#cache-read-only).
     #[serde(rename = "cache-read-only")]
     #[serde(skip_serializing_if = "Option::is_none")]
-    cache_read_only: Option<bool>,
+    pub cache_read_only: Option<bool>,
 
     /// Defaults to `auto`.
     /// Determines the compatibility of the binaries to be installed. If it is
@@ -1854,7 +2520,7 @@ pub struct Config {
     /// Reference: [Config (bin-compat)](https://getcomposer.org/doc/06-config.md#bin-compat).
     #[serde(rename = "bin-compat")]
     #[serde(skip_serializing_if = "Option::is_none")]
-    bin_compat: Option<BinaryCompatibility>,
+    pub bin_compat: Option<BinaryCompatibility>,
 
     /// Defaults to `true`.
     /// If `false`, the Composer autoloader will not be prepended to existing autoloaders.
@@ -1863,7 +2529,7 @@ pub struct Config {
     /// Reference: [Config (prepend-autoloader)](https://getcomposer.org/doc/06-config.md#prepend-autoloader).
     #[serde(rename = "prepend-autoloader")]
     #[serde(skip_serializing_if = "Option::is_none")]
-    prepend_autoloader: Option<bool>,
+    pub prepend_autoloader: Option<bool>,
 
     /// Defaults to `null`.
     /// When set to a non-empty string, this value will be used as a suffix for the
@@ -1874,7 +2540,7 @@ pub struct Config {
     /// Reference: [Config (autoloader-suffix)](https://getcomposer.org/doc/06-config.md#autoloader-suffix).
     #[serde(rename = "autoloader-suffix")]
     #[serde(skip_serializing_if = "Option::is_none")]
-    autoloader_suffix: Option<String>,
+    pub autoloader_suffix: Option<String>,
 
     /// Defaults to `false`.
     /// If `true`, always optimize when dumping the autoloader.
@@ -1882,7 +2548,7 @@ pub struct Config {
     /// Reference: [Config (optimize-autoloader)](https://getcomposer.org/doc/06-config.md#optimize-autoloader).
     #[serde(rename = "optimize-autoloader")]
     #[serde(skip_serializing_if = "Option::is_none")]
-    optimize_autoloader: Option<bool>,
+    pub optimize_autoloader: Option<bool>,
 
     /// Defaults to `false`.
     /// If `true`, the require command keeps packages sorted by name in
@@ -1891,7 +2557,7 @@ pub struct Config {
     /// Reference: [Config (sort-packages)](https://getcomposer.org/doc/06-config.md#sort-packages).
     #[serde(rename = "sort-packages")]
     #[serde(skip_serializing_if = "Option::is_none")]
-    sort_packages: Option<bool>,
+    pub sort_packages: Option<bool>,
 
     /// Defaults to `false`.
     /// If `true`, the Composer autoloader will only load classes from the classmap.
@@ -1900,7 +2566,7 @@ pub struct Config {
     /// Reference: [Config (classmap-authoritative)](https://getcomposer.org/doc/06-config.md#classmap-authoritative).
     #[serde(rename = "classmap-authoritative")]
     #[serde(skip_serializing_if = "Option::is_none")]
-    classmap_authoritative: Option<bool>,
+    pub classmap_authoritative: Option<bool>,
 
     /// Defaults to `false`.
     /// If `true`, the Composer autoloader will check for APCu and use it to
@@ -1909,7 +2575,7 @@ pub struct Config {
     /// Reference: [Config (apcu-autoloader)](https://getcomposer.org/doc/06-config.md#apcu-autoloader).
     #[serde(rename = "apcu-autoloader")]
     #[serde(skip_serializing_if = "Option::is_none")]
-    apcu_autoloader: Option<bool>,
+    pub apcu_autoloader: Option<bool>,
 
     /// Defaults to `["github.com"]`.
     /// A list of domains to use in github mode.
@@ -1918,7 +2584,7 @@ pub struct Config {
     /// Reference: [Config (github-domains)](https://getcomposer.org/doc/06-config.md#github-domains).
     #[serde(rename = "github-domains")]
     #[serde(skip_serializing_if = "Option::is_none")]
-    github_domains: Option<Vec<GithubHost>>,
+    pub github_domains: Option<Vec<GithubHost>>,
 
     /// Defaults to `true`.
     /// If `false`, the OAuth tokens created to access the github API will have
@@ -1927,7 +2593,7 @@ pub struct Config {
     /// Reference: [Config (github-expose-hostname)](https://getcomposer.org/doc/06-config.md#github-expose-hostname).
     #[serde(rename = "github-expose-hostname")]
     #[serde(skip_serializing_if = "Option::is_none")]
-    github_expose_hostname: Option<bool>,
+    pub github_expose_hostname: Option<bool>,
 
     /// Defaults to `true`.
     /// Similar to the `no-api` key on a specific repository, setting `use-github-api`
@@ -1940,7 +2606,7 @@ pub struct Config {
     /// Reference: [Config (use-github-api)](https://getcomposer.org/doc/06-config.md#use-github-api).
     #[serde(rename = "use-github-api")]
     #[serde(skip_serializing_if = "Option::is_none")]
-    use_github_api: Option<bool>,
+    pub use_github_api: Option<bool>,
 
     /// Defaults to `true`.
     /// Composer allows repositories to define a notification URL, so that they
@@ -1950,7 +2616,7 @@ pub struct Config {
     /// Reference: [Config (notify-on-install)](https://getcomposer.org/doc/06-config.md#notify-on-install).
     #[serde(rename = "notify-on-install")]
     #[serde(skip_serializing_if = "Option::is_none")]
-    notify_on_install: Option<bool>,
+    pub notify_on_install: Option<bool>,
 
     /// Defaults to `false` and can be any of `true`, `false` or `"stash"`.
     /// This option allows you to set the default style of handling dirty updates
@@ -1962,7 +2628,7 @@ pub struct Config {
     /// Reference: [Config (discard-changes)](https://getcomposer.org/doc/06-config.md#discard-changes).
     #[serde(rename = "discard-changes")]
     #[serde(skip_serializing_if = "Option::is_none")]
-    discard_changes: Option<DiscardChangesMode>,
+    pub discard_changes: Option<DiscardChangesMode>,
 
     /// Defaults to `tar`.
     /// Overrides the default format used by the archive command.
@@ -1970,7 +2636,7 @@ pub struct Config {
     /// Reference: [Config (archive-format)](https://getcomposer.org/doc/06-config.md#archive-format).
     #[serde(rename = "archive-format")]
     #[serde(skip_serializing_if = "Option::is_none")]
-    archive_format: Option<ArchiveFormat>,
+    pub archive_format: Option<ArchiveFormat>,
 
     /// Defaults to `.`.
     /// Default destination for archives created by the archive command.
@@ -1987,7 +2653,7 @@ pub struct Config {
     /// Reference: [Config (archive-dir)](https://getcomposer.org/doc/06-config.md#archive-dir).
     #[serde(rename = "archive-dir")]
     #[serde(skip_serializing_if = "Option::is_none")]
-    archive_dir: Option<String>,
+    pub archive_dir: Option<String>,
 
     /// Defaults to `true`.
     /// If set to `false`, Composer will not create `.htaccess` files in the
@@ -1996,7 +2662,7 @@ pub struct Config {
     /// Reference: [Config (htaccess-protect)](https://getcomposer.org/doc/06-config.md#htaccess-protect).
     #[serde(rename = "htaccess-protect")]
     #[serde(skip_serializing_if = "Option::is_none")]
-    htaccess_protect: Option<bool>,
+    pub htaccess_protect: Option<bool>,
 
     /// Defaults to `true`.
     /// If set to `false`, Composer will not create a `composer.lock` file and
@@ -2005,7 +2671,7 @@ pub struct Config {
     /// Reference: [Config (lock)](https://getcomposer.org/doc/06-config.md#lock).
     #[serde(rename = "lock")]
     #[serde(skip_serializing_if = "Option::is_none")]
-    lock: Option<bool>,
+    pub lock: Option<bool>,
 
     /// Defaults to `php-only` which only checks the PHP version.
     /// Set to `true` to also check the presence of extension.
@@ -2015,7 +2681,7 @@ pub struct Config {
     /// Reference: [Config (platform-check)](https://getcomposer.org/doc/06-config.md#platform-check).
     #[serde(rename = "platform-check")]
     #[serde(skip_serializing_if = "Option::is_none")]
-    platform_check: Option<PlatformCheckMode>,
+    pub platform_check: Option<PlatformCheckMode>,
 
     /// Defaults to `[]`.
     /// Lists domains which should be trusted/marked as using a secure Subversion/SVN transport.
@@ -2027,7 +2693,7 @@ pub struct Config {
     /// Reference: [Config (secure-svn-domains)](https://getcomposer.org/doc/06-config.md#secure-svn-domains).
     #[serde(rename = "secure-svn-domains")]
     #[serde(skip_serializing_if = "Option::is_none")]
-    secure_svn_domains: Option<Vec<Host>>,
+    pub secure_svn_domains: Option<Vec<Host>>,
 }
 
 /// What to do after prompting for authentication, one of:
@@ -2046,10 +2712,15 @@ pub enum ConfigStoreAuths {
     AskEveryTime,
 }
 
+/// The plugins Composer may execute code of, either all or none of them, or a map of package names
+/// or patterns like `my-org/*` to whether they are allowed.
+///
+/// Reference: [Config (allow-plugins)](https://getcomposer.org/doc/06-config.md#allow-plugins).
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
 pub enum AllowPlugins {
     Toggle(bool),
-    Map(HashMap<String, bool>),
+    Map(IndexMap<String, bool>),
 }
 
 /// Security audit configuration options.
@@ -2204,114 +2875,148 @@ pub enum PluginEvent {
     PrePoolCreate,
 }
 
+/// Scripts hooked into Composer events, as well as custom scripts runnable with `composer run-script`.
+///
+/// Reference: [Scripts](https://getcomposer.org/doc/articles/scripts.md).
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Scripts {
     /// Occurs before the `install` command is executed with a lock file present.
     #[serde(rename = "pre-install-cmd")]
-    pre_install_cmd: OneOrMany<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pre_install_cmd: Option<OneOrMany<String>>,
 
     /// Occurs after the `install` command has been executed with a lock file present.
     #[serde(rename = "post-install-cmd")]
-    post_install_cmd: OneOrMany<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    post_install_cmd: Option<OneOrMany<String>>,
 
     /// Occurs before the `update` command is executed, or before the install command is executed without a lock file present.
     #[serde(rename = "pre-update-cmd")]
-    pre_update_cmd: OneOrMany<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pre_update_cmd: Option<OneOrMany<String>>,
 
     /// Occurs after the `update` command has been executed, or after the install command has been executed without a lock file present.
     #[serde(rename = "post-update-cmd")]
-    post_update_cmd: OneOrMany<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    post_update_cmd: Option<OneOrMany<String>>,
 
     /// Occurs before the `status` command is executed.
     #[serde(rename = "pre-status-cmd")]
-    pre_status_cmd: OneOrMany<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pre_status_cmd: Option<OneOrMany<String>>,
 
     /// Occurs after the `status` command has been executed.
     #[serde(rename = "post-status-cmd")]
-    post_status_cmd: OneOrMany<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    post_status_cmd: Option<OneOrMany<String>>,
 
     /// Occurs before the `archive` command is executed.
     #[serde(rename = "pre-archive-cmd")]
-    pre_archive_cmd: OneOrMany<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pre_archive_cmd: Option<OneOrMany<String>>,
 
     /// Occurs after the `archive` command has been executed.
     #[serde(rename = "post-archive-cmd")]
-    post_archive_cmd: OneOrMany<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    post_archive_cmd: Option<OneOrMany<String>>,
 
     /// Occurs before the autoloader is dumped, either during `install`/`update`, or via the `dump-autoload` command.
     #[serde(rename = "pre-autoload-dump")]
-    pre_autoload_dump: OneOrMany<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pre_autoload_dump: Option<OneOrMany<String>>,
 
     /// Occurs after the autoloader has been dumped, either during `install`/`update`, or via the `dump-autoload` command.
     #[serde(rename = "post-autoload-dump")]
-    post_autoload_dump: OneOrMany<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    post_autoload_dump: Option<OneOrMany<String>>,
 
     /// Occurs after the root package has been installed during the `create-project` command (but before its dependencies are installed).
     #[serde(rename = "post-root-package-install")]
-    post_root_package_install: OneOrMany<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    post_root_package_install: Option<OneOrMany<String>>,
 
     /// Occurs after the `create-project` command has been executed.
     #[serde(rename = "post-create-project-cmd")]
-    post_create_project_cmd: OneOrMany<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    post_create_project_cmd: Option<OneOrMany<String>>,
 
     /// Occurs before the install/upgrade/.. operations are executed when installing a lock file.
     /// Plugins that need to hook into this event will need to be installed globally to be usable,
     /// as otherwise they would not be loaded yet when a fresh install of a project happens.
     #[serde(rename = "pre-operations-exec")]
-    pre_operations_exec: OneOrMany<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pre_operations_exec: Option<OneOrMany<String>>,
 
     /// Occurs before a package is installed.
     #[serde(rename = "pre-package-install")]
-    pre_package_install: OneOrMany<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pre_package_install: Option<OneOrMany<String>>,
 
     /// Occurs after a package has been installed.
     #[serde(rename = "post-package-install")]
-    post_package_install: OneOrMany<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    post_package_install: Option<OneOrMany<String>>,
 
     /// Occurs before a package is updated.
     #[serde(rename = "pre-package-update")]
-    pre_package_update: OneOrMany<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pre_package_update: Option<OneOrMany<String>>,
 
     /// Occurs after a package has been updated.
     #[serde(rename = "post-package-update")]
-    post_package_update: OneOrMany<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    post_package_update: Option<OneOrMany<String>>,
 
     /// Occurs before a package is uninstalled.
     #[serde(rename = "pre-package-uninstall")]
-    pre_package_uninstall: OneOrMany<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pre_package_uninstall: Option<OneOrMany<String>>,
 
     /// Occurs after a package has been uninstalled.
     #[serde(rename = "post-package-uninstall")]
-    post_package_uninstall: OneOrMany<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    post_package_uninstall: Option<OneOrMany<String>>,
 
     /// Occurs after a Composer instance is done being initialized.
     #[serde(rename = "init")]
-    init: OneOrMany<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    init: Option<OneOrMany<String>>,
 
     /// Occurs before any Composer Command is executed on the CLI.
     /// It provides you with access to the input and output objects of the program.
     #[serde(rename = "command")]
-    command: OneOrMany<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    command: Option<OneOrMany<String>>,
 
     /// Occurs before files are downloaded and allows you to manipulate the `HttpDownloader`
     /// object prior to downloading files based on the URL to be downloaded.
     #[serde(rename = "pre-file-download")]
-    pre_file_download: OneOrMany<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pre_file_download: Option<OneOrMany<String>>,
 
     /// Occurs after package dist files are downloaded and allows you to perform
     /// additional checks on the file if required.
     #[serde(rename = "post-file-download")]
-    post_file_download: OneOrMany<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    post_file_download: Option<OneOrMany<String>>,
 
     /// Occurs before a command is executed and allows you to manipulate the `InputInterface`
     /// object's options and arguments to tweak a command's behavior.
     #[serde(rename = "pre-command-run")]
-    pre_command_run: OneOrMany<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pre_command_run: Option<OneOrMany<String>>,
 
     /// Occurs before the Pool of packages is created, and lets you filter the
     /// list of packages that is going to enter the Solver.
     #[serde(rename = "pre-pool-create")]
-    pre_pool_create: OneOrMany<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pre_pool_create: Option<OneOrMany<String>>,
+
+    /// Custom scripts, keyed by the name they are run with.
+    ///
+    /// Reference: [Writing custom commands](https://getcomposer.org/doc/articles/scripts.md#writing-custom-commands).
+    #[serde(flatten)]
+    pub custom: IndexMap<String, OneOrMany<String>>,
 }
 
 /// A set of options for creating package archives.
@@ -2375,8 +3080,662 @@ pub struct Archive {
 /// Defaults to `false`.
 ///
 /// See [The composer.json schema](https://getcomposer.org/doc/04-schema.md#abandoned) for details.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
 pub enum Abandoned {
     Toggle(bool),
     RecommendedAlternative(String),
 }
+
+#[cfg(test)]
+mod tests {
+    use indexmap::IndexMap;
+
+    use crate::composer_json::{canonical_name, compare_package_names, is_platform_package, Abandoned, AllowPlugins, Autoload, ComposerJson, Config, DiscardChangesMode, EnsureResult, Funding, NormalizeOptions, OneOrMany, PackageSourceConfig, PackageType, PlatformCheck, PreferredInstall, ReleaseTime, Stability};
+    use crate::constraint::ConstraintStyle;
+    use crate::modify_composer_json::PackagePattern;
+
+    fn composer_json(json: &str) -> ComposerJson {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn platform_packages() {
+        for name in ["php", "php-64bit", "ext-mbstring", "lib-curl", "hhvm", "composer-plugin-api"] {
+            assert!(is_platform_package(name), "{} should be a platform package", name);
+        }
+
+        for name in ["monolog/monolog", "phpunit/phpunit", "extension"] {
+            assert!(!is_platform_package(name), "{} should not be a platform package", name);
+        }
+    }
+
+    #[test]
+    fn package_name_ordering() {
+        let mut names = vec!["symfony/yaml", "ext-json", "monolog/monolog", "php", "lib-curl", "ext-ctype", "composer-runtime-api"];
+        names.sort_by(|a, b| compare_package_names(a, b));
+
+        assert_eq!(names, vec!["php", "ext-ctype", "ext-json", "lib-curl", "composer-runtime-api", "monolog/monolog", "symfony/yaml"]);
+    }
+
+    #[test]
+    fn config_serializes_renamed_fields() {
+        let config = Config { vendor_dir: Some("lib/vendor".to_string()), process_timeout: Some(600), ..Default::default() };
+
+        assert_eq!(serde_json::to_value(&config).unwrap(), serde_json::json!({ "process-timeout": 600, "vendor-dir": "lib/vendor" }));
+
+        let config: Config = serde_json::from_str(r#"{ "vendor-dir": "deps", "sort-packages": true }"#).unwrap();
+
+        assert_eq!(config.vendor_dir.as_deref(), Some("deps"));
+        assert_eq!(config.sort_packages, Some(true));
+    }
+
+    #[test]
+    fn allow_plugins_round_trip() {
+        let config: Config = serde_json::from_str(r#"{ "allow-plugins": false }"#).unwrap();
+
+        assert!(matches!(config.allow_plugins, Some(AllowPlugins::Toggle(false))));
+        assert_eq!(serde_json::to_value(&config).unwrap(), serde_json::json!({ "allow-plugins": false }));
+
+        let config: Config = serde_json::from_str(r#"{ "allow-plugins": { "my-org/*": true, "foo/plugin": false } }"#).unwrap();
+
+        assert!(matches!(&config.allow_plugins, Some(AllowPlugins::Map(plugins)) if plugins.get("my-org/*") == Some(&true)));
+        assert_eq!(serde_json::to_value(&config).unwrap(), serde_json::json!({ "allow-plugins": { "my-org/*": true, "foo/plugin": false } }));
+    }
+
+    #[test]
+    fn preferred_install_round_trip() {
+        let config: Config = serde_json::from_str(r#"{ "preferred-install": "dist" }"#).unwrap();
+
+        assert!(matches!(config.preferred_install, Some(PreferredInstall::Dist)));
+        assert_eq!(serde_json::to_value(&config).unwrap(), serde_json::json!({ "preferred-install": "dist" }));
+
+        let preferred_install = serde_json::json!({ "my-organization/*": "source", "partner-organization/*": "auto", "*": "dist" });
+        let config: Config = serde_json::from_value(serde_json::json!({ "preferred-install": preferred_install })).unwrap();
+
+        assert!(matches!(&config.preferred_install, Some(PreferredInstall::Map(map)) if matches!(map.get("my-organization/*"), Some(PackageSourceConfig::Source))));
+        assert_eq!(serde_json::to_value(&config).unwrap()["preferred-install"], preferred_install);
+    }
+
+    #[test]
+    fn discard_changes_round_trip() {
+        for (value, stash) in [(serde_json::json!("stash"), true), (serde_json::json!(true), false), (serde_json::json!(false), false)] {
+            let config: Config = serde_json::from_value(serde_json::json!({ "discard-changes": value })).unwrap();
+
+            assert_eq!(matches!(config.discard_changes, Some(DiscardChangesMode::Stash)), stash);
+            assert_eq!(serde_json::to_value(&config).unwrap()["discard-changes"], value);
+        }
+    }
+
+    #[test]
+    fn effective_platform_prefers_config_platform() {
+        let c = composer_json(r#"{
+            "name": "foo/bar",
+            "require": { "php": ">=7.4", "ext-json": "*", "monolog/monolog": "^2.0" },
+            "config": { "platform": { "php": "8.1.2", "ext-bcmath": "2.6.3" } }
+        }"#);
+
+        let platform = c.effective_platform();
+        let platform: Vec<(&str, &str)> = platform.iter()
+            .map(|(k, v)| (k.as_str(), v.as_str()))
+            .collect();
+
+        assert_eq!(platform, vec![("ext-json", "*"), ("php", "8.1.2"), ("ext-bcmath", "2.6.3")]);
+    }
+
+    #[test]
+    fn effective_platform_without_config() {
+        let c = composer_json(r#"{ "name": "foo/bar", "require": { "php": ">=7.4" } }"#);
+
+        assert_eq!(c.effective_platform().get("php").map(String::as_str), Some(">=7.4"));
+    }
+
+    #[test]
+    fn autoload_files_as_single_string() {
+        let autoload: Autoload = serde_json::from_str(r#"{ "files": "bootstrap.php" }"#).unwrap();
+
+        assert!(matches!(&autoload.files, Some(OneOrMany::One(f)) if f == "bootstrap.php"));
+        assert_eq!(serde_json::to_string(&autoload).unwrap(), r#"{"files":["bootstrap.php"]}"#);
+    }
+
+    #[test]
+    fn autoload_files_as_array() {
+        let autoload: Autoload = serde_json::from_str(r#"{ "files": ["a.php", "b.php"] }"#).unwrap();
+
+        assert_eq!(autoload.files.as_ref().unwrap().as_slice(), ["a.php", "b.php"]);
+        assert_eq!(serde_json::to_string(&autoload).unwrap(), r#"{"files":["a.php","b.php"]}"#);
+    }
+
+    #[test]
+    fn autoload_classmap_as_single_string() {
+        let autoload: Autoload = serde_json::from_str(r#"{ "classmap": "src/" }"#).unwrap();
+
+        assert_eq!(autoload.classmap.as_ref().unwrap().as_slice(), ["src/"]);
+        assert_eq!(serde_json::to_string(&autoload).unwrap(), r#"{"classmap":["src/"]}"#);
+    }
+
+    #[test]
+    fn autoload_namespaces() {
+        let c = composer_json(r#"{
+            "name": "foo/bar",
+            "require": {},
+            "autoload": { "psr-4": { "App\\": "src/" } },
+            "autoload-dev": { "psr-4": { "App\\Tests\\": "tests/" } }
+        }"#);
+
+        assert_eq!(c.autoload_namespaces(true), vec!["App\\", "App\\Tests\\"]);
+        assert_eq!(c.autoload_namespaces(false), vec!["App\\"]);
+    }
+
+    #[test]
+    fn autoload_namespaces_include_psr_0() {
+        let c = composer_json(r#"{
+            "name": "foo/bar",
+            "require": {},
+            "autoload": { "psr-4": { "App\\": "src/" }, "psr-0": { "Legacy_": "lib/" } }
+        }"#);
+
+        assert_eq!(c.autoload_namespaces(true), vec!["App\\", "Legacy_"]);
+    }
+
+    #[test]
+    fn prune_platform_duplicates_removes_covered_requirement() {
+        let mut c = composer_json(r#"{
+            "name": "foo/bar",
+            "require": { "php": ">=8.1", "ext-json": "*", "monolog/monolog": "^3.0" },
+            "config": { "platform": { "php": "8.1" } }
+        }"#);
+
+        assert_eq!(c.prune_platform_duplicates(), vec!["php"]);
+        assert!(!c.package_links.require.contains_key("php"));
+        assert!(c.package_links.require.contains_key("ext-json"));
+        assert!(c.package_links.require.contains_key("monolog/monolog"));
+    }
+
+    #[test]
+    fn check_platform_requirements_against_config_platform() {
+        let with_platform = |php: &str| composer_json(&format!(r#"{{
+            "name": "foo/bar",
+            "require": {{ "php": ">=7.4", "monolog/monolog": "^3.0" }},
+            "require-dev": {{ "ext-xdebug": "*" }},
+            "config": {{ "platform": {{ "php": "{}" }} }}
+        }}"#, php));
+
+        let satisfied = with_platform("7.4.0");
+        let unsatisfied = with_platform("7.3.0");
+
+        assert_eq!(satisfied.check_platform_requirements(false), vec![("php", ">=7.4", PlatformCheck::Satisfied("7.4.0".to_string()))]);
+        assert_eq!(unsatisfied.check_platform_requirements(true), vec![
+            ("php", ">=7.4", PlatformCheck::Unsatisfied("7.3.0".to_string())),
+            ("ext-xdebug", "*", PlatformCheck::Missing),
+        ]);
+    }
+
+    #[test]
+    fn purge_package_removes_from_all_sections() {
+        let mut c = composer_json(r#"{
+            "name": "foo/bar",
+            "require": { "php": ">=8.1", "psr/log": "^3.0" },
+            "require-dev": { "phpunit/phpunit": "^10.0" },
+            "conflict": { "psr/log": "<1.1" }
+        }"#);
+
+        let purged = c.purge_package(&PackagePattern::new("psr/log").unwrap());
+
+        assert_eq!(purged, vec!["require", "conflict"]);
+        assert_eq!(c.package_links.require.keys().collect::<Vec<_>>(), ["php"]);
+        assert!(c.package_links.require_dev.is_some());
+        assert!(c.package_links.conflict.is_none());
+    }
+
+    #[test]
+    fn purge_package_without_matches() {
+        let mut c = composer_json(r#"{
+            "name": "foo/bar",
+            "require": { "php": ">=8.1" },
+            "provide": { "psr/log-implementation": "3.0" }
+        }"#);
+
+        assert!(c.purge_package(&PackagePattern::new("psr/log").unwrap()).is_empty());
+        assert_eq!(c.package_links.require.len(), 1);
+        assert_eq!(c.package_links.provide.as_ref().map(|p| p.len()), Some(1));
+    }
+
+    #[test]
+    fn prune_platform_duplicates_keeps_uncovered_requirement() {
+        let mut c = composer_json(r#"{
+            "name": "foo/bar",
+            "require": { "php": ">=8.1" },
+            "config": { "platform": { "php": "8.0.30" } }
+        }"#);
+
+        assert!(c.prune_platform_duplicates().is_empty());
+        assert!(c.package_links.require.contains_key("php"));
+    }
+
+    #[test]
+    fn require_entries_matching() {
+        let c = composer_json(r#"{
+            "name": "foo/bar",
+            "require": {
+                "php": ">=8.1",
+                "symfony/yaml": "^6.0",
+                "monolog/monolog": "^3.0",
+                "symfony/console": "^6.3",
+                "symfony-cmf/routing": "^3.0"
+            }
+        }"#);
+
+        let pattern = PackagePattern::new("symfony/*").unwrap();
+
+        assert_eq!(c.require_entries_matching(&pattern), vec![("symfony/console", "^6.3"), ("symfony/yaml", "^6.0")]);
+    }
+
+    #[test]
+    fn effective_minimum_stability() {
+        let c = composer_json(r#"{ "name": "foo/bar", "require": {}, "minimum-stability": "dev" }"#);
+        assert_eq!(c.effective_minimum_stability(), Stability::Dev);
+
+        let c = composer_json(r#"{ "name": "foo/bar", "require": {} }"#);
+        assert_eq!(c.effective_minimum_stability(), Stability::Stable);
+    }
+
+    #[test]
+    fn stability_round_trip() {
+        for (stability, name) in [(Stability::Dev, "dev"), (Stability::Alpha, "alpha"), (Stability::Beta, "beta"), (Stability::RC, "RC"), (Stability::Stable, "stable")] {
+            assert_eq!(serde_json::to_value(stability).unwrap(), serde_json::json!(name));
+            assert_eq!(serde_json::from_value::<Stability>(serde_json::json!(name)).unwrap(), stability);
+        }
+
+        let json = r#"{"name":"foo/bar","require":{},"minimum-stability":"RC"}"#;
+
+        assert_eq!(serde_json::to_string(&composer_json(json)).unwrap(), json);
+    }
+
+    #[test]
+    fn funding_keeps_unknown_keys() {
+        let json = r#"{"type":"liberapay","url":"https://liberapay.com/foo","note":"Thank you!","priority":1}"#;
+        let funding: Funding = serde_json::from_str(json).unwrap();
+
+        assert_eq!(funding.platform, "liberapay");
+        assert_eq!(funding.extra["note"], "Thank you!");
+        assert_eq!(serde_json::to_string(&funding).unwrap(), json);
+    }
+
+    #[test]
+    fn ensure_require_outcomes() {
+        let mut c = composer_json(r#"{ "name": "foo/bar", "require": { "monolog/monolog": "^2.0" } }"#);
+
+        assert_eq!(c.ensure_require("psr/log", "^3.0", false), EnsureResult::Added);
+        assert_eq!(c.ensure_require("monolog/monolog", "^3.0", false), EnsureResult::Updated);
+        assert_eq!(c.ensure_require("monolog/monolog", "^3.0", false), EnsureResult::Unchanged);
+        assert_eq!(c.package_links.require.get("monolog/monolog").map(String::as_str), Some("^3.0"));
+        assert_eq!(c.package_links.require.len(), 2);
+    }
+
+    #[test]
+    fn ensure_require_dev() {
+        let mut c = composer_json(r#"{ "name": "foo/bar", "require": {} }"#);
+
+        assert_eq!(c.ensure_require("phpunit/phpunit", "^10.0", true), EnsureResult::Added);
+        assert_eq!(c.ensure_require("phpunit/phpunit", "^10.0", true), EnsureResult::Unchanged);
+        assert!(c.package_links.require.is_empty());
+        assert_eq!(c.package_links.require_dev.unwrap().get("phpunit/phpunit").map(String::as_str), Some("^10.0"));
+    }
+
+    #[test]
+    fn missing_type_is_library() {
+        let c = composer_json(r#"{ "name": "foo/bar", "require": {} }"#);
+
+        assert!(c.is_library());
+        assert!(!c.is_project() && !c.is_metapackage() && !c.is_plugin());
+    }
+
+    #[test]
+    fn explicit_project_type() {
+        let c = composer_json(r#"{ "name": "foo/bar", "type": "project", "require": {} }"#);
+
+        assert!(c.is_project());
+        assert!(!c.is_library() && !c.is_metapackage() && !c.is_plugin());
+    }
+
+    #[test]
+    fn custom_type_matches_no_builtin_type() {
+        let mut c = composer_json(r#"{ "name": "foo/bar", "require": {} }"#);
+        c.package_type = Some(PackageType::Custom("symfony-bundle".to_string()));
+
+        assert!(!c.is_library() && !c.is_project() && !c.is_metapackage() && !c.is_plugin());
+    }
+
+    fn normalize(json: &str, options: NormalizeOptions) -> serde_json::Value {
+        let mut c = composer_json(json);
+        c.normalize(&options);
+
+        serde_json::to_value(&c).unwrap()
+    }
+
+    const UNNORMALIZED: &str = r#"{
+        "name": "Foo/Bar",
+        "version": "1.0.0",
+        "keywords": [],
+        "require": { "php": ">=8.1" },
+        "require-dev": {},
+        "extra": {}
+    }"#;
+
+    #[test]
+    fn normalize_without_options_changes_nothing() {
+        assert_eq!(normalize(UNNORMALIZED, NormalizeOptions::default()), serde_json::to_value(composer_json(UNNORMALIZED)).unwrap());
+    }
+
+    #[test]
+    fn normalize_explicit_type() {
+        let json = normalize(UNNORMALIZED, NormalizeOptions { explicit_type: true, ..Default::default() });
+
+        assert_eq!(json["type"], "library");
+        assert_eq!(json["name"], "Foo/Bar");
+    }
+
+    #[test]
+    fn normalize_lowercase_name() {
+        let json = normalize(UNNORMALIZED, NormalizeOptions { lowercase_name: true, ..Default::default() });
+
+        assert_eq!(json["name"], "foo/bar");
+        assert!(json.get("type").is_none());
+    }
+
+    #[test]
+    fn normalize_omit_version() {
+        let json = normalize(UNNORMALIZED, NormalizeOptions { omit_version: true, ..Default::default() });
+
+        assert!(json.get("version").is_none());
+        assert_eq!(json["keywords"], serde_json::json!([]));
+    }
+
+    #[test]
+    fn normalize_prune_empty() {
+        let json = normalize(UNNORMALIZED, NormalizeOptions { prune_empty: true, ..Default::default() });
+
+        assert!(json.get("keywords").is_none());
+        assert!(json.get("require-dev").is_none());
+        assert!(json.get("extra").is_none());
+        assert_eq!(json["version"], "1.0.0");
+        assert_eq!(json["require"], serde_json::json!({ "php": ">=8.1" }));
+    }
+
+    #[test]
+    fn apply_version_policy_rewrites_require_and_require_dev() {
+        let mut c = composer_json(r#"{
+            "name": "foo/bar",
+            "require": { "php": ">=8.1", "monolog/monolog": "3.5.0", "psr/log": "~3.0" },
+            "require-dev": { "phpunit/phpunit": "10.5.0" }
+        }"#);
+
+        c.apply_version_policy(ConstraintStyle::Caret);
+
+        assert_eq!(c.package_links.require.get("php").map(String::as_str), Some(">=8.1"));
+        assert_eq!(c.package_links.require.get("monolog/monolog").map(String::as_str), Some("^3.5.0"));
+        assert_eq!(c.package_links.require.get("psr/log").map(String::as_str), Some("^3.0"));
+        assert_eq!(c.package_links.require_dev.unwrap().get("phpunit/phpunit").map(String::as_str), Some("^10.5.0"));
+    }
+
+    #[test]
+    fn conflicts_with() {
+        let c = composer_json(r#"{ "name": "foo/baz", "require": {}, "conflict": { "foo/bar": "<2.0" } }"#);
+
+        assert!(c.conflicts_with("foo/bar", "1.5.0"));
+        assert!(!c.conflicts_with("foo/bar", "2.1.0"));
+        assert!(!c.conflicts_with("foo/other", "1.5.0"));
+    }
+
+    #[test]
+    fn time_as_date() {
+        let c = composer_json(r#"{ "name": "foo/bar", "time": "2021-03-04", "require": {} }"#);
+
+        assert!(matches!(c.time, Some(ReleaseTime::Date(_))));
+        assert_eq!(serde_json::to_value(&c).unwrap()["time"], "2021-03-04");
+    }
+
+    #[test]
+    fn time_as_date_time() {
+        let c = composer_json(r#"{ "name": "foo/bar", "time": "2021-03-04 05:06:07", "require": {} }"#);
+
+        assert!(matches!(c.time, Some(ReleaseTime::DateTime(_))));
+        assert_eq!(serde_json::to_value(&c).unwrap()["time"], "2021-03-04 05:06:07");
+    }
+
+    #[test]
+    fn time_rejects_malformed_date() {
+        let error = serde_json::from_str::<ComposerJson>(r#"{ "name": "foo/bar", "time": "2021-13-40", "require": {} }"#).unwrap_err();
+
+        assert!(error.to_string().contains("invalid time \"2021-13-40\""));
+    }
+
+    #[test]
+    fn canonical_names() {
+        assert_eq!(canonical_name(" Monolog/Monolog "), "monolog/monolog");
+        assert_eq!(canonical_name("php"), "php");
+    }
+
+    #[test]
+    fn round_trip_preserves_key_order() {
+        let json = r#"{
+            "name": "foo/bar",
+            "require": { "symfony/yaml": "^6.0", "php": ">=8.1", "monolog/monolog": "^3.0" },
+            "require-dev": { "phpunit/phpunit": "^10.0", "mockery/mockery": "^1.6" },
+            "suggest": { "ext-intl": "For translations", "ext-apcu": "For caching" },
+            "autoload": { "psr-4": { "Foo\\Bar\\": "src/", "Foo\\Baz\\": "lib/" } },
+            "extra": { "zeta": 1, "alpha": 2 }
+        }"#;
+        let value = serde_json::to_value(composer_json(json)).unwrap();
+        let keys = |v: &serde_json::Value| -> Vec<String> { v.as_object().unwrap().keys().cloned().collect() };
+
+        assert_eq!(keys(&value["require"]), ["symfony/yaml", "php", "monolog/monolog"]);
+        assert_eq!(keys(&value["require-dev"]), ["phpunit/phpunit", "mockery/mockery"]);
+        assert_eq!(keys(&value["suggest"]), ["ext-intl", "ext-apcu"]);
+        assert_eq!(keys(&value["autoload"]["psr-4"]), ["Foo\\Bar\\", "Foo\\Baz\\"]);
+        assert_eq!(keys(&value["extra"]), ["zeta", "alpha"]);
+    }
+
+    #[test]
+    fn serializes_fields_in_canonical_order() {
+        let c = composer_json(r#"{
+            "extra": { "branch-alias": { "dev-main": "1.x-dev" } },
+            "scripts": { "test": "phpunit" },
+            "autoload-dev": { "psr-4": { "Foo\\Bar\\Tests\\": "tests/" } },
+            "require-dev": { "phpunit/phpunit": "^10.0" },
+            "config": { "sort-packages": true },
+            "autoload": { "psr-4": { "Foo\\Bar\\": "src/" } },
+            "require": { "php": ">=8.1" },
+            "authors": [{ "name": "Jane Doe", "email": "jane@example.com" }],
+            "prefer-stable": true,
+            "license": "MIT",
+            "minimum-stability": "dev",
+            "keywords": ["foo"],
+            "type": "library",
+            "description": "Foo bar",
+            "suggest": { "ext-intl": "For translations" },
+            "name": "foo/bar",
+            "bin": ["bin/foo"]
+        }"#);
+
+        let value: serde_json::Value = serde_json::from_str(&serde_json::to_string(&c).unwrap()).unwrap();
+        let keys: Vec<&String> = value.as_object().unwrap().keys().collect();
+
+        assert_eq!(keys, [
+            "name", "description", "type", "keywords", "license", "authors",
+            "require", "require-dev", "suggest", "autoload", "autoload-dev",
+            "minimum-stability", "prefer-stable", "config", "scripts", "extra", "bin",
+        ]);
+    }
+
+    #[test]
+    fn license_as_single_string() {
+        let c = composer_json(r#"{ "name": "foo/bar", "license": "MIT", "require": {} }"#);
+        let value = serde_json::to_value(&c).unwrap();
+
+        assert!(matches!(&c.license, Some(OneOrMany::One(license)) if license == "MIT"));
+        assert_eq!(value["license"], serde_json::json!("MIT"));
+    }
+
+    #[test]
+    fn license_as_array() {
+        let c = composer_json(r#"{ "name": "foo/bar", "license": ["MIT", "Apache-2.0"], "require": {} }"#);
+        let value = serde_json::to_value(&c).unwrap();
+
+        assert!(matches!(&c.license, Some(OneOrMany::Many(licenses)) if licenses == &["MIT", "Apache-2.0"]));
+        assert_eq!(value["license"], serde_json::json!(["MIT", "Apache-2.0"]));
+    }
+
+    #[test]
+    fn abandoned_from_bool_or_string() {
+        for (json, expected) in [
+            ("false", Abandoned::Toggle(false)),
+            ("true", Abandoned::Toggle(true)),
+            (r#""monolog/monolog""#, Abandoned::RecommendedAlternative("monolog/monolog".to_string())),
+        ] {
+            let c = composer_json(&format!(r#"{{ "name": "foo/bar", "require": {{}}, "abandoned": {} }}"#, json));
+            let value = serde_json::to_value(&c).unwrap();
+
+            assert_eq!(c.abandoned, Some(expected));
+            assert_eq!(value["abandoned"].to_string(), json);
+        }
+    }
+
+    #[test]
+    fn convert_psr0_to_psr4() {
+        let mut c = composer_json(r#"{
+            "name": "foo/bar",
+            "require": {},
+            "autoload": { "psr-0": { "Foo\\": "src/", "Legacy_": "lib/" } }
+        }"#);
+
+        let unconverted = c.convert_psr0_to_psr4();
+        let autoload = c.autoload.unwrap();
+
+        assert_eq!(unconverted, ["Legacy_"]);
+        assert_eq!(autoload.psr_4.unwrap().get("Foo\\").map(String::as_str), Some("src/Foo/"));
+        assert_eq!(autoload.psr_0.unwrap().keys().collect::<Vec<_>>(), ["Legacy_"]);
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn convert_psr0_to_psr4_clears_target_dir() {
+        let mut c = composer_json(r#"{
+            "name": "symfony/yaml",
+            "require": {},
+            "target-dir": "Symfony/Component/Yaml",
+            "autoload": { "psr-0": { "Symfony\\Component\\Yaml\\": "" } }
+        }"#);
+
+        assert!(c.convert_psr0_to_psr4().is_empty());
+
+        let autoload = c.autoload.unwrap();
+
+        assert_eq!(autoload.psr_4.unwrap().get("Symfony\\Component\\Yaml\\").map(String::as_str), Some(""));
+        assert!(autoload.psr_0.is_none());
+        assert!(c.target_dir.is_none());
+    }
+
+    #[test]
+    fn normalize_psr4_fixes_prefixes_and_paths() {
+        let mut c = composer_json(r#"{
+            "name": "foo/bar",
+            "require": {},
+            "autoload": { "psr-4": { "Foo": "src", "Foo\\Bar\\\\": "lib//", "": "fallback/", "Root\\": "" } },
+            "autoload-dev": { "psr-4": { "Foo\\Tests\\": "tests/" } }
+        }"#);
+
+        let fixed = c.normalize_psr4();
+        let autoload = serde_json::to_value(&c.autoload).unwrap();
+
+        assert_eq!(fixed, [
+            r#"autoload.psr-4 "Foo" -> "Foo\""#,
+            r#"autoload.psr-4 "Foo\": "src" -> "src/""#,
+            r#"autoload.psr-4 "Foo\Bar\\" -> "Foo\Bar\""#,
+            r#"autoload.psr-4 "Foo\Bar\": "lib//" -> "lib/""#,
+        ]);
+        assert_eq!(autoload, serde_json::json!({ "psr-4": { "Foo\\": "src/", "Foo\\Bar\\": "lib/", "": "fallback/", "Root\\": "" } }));
+        assert!(c.normalize_psr4().is_empty());
+    }
+
+    #[test]
+    fn package_type_round_trip() {
+        for (name, expected) in [
+            ("library", PackageType::Library),
+            ("composer-plugin", PackageType::ComposerPlugin),
+            ("typo3-cms-extension", PackageType::Custom("typo3-cms-extension".to_string())),
+        ] {
+            let c = composer_json(&format!(r#"{{ "name": "foo/bar", "require": {{}}, "type": "{}" }}"#, name));
+            let value = serde_json::to_value(&c).unwrap();
+
+            assert_eq!(c.package_type, Some(expected));
+            assert_eq!(value["type"], name);
+        }
+    }
+
+    #[test]
+    fn require_dev_or_default_inserts_empty_map() {
+        let mut c = composer_json(r#"{ "name": "foo/bar", "require": {} }"#);
+
+        assert!(c.require_dev_or_default().is_empty());
+        assert_eq!(c.package_links.require_dev, Some(IndexMap::new()));
+
+        c.normalize(&NormalizeOptions { prune_empty: true, ..Default::default() });
+
+        assert!(c.package_links.require_dev.is_none());
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn include_path_and_target_dir() {
+        let c = composer_json(r#"{
+            "name": "foo/bar",
+            "require": {},
+            "include-path": ["lib/"],
+            "target-dir": "Foo/Bar"
+        }"#);
+        let value = serde_json::to_value(&c).unwrap();
+
+        assert_eq!(c.include_path, Some(vec!["lib/".to_string()]));
+        assert_eq!(c.target_dir.as_deref(), Some("Foo/Bar"));
+        assert_eq!(value["include-path"], serde_json::json!(["lib/"]));
+        assert_eq!(value["target-dir"], "Foo/Bar");
+    }
+
+    #[test]
+    fn repositories_round_trip_options_and_inline_package() {
+        let repositories = serde_json::json!([
+            { "type": "composer", "url": "https://packages.example.com" },
+            { "type": "composer", "url": "https://packages.example.com", "options": { "ssl": { "verify_peer": "true" } } },
+            { "type": "vcs", "url": "https://github.com/Seldaek/monolog" },
+            { "packagist.org": false },
+            {
+                "type": "package",
+                "package": {
+                    "name": "smarty/smarty",
+                    "version": "3.1.7",
+                    "dist": { "url": "https://www.smarty.net/files/Smarty-3.1.7.zip", "type": "zip" },
+                    "source": { "url": "https://smarty-php.googlecode.com/svn/", "type": "svn", "reference": "tags/Smarty_3_1_7/distribution/" }
+                }
+            }
+        ]);
+        let c: ComposerJson = serde_json::from_value(serde_json::json!({ "name": "foo/bar", "require": {}, "repositories": repositories })).unwrap();
+        let value = serde_json::to_value(&c).unwrap();
+
+        assert_eq!(value["repositories"], repositories);
+    }
+
+    #[test]
+    fn round_trip_preserves_unknown_fields() {
+        let custom = r#"{"nested":{"b":[1,2.5,"x"],"a":null},"flag":true}"#;
+        let c = composer_json(&format!(r#"{{"name":"foo/bar","my-custom-field":{},"require":{{"php":">=8.1"}}}}"#, custom));
+
+        assert_eq!(c.unknown.keys().collect::<Vec<_>>(), ["my-custom-field"]);
+        assert!(!c.unknown.contains_key("require"));
+        assert_eq!(
+            serde_json::to_string(&c).unwrap(),
+            format!(r#"{{"name":"foo/bar","require":{{"php":">=8.1"}},"my-custom-field":{}}}"#, custom),
+        );
+    }
+}
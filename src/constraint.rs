@@ -0,0 +1,573 @@
+use std::fmt;
+use std::str::FromStr;
+
+use clap::ValueEnum;
+
+/// Error returned when a version or version constraint cannot be parsed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConstraintError(String);
+
+impl fmt::Display for ConstraintError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for ConstraintError {}
+
+/// Stability part of a [Version], ordered from least to most stable.
+///
+/// `patch` releases sort after the plain (stable) release they patch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum VersionStability {
+    Dev,
+    Alpha(u64),
+    Beta(u64),
+    RC(u64),
+    Stable,
+    Patch(u64),
+}
+
+/// A concrete version, normalized to four numeric parts like Composer does, e.g. `1.2.3.0`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Version {
+    numbers: [u64; 4],
+    stability: VersionStability,
+}
+
+impl Version {
+    #[cfg(test)]
+    pub fn new(major: u64, minor: u64, patch: u64) -> Version {
+        Version { numbers: [major, minor, patch, 0], stability: VersionStability::Stable }
+    }
+
+    /// Parses a version, also returning how many numeric parts it was written with.
+    fn parse_with_precision(s: &str) -> Result<(Version, usize), ConstraintError> {
+        let invalid = || ConstraintError(format!("invalid version: \"{}\"", s));
+        let trimmed = s.trim();
+        let trimmed = trimmed.strip_prefix(['v', 'V']).unwrap_or(trimmed);
+
+        let (numbers_part, suffix) = match trimmed.split_once('-') {
+            Some((numbers, suffix)) => (numbers, Some(suffix)),
+            None => (trimmed, None),
+        };
+
+        let parts: Vec<&str> = numbers_part.split('.').collect();
+
+        if parts.is_empty() || parts.len() > 4 {
+            return Err(invalid());
+        }
+
+        let mut numbers = [0; 4];
+
+        for (i, part) in parts.iter().enumerate() {
+            if part.is_empty() || !part.chars().all(|c| c.is_ascii_digit()) {
+                return Err(invalid());
+            }
+
+            numbers[i] = part.parse().map_err(|_| invalid())?;
+        }
+
+        let stability = match suffix {
+            Some(suffix) => parse_stability(suffix).ok_or_else(invalid)?,
+            None => VersionStability::Stable,
+        };
+
+        Ok((Version { numbers, stability }, parts.len()))
+    }
+
+    /// The lowest version sharing these numeric parts, i.e. the `-dev` version, unless an
+    /// explicit stability was given.
+    fn lowest(self) -> Version {
+        match self.stability {
+            VersionStability::Stable => Version { stability: VersionStability::Dev, ..self },
+            _ => self,
+        }
+    }
+
+    /// The `-dev` version of the release following this one, bumping the numeric part at `index`.
+    fn bump(self, index: usize) -> Version {
+        let mut numbers = [0; 4];
+
+        numbers[..index].copy_from_slice(&self.numbers[..index]);
+        numbers[index] = self.numbers[index] + 1;
+
+        Version { numbers, stability: VersionStability::Dev }
+    }
+}
+
+impl FromStr for Version {
+    type Err = ConstraintError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Version::parse_with_precision(s).map(|(version, _)| version)
+    }
+}
+
+fn parse_stability(suffix: &str) -> Option<VersionStability> {
+    let suffix = suffix.to_lowercase();
+    let split = suffix.find(|c: char| c.is_ascii_digit()).unwrap_or(suffix.len());
+    let (name, number) = suffix.split_at(split);
+    let name = name.trim_end_matches('.');
+    let number = match number {
+        "" => 0,
+        n => n.parse().ok()?,
+    };
+
+    match name {
+        "dev" if number == 0 => Some(VersionStability::Dev),
+        "alpha" | "a" => Some(VersionStability::Alpha(number)),
+        "beta" | "b" => Some(VersionStability::Beta(number)),
+        "rc" => Some(VersionStability::RC(number)),
+        "stable" if number == 0 => Some(VersionStability::Stable),
+        "patch" | "pl" | "p" => Some(VersionStability::Patch(number)),
+        _ => None,
+    }
+}
+
+/// Comparison operator of a [Constraint::Comparison].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operator {
+    Equal,
+    NotEqual,
+    GreaterThan,
+    GreaterThanOrEqual,
+    LessThan,
+    LessThanOrEqual,
+}
+
+/// A parsed Composer version constraint.
+///
+/// Reference: [Versions and constraints](https://getcomposer.org/doc/articles/versions.md).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Constraint {
+    /// `*` or an empty constraint, matching any version.
+    Any,
+
+    /// An exact version like `1.0.2`.
+    Exact(Version),
+
+    /// A comparison like `>=1.0` or `!=1.5.0`.
+    Comparison(Operator, Version),
+
+    /// A caret constraint like `^1.2.3`, allowing non-breaking updates.
+    /// The second field is the number of numeric parts the version was written with.
+    Caret(Version, usize),
+
+    /// A tilde constraint like `~1.2`, allowing the last given part to increase.
+    /// The second field is the number of numeric parts the version was written with.
+    Tilde(Version, usize),
+
+    /// A wildcard constraint like `1.0.*`, given as its numeric prefix.
+    Wildcard(Version, usize),
+
+    /// A hyphenated range like `1.0 - 2.0`, including both ends. An upper end written without
+    /// a patch part matches all of its patch releases, so `1.0 - 2.0` is `>=1.0 <2.1`.
+    /// The third field is the number of numeric parts the upper end was written with.
+    Range(Version, Version, usize),
+
+    /// All of the constraints must match, e.g. `>=1.0 <2.0`.
+    And(Vec<Constraint>),
+
+    /// Any of the constraints must match, e.g. `^1.0 || ^2.0`.
+    Or(Vec<Constraint>),
+}
+
+impl Constraint {
+    /// Whether `version` satisfies this constraint.
+    pub fn matches(&self, version: &Version) -> bool {
+        match self {
+            Constraint::Any => true,
+            Constraint::Exact(v) => version == v,
+            Constraint::Comparison(op, v) => match op {
+                Operator::Equal => version == v,
+                Operator::NotEqual => version != v,
+                Operator::GreaterThan => version > v,
+                Operator::GreaterThanOrEqual => version >= &v.lowest(),
+                Operator::LessThan => version < &v.lowest(),
+                Operator::LessThanOrEqual => version <= v,
+            },
+            Constraint::Caret(..) | Constraint::Tilde(..) | Constraint::Wildcard(..) => {
+                matches!(self.bounds(), Some((lower, upper)) if version >= &lower && version < &upper)
+            },
+            Constraint::Range(lower, upper, precision) => version >= &lower.lowest() && match precision {
+                1 | 2 => version < &upper.bump(precision - 1),
+                _ => version <= upper,
+            },
+            Constraint::And(constraints) => constraints.iter().all(|c| c.matches(version)),
+            Constraint::Or(constraints) => constraints.iter().any(|c| c.matches(version)),
+        }
+    }
+
+    /// The lowest matching version and the lowest version above it that does not match, for
+    /// constraints matching a single contiguous range of versions, like `^1.2` or `>=1.2 <2.0`.
+    fn bounds(&self) -> Option<(Version, Version)> {
+        match self {
+            Constraint::Caret(v, precision) => {
+                let index = v.numbers.iter()
+                    .take(*precision)
+                    .position(|n| *n > 0)
+                    .unwrap_or(precision - 1);
+
+                Some((v.lowest(), v.bump(index)))
+            },
+            Constraint::Tilde(v, precision) => {
+                let index = if *precision <= 2 { 0 } else { precision - 2 };
+
+                Some((v.lowest(), v.bump(index)))
+            },
+            Constraint::Wildcard(v, precision) => Some((v.lowest(), v.bump(precision - 1))),
+            Constraint::And(constraints) => match constraints.as_slice() {
+                [Constraint::Comparison(Operator::GreaterThanOrEqual, lower), Constraint::Comparison(Operator::LessThan, upper)] => {
+                    Some((lower.lowest(), upper.lowest()))
+                },
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    fn parse_single(s: &str) -> Result<Constraint, ConstraintError> {
+        // Stability flags (`@beta`) and commit references (`#abc123`) do not affect which
+        // version numbers match.
+        let s = s.split(['@', '#']).next().unwrap_or("").trim();
+
+        if s.is_empty() || s == "*" {
+            return Ok(Constraint::Any);
+        }
+
+        for (prefix, op) in [
+            (">=", Operator::GreaterThanOrEqual),
+            ("<=", Operator::LessThanOrEqual),
+            ("!=", Operator::NotEqual),
+            ("<>", Operator::NotEqual),
+            ("==", Operator::Equal),
+            (">", Operator::GreaterThan),
+            ("<", Operator::LessThan),
+            ("=", Operator::Equal),
+        ] {
+            if let Some(version) = s.strip_prefix(prefix) {
+                return Ok(Constraint::Comparison(op, version.trim().parse()?));
+            }
+        }
+
+        if let Some(version) = s.strip_prefix('^') {
+            let (version, precision) = Version::parse_with_precision(version)?;
+
+            return Ok(Constraint::Caret(version, precision));
+        }
+
+        if let Some(version) = s.strip_prefix('~') {
+            let (version, precision) = Version::parse_with_precision(version)?;
+
+            return Ok(Constraint::Tilde(version, precision));
+        }
+
+        if let Some(prefix) = s.strip_suffix(".*").or_else(|| s.strip_suffix(".x")) {
+            let (version, precision) = Version::parse_with_precision(prefix)?;
+
+            return Ok(Constraint::Wildcard(version, precision));
+        }
+
+        Ok(Constraint::Exact(s.parse()?))
+    }
+}
+
+impl FromStr for Constraint {
+    type Err = ConstraintError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut alternatives = Vec::new();
+
+        for alternative in s.replace("||", "|").split('|') {
+            if let Some((lower, upper)) = alternative.split_once(" - ") {
+                let (upper, precision) = Version::parse_with_precision(upper)?;
+
+                alternatives.push(Constraint::Range(lower.trim().parse()?, upper, precision));
+                continue;
+            }
+
+            let mut tokens: Vec<String> = Vec::new();
+
+            for token in alternative.split([',', ' ']).filter(|t| !t.is_empty()) {
+                // Allow whitespace between an operator and its version, e.g. `>= 1.0`.
+                match tokens.last_mut() {
+                    Some(last) if last.chars().all(|c| "<>=!^~".contains(c)) => last.push_str(token),
+                    _ => tokens.push(token.to_string()),
+                }
+            }
+
+            let mut constraints = tokens.iter()
+                .map(|t| Constraint::parse_single(t))
+                .collect::<Result<Vec<Constraint>, ConstraintError>>()?;
+
+            alternatives.push(match constraints.len() {
+                0 => Constraint::Any,
+                1 => constraints.remove(0),
+                _ => Constraint::And(constraints),
+            });
+        }
+
+        Ok(match alternatives.len() {
+            1 => alternatives.remove(0),
+            _ => Constraint::Or(alternatives),
+        })
+    }
+}
+
+/// A style of writing single-version constraints, enforced by [restyle].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ConstraintStyle {
+    /// `^1.2.3`, allowing non-breaking updates.
+    Caret,
+
+    /// `~1.2.3`, allowing the last given part to increase.
+    Tilde,
+
+    /// `1.2.3`, allowing exactly that version.
+    Exact,
+}
+
+/// Rewrites `constraint` to `style`, if it is a plain, stable single-version constraint like
+/// `1.2.3`, `=1.2.3`, `^1.2.3` or `~1.2`. An exact version can be rewritten to any style, but a
+/// caret or tilde constraint only to one matching the same versions, like `~1.2` to `^1.2`.
+///
+/// Returns `None` for constraints that are left as they are, i.e. ranges (`>=1.0 <2.0`, `1.0.*`, `^1.0 || ^2.0`),
+/// dev constraints (`dev-main`, `1.0.x-dev`, `^1.0@dev`), pre-releases, constraints already in `style`
+/// and caret or tilde constraints whose versions would change, like `~1.2.3` to `^1.2.3` or `^1.2.3` to `1.2.3`.
+pub fn restyle(constraint: &str, style: ConstraintStyle) -> Option<String> {
+    let trimmed = constraint.trim();
+
+    if trimmed.contains(['@', '#', ' ', ',', '|', '*']) {
+        return None;
+    }
+
+    let version = ["^", "~", "==", "="].iter()
+        .find_map(|prefix| trimmed.strip_prefix(prefix))
+        .unwrap_or(trimmed);
+
+    match Version::parse_with_precision(version) {
+        Ok((v, _)) if v.stability == VersionStability::Stable => {},
+        _ => return None,
+    }
+
+    let restyled = match style {
+        ConstraintStyle::Caret => format!("^{}", version),
+        ConstraintStyle::Tilde => format!("~{}", version),
+        ConstraintStyle::Exact => version.to_string(),
+    };
+
+    let parsed: Constraint = trimmed.parse().ok()?;
+    let same_versions = match parsed {
+        Constraint::Exact(..) | Constraint::Comparison(Operator::Equal, _) => true,
+        _ => parsed.bounds().is_some() && parsed.bounds() == restyled.parse::<Constraint>().ok()?.bounds(),
+    };
+
+    match restyled != trimmed && same_versions {
+        true => Some(restyled),
+        false => None,
+    }
+}
+
+/// Rewrites `constraint` to the caret constraint matching exactly the same versions, e.g. `~1.2`,
+/// `1.*` or `>=1.2 <2.0.0` to `^1.2` and `^1`.
+///
+/// Returns `None` for constraints without such an equivalent, like `~1.2.3` or `>=1.0 <1.5`,
+/// and for caret constraints and constraints that cannot be parsed.
+pub fn to_caret(constraint: &str) -> Option<String> {
+    let trimmed = constraint.trim();
+    let parsed: Constraint = trimmed.parse().ok()?;
+
+    let lower = match &parsed {
+        Constraint::Tilde(..) => trimmed.strip_prefix('~')?.trim(),
+        Constraint::Wildcard(..) => trimmed.strip_suffix(".*").or_else(|| trimmed.strip_suffix(".x"))?,
+        Constraint::And(..) => trimmed.strip_prefix(">=")?.split([' ', ',']).find(|t| !t.is_empty())?,
+        _ => return None,
+    };
+    let caret = format!("^{}", lower);
+
+    match (parsed.bounds(), caret.parse::<Constraint>().ok()?.bounds()) {
+        (Some(bounds), Some(caret_bounds)) if bounds == caret_bounds => Some(caret),
+        _ => None,
+    }
+}
+
+/// Whether `version` satisfies `constraint`, both given in Composer notation.
+pub fn satisfies(version: &str, constraint: &str) -> Result<bool, ConstraintError> {
+    let version: Version = version.parse()?;
+    let constraint: Constraint = constraint.parse()?;
+
+    Ok(constraint.matches(&version))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::constraint::{restyle, satisfies, to_caret, Constraint, ConstraintStyle, Operator, Version};
+
+    #[test]
+    fn version_ordering() {
+        let versions: Vec<Version> = ["1.0.0-dev", "1.0.0-alpha2", "1.0.0-beta1", "1.0.0-RC5", "1.0.0", "v1.0.0-p1", "1.0.1"]
+            .iter()
+            .map(|v| v.parse().unwrap())
+            .collect();
+
+        assert!(versions.windows(2).all(|w| w[0] < w[1]));
+        assert_eq!("1.2".parse::<Version>().unwrap(), Version::new(1, 2, 0));
+    }
+
+    #[test]
+    fn invalid_versions() {
+        for version in ["", "latest", "1.x", "1.0.0.0.0", "1.0-foo"] {
+            assert!(version.parse::<Version>().is_err(), "{} should be invalid", version);
+        }
+    }
+
+    #[test]
+    fn satisfies_constraints() {
+        let cases = [
+            ("7.4.0", ">=7.4", true),
+            ("7.3.0", ">=7.4", false),
+            ("8.1", "^8.1", true),
+            ("9.0.0", "^8.1", false),
+            ("1.9.9", "~1.2", true),
+            ("1.3.0", "~1.2.3", false),
+            ("1.0.5", "1.0.*", true),
+            ("1.1.0", "1.0.*", false),
+            ("2.1.0", "^1.0 || ^2.0", true),
+            ("1.5.0", ">=1.0 <2.0", true),
+            ("2.0.0", ">=1.0, <2.0", false),
+            ("1.2.3", "1.2.3", true),
+            ("3.0.0", "*", true),
+        ];
+
+        for (version, constraint, expected) in cases {
+            assert_eq!(satisfies(version, constraint).unwrap(), expected, "{} satisfies {}", version, constraint);
+        }
+    }
+
+    #[test]
+    fn satisfies_constraint_matrix() {
+        let cases = [
+            // Comparisons
+            ("1.0.0", "=1.0.0", true),
+            ("1.0.1", "==1.0.0", false),
+            ("1.0.1", "!=1.0.0", true),
+            ("1.0.0", "<>1.0.0", false),
+            ("1.0.1", ">1.0.0", true),
+            ("1.0.0", ">1.0.0", false),
+            ("1.0.0", ">= 1.0", true),
+            ("0.9.9", "<1.0", true),
+            ("1.0.0-beta1", "<1.0", false),
+            ("1.0.0", "<1.0", false),
+            ("1.0.0", "<=1.0.0", true),
+            ("1.0.1", "<=1.0.0", false),
+            // Caret
+            ("1.2.3", "^1.2.3", true),
+            ("1.2.2", "^1.2.3", false),
+            ("1.99.0", "^1.2.3", true),
+            ("0.3.9", "^0.3", true),
+            ("0.4.0", "^0.3", false),
+            ("0.0.3", "^0.0.3", true),
+            ("0.0.4", "^0.0.3", false),
+            ("2.0.0-beta1", "^1.0", false),
+            // Tilde
+            ("1.2.9", "~1.2.3", true),
+            ("1.2.2", "~1.2.3", false),
+            ("1.99.0", "~1.2", true),
+            ("2.0.0", "~1.2", false),
+            ("1.9.0", "~1", true),
+            // Wildcards
+            ("1.99.99", "1.*", true),
+            ("2.0.0", "1.*", false),
+            ("1.0.0", "1.0.x", true),
+            ("1.0.5", "1.0.*", true),
+            // Hyphenated ranges
+            ("1.0.0", "1.0 - 2.0", true),
+            ("2.0.9", "1.0 - 2.0", true),
+            ("2.1.0", "1.0 - 2.0", false),
+            ("0.9.0", "1.0 - 2.0", false),
+            ("2.1.0", "1.0.0 - 2.1.0", true),
+            ("2.1.1", "1.0.0 - 2.1.0", false),
+            ("3.5.0", "1.0 - 2.0 || ^3.0", true),
+            // And / or, stability flags and prefixed versions
+            ("1.5.0", ">=1.0,<1.5", false),
+            ("1.4.0", ">=1.0 <1.5 || >=2.0", true),
+            ("1.7.0", ">=1.0 <1.5 || >=2.0", false),
+            ("2.0.0", "^1.0 | ^2.0", true),
+            ("1.2.0", "^1.0@dev", true),
+            ("v1.2.3", "1.2.3", true),
+            ("1.2.3", "v1.2.3", true),
+        ];
+
+        for (version, constraint, expected) in cases {
+            assert_eq!(satisfies(version, constraint).unwrap(), expected, "{} satisfies {}", version, constraint);
+        }
+    }
+
+    #[test]
+    fn parse_constraint_kinds() {
+        let version = |v: &str| v.parse::<Version>().unwrap();
+
+        assert_eq!("".parse::<Constraint>().unwrap(), Constraint::Any);
+        assert_eq!(">=7.4".parse::<Constraint>().unwrap(), Constraint::Comparison(Operator::GreaterThanOrEqual, version("7.4")));
+        assert_eq!("^3.0".parse::<Constraint>().unwrap(), Constraint::Caret(version("3.0"), 2));
+        assert_eq!("~1.2".parse::<Constraint>().unwrap(), Constraint::Tilde(version("1.2"), 2));
+        assert_eq!("1.0.*".parse::<Constraint>().unwrap(), Constraint::Wildcard(version("1.0"), 2));
+        assert_eq!("1.0 - 2.0".parse::<Constraint>().unwrap(), Constraint::Range(version("1.0"), version("2.0"), 2));
+        assert!(matches!(">=1.0 <2.0".parse::<Constraint>().unwrap(), Constraint::And(c) if c.len() == 2));
+        assert!(matches!("^1.0 || ^2.0".parse::<Constraint>().unwrap(), Constraint::Or(c) if c.len() == 2));
+    }
+
+    #[test]
+    fn invalid_constraints() {
+        for constraint in ["^latest", ">=1.x", "1.0 - ", "~"] {
+            assert!(constraint.parse::<Constraint>().is_err(), "{} should be invalid", constraint);
+        }
+    }
+
+    #[test]
+    fn to_caret_of_equivalent_constraints() {
+        assert_eq!(to_caret("~1.2").as_deref(), Some("^1.2"));
+        assert_eq!(to_caret("~0.1.2").as_deref(), Some("^0.1.2"));
+        assert_eq!(to_caret(">=1.2 <2.0.0").as_deref(), Some("^1.2"));
+        assert_eq!(to_caret(">= 1.2, <2.0").as_deref(), Some("^1.2"));
+        assert_eq!(to_caret("1.*").as_deref(), Some("^1"));
+    }
+
+    #[test]
+    fn to_caret_of_other_constraints() {
+        for constraint in ["~1.2.3", "~0.3", ">=1.0 <1.5", "1.2.*", "^1.2", ">=1.0", "1.2.3", "^1.0 || ^2.0", "dev-main", "*"] {
+            assert_eq!(to_caret(constraint), None, "{} has no caret form", constraint);
+        }
+    }
+
+    #[test]
+    fn restyle_to_caret() {
+        assert_eq!(restyle("1.2.3", ConstraintStyle::Caret).as_deref(), Some("^1.2.3"));
+        assert_eq!(restyle("~1.2", ConstraintStyle::Caret).as_deref(), Some("^1.2"));
+        assert_eq!(restyle("^1.2", ConstraintStyle::Caret), None);
+    }
+
+    #[test]
+    fn restyle_to_tilde() {
+        assert_eq!(restyle("^1.2", ConstraintStyle::Tilde).as_deref(), Some("~1.2"));
+        assert_eq!(restyle("=v2.0.1", ConstraintStyle::Tilde).as_deref(), Some("~v2.0.1"));
+    }
+
+    #[test]
+    fn restyle_to_exact() {
+        assert_eq!(restyle("=1.2.3", ConstraintStyle::Exact).as_deref(), Some("1.2.3"));
+        assert_eq!(restyle("==1.2.3", ConstraintStyle::Exact).as_deref(), Some("1.2.3"));
+    }
+
+    #[test]
+    fn restyle_skips_ranges_and_dev_constraints() {
+        for constraint in [">=1.0", ">=1.0 <2.0", "1.0.*", "^1.0 || ^2.0", "*", "dev-main", "1.0.x-dev", "^1.0@dev", "1.0.0-beta1"] {
+            assert_eq!(restyle(constraint, ConstraintStyle::Caret), None, "{} should be skipped", constraint);
+        }
+
+        for (constraint, style) in [("~1.2.3", ConstraintStyle::Caret), ("^0.3", ConstraintStyle::Tilde), ("^1.2.3", ConstraintStyle::Exact)] {
+            assert_eq!(restyle(constraint, style), None, "{} should not be rewritten to match other versions", constraint);
+        }
+    }
+}
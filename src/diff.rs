@@ -0,0 +1,178 @@
+/// Default number of unchanged lines shown around each change in a [unified_diff].
+pub(crate) const DEFAULT_CONTEXT_LINES: usize = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Line<'a> {
+    Unchanged(&'a str),
+    Removed(&'a str),
+    Added(&'a str),
+}
+
+/// A line-based unified diff between `old` and `new`, labelled `old_label` and `new_label` in the
+/// header, showing `context` unchanged lines around each change. Returns an empty string if the
+/// contents are equal.
+///
+/// Reference: [Detailed Description of Unified Format](https://www.gnu.org/software/diffutils/manual/html_node/Detailed-Unified.html).
+pub(crate) fn unified_diff(old: &str, new: &str, old_label: &str, new_label: &str, context: usize) -> String {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+    let lines = diff_lines(&old, &new);
+
+    if lines.iter().all(|line| matches!(line, Line::Unchanged(_))) {
+        return String::new();
+    }
+
+    let mut out = format!("--- {}\n+++ {}\n", old_label, new_label);
+
+    for (start, end) in hunks(&lines, context) {
+        let (old_start, new_start) = positions(&lines[..start]);
+        let (old_len, new_len) = positions(&lines[start..end]);
+
+        out.push_str(&format!("@@ -{} +{} @@\n", range(old_start, old_len), range(new_start, new_len)));
+
+        for line in lines[start..end].iter() {
+            match line {
+                Line::Unchanged(text) => out.push_str(&format!(" {}\n", text)),
+                Line::Removed(text) => out.push_str(&format!("-{}\n", text)),
+                Line::Added(text) => out.push_str(&format!("+{}\n", text)),
+            }
+        }
+    }
+
+    out
+}
+
+/// The lines of `old` and `new` as unchanged, removed or added, from their longest common subsequence.
+/// The common prefix and suffix are split off first, keeping the table small for typical edits.
+fn diff_lines<'a>(old: &[&'a str], new: &[&'a str]) -> Vec<Line<'a>> {
+    let prefix = old.iter().zip(new.iter()).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..].iter().rev().zip(new[prefix..].iter().rev()).take_while(|(a, b)| a == b).count();
+
+    let old_mid = &old[prefix..old.len() - suffix];
+    let new_mid = &new[prefix..new.len() - suffix];
+
+    // lcs[i][j] is the length of the longest common subsequence of old_mid[i..] and new_mid[j..].
+    let mut lcs = vec![vec![0usize; new_mid.len() + 1]; old_mid.len() + 1];
+
+    for i in (0..old_mid.len()).rev() {
+        for j in (0..new_mid.len()).rev() {
+            lcs[i][j] = if old_mid[i] == new_mid[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut lines: Vec<Line> = old[..prefix].iter().map(|line| Line::Unchanged(line)).collect();
+    let (mut i, mut j) = (0, 0);
+
+    while i < old_mid.len() || j < new_mid.len() {
+        if i < old_mid.len() && j < new_mid.len() && old_mid[i] == new_mid[j] {
+            lines.push(Line::Unchanged(old_mid[i]));
+            i += 1;
+            j += 1;
+        } else if i < old_mid.len() && (j == new_mid.len() || lcs[i + 1][j] >= lcs[i][j + 1]) {
+            lines.push(Line::Removed(old_mid[i]));
+            i += 1;
+        } else {
+            lines.push(Line::Added(new_mid[j]));
+            j += 1;
+        }
+    }
+
+    lines.extend(old[old.len() - suffix..].iter().map(|line| Line::Unchanged(line)));
+
+    lines
+}
+
+/// Index ranges of `lines` to show as hunks: each change with up to `context` unchanged lines
+/// around it, merging changes whose context overlaps or touches.
+fn hunks(lines: &[Line], context: usize) -> Vec<(usize, usize)> {
+    let mut hunks: Vec<(usize, usize)> = Vec::new();
+
+    for (i, _) in lines.iter().enumerate().filter(|(_, line)| !matches!(line, Line::Unchanged(_))) {
+        let start = i.saturating_sub(context);
+        let end = (i + 1 + context).min(lines.len());
+
+        match hunks.last_mut() {
+            Some(last) if start <= last.1 => last.1 = end,
+            _ => hunks.push((start, end)),
+        }
+    }
+
+    hunks
+}
+
+/// Number of old and new lines in `lines`.
+fn positions(lines: &[Line]) -> (usize, usize) {
+    lines.iter().fold((0, 0), |(old, new), line| match line {
+        Line::Unchanged(_) => (old + 1, new + 1),
+        Line::Removed(_) => (old + 1, new),
+        Line::Added(_) => (old, new + 1),
+    })
+}
+
+/// A hunk range in `start,length` form, where `start` is one-based, or the line before the hunk if it is empty.
+fn range(lines_before: usize, len: usize) -> String {
+    match len {
+        0 => format!("{},0", lines_before),
+        _ => format!("{},{}", lines_before + 1, len),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::diff::{unified_diff, DEFAULT_CONTEXT_LINES};
+
+    #[test]
+    fn equal_contents_have_no_diff() {
+        assert_eq!(unified_diff("a\nb\n", "a\nb\n", "old", "new", DEFAULT_CONTEXT_LINES), "");
+    }
+
+    #[test]
+    fn diff_marks_added_and_removed_lines() {
+        let old = "{\n  \"require\": {\n    \"php\": \">=8.1\",\n    \"psr/log\": \"^1.0\"\n  }\n}\n";
+        let new = "{\n  \"require\": {\n    \"php\": \">=8.1\",\n    \"psr/log\": \"^3.0\"\n  }\n}\n";
+
+        assert_eq!(unified_diff(old, new, "a/composer.json", "b/composer.json", DEFAULT_CONTEXT_LINES), concat!(
+            "--- a/composer.json\n",
+            "+++ b/composer.json\n",
+            "@@ -1,6 +1,6 @@\n",
+            " {\n",
+            "   \"require\": {\n",
+            "     \"php\": \">=8.1\",\n",
+            "-    \"psr/log\": \"^1.0\"\n",
+            "+    \"psr/log\": \"^3.0\"\n",
+            "   }\n",
+            " }\n",
+        ));
+    }
+
+    #[test]
+    fn distant_changes_get_separate_hunks() {
+        let old: String = (1..=20).map(|i| format!("{}\n", i)).collect();
+        let new: String = (1..=20).filter(|i| *i != 19).map(|i| if i == 2 { "two\n".to_string() } else { format!("{}\n", i) }).collect();
+
+        let diff = unified_diff(&old, &new, "old", "new", DEFAULT_CONTEXT_LINES);
+
+        assert!(diff.contains("@@ -1,5 +1,5 @@\n 1\n-2\n+two\n 3\n"));
+        assert!(diff.contains("@@ -16,5 +16,4 @@\n 16\n 17\n 18\n-19\n 20\n"));
+    }
+
+    #[test]
+    fn context_controls_unchanged_lines_shown() {
+        let old = "a\nb\nc\nd\ne\n";
+        let new = "a\nb\nC\nd\ne\n";
+
+        assert_eq!(unified_diff(old, new, "old", "new", 0), "--- old\n+++ new\n@@ -3,1 +3,1 @@\n-c\n+C\n");
+        assert_eq!(unified_diff(old, new, "old", "new", 3), "--- old\n+++ new\n@@ -1,5 +1,5 @@\n a\n b\n-c\n+C\n d\n e\n");
+    }
+
+    #[test]
+    fn zero_context_keeps_separate_changes_apart() {
+        let diff = unified_diff("a\nb\nc\n", "A\nb\nC\n", "old", "new", 0);
+
+        assert_eq!(diff, "--- old\n+++ new\n@@ -1,1 +1,1 @@\n-a\n+A\n@@ -3,1 +3,1 @@\n-c\n+C\n");
+    }
+}
@@ -0,0 +1,99 @@
+use std::error::Error;
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+
+/// An error reading, parsing or modifying a composer.json or modify-composer.json file.
+#[derive(Debug)]
+pub enum ModifierError {
+    /// A file to read does not exist.
+    FileNotFound(PathBuf),
+
+    /// Reading or writing a file failed for any other reason.
+    Io(io::Error),
+
+    /// A file is not valid JSON, or does not have the structure of the file it is read as.
+    JsonParse {
+        /// The file being parsed, if the JSON was read from a file.
+        file: Option<String>,
+        source: serde_json::Error,
+    },
+
+    /// A YAML file, such as a `modify-composer.yaml` file, is not valid YAML, or does not have the
+    /// structure of the file it is read as.
+    YamlParse {
+        file: String,
+        source: serde_yaml::Error,
+    },
+
+    /// The input is well-formed, but not acceptable, e.g. an invalid option value or a package
+    /// that is already required.
+    Validation(String),
+
+    /// A package pattern, or another pattern such as that of `--replace-url`, cannot be compiled to a regular expression.
+    PatternCompile {
+        pattern: String,
+        source: regex::Error,
+    },
+}
+
+impl ModifierError {
+    pub(crate) fn validation(message: impl Into<String>) -> ModifierError {
+        ModifierError::Validation(message.into())
+    }
+}
+
+impl fmt::Display for ModifierError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ModifierError::FileNotFound(path) => write!(f, "File not found: {}", path.display()),
+            ModifierError::Io(e) => write!(f, "{}", e),
+            ModifierError::JsonParse { file: Some(file), source } => write!(f, "error parsing {}: {}", file, source),
+            ModifierError::JsonParse { file: None, source } => write!(f, "error parsing JSON: {}", source),
+            ModifierError::YamlParse { file, source } => write!(f, "error parsing {}: {}", file, source),
+            ModifierError::Validation(message) => f.write_str(message),
+            ModifierError::PatternCompile { pattern, source } => write!(f, "invalid pattern \"{}\": {}", pattern, source),
+        }
+    }
+}
+
+impl Error for ModifierError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ModifierError::Io(e) => Some(e),
+            ModifierError::JsonParse { source, .. } => Some(source),
+            ModifierError::YamlParse { source, .. } => Some(source),
+            ModifierError::PatternCompile { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for ModifierError {
+    fn from(e: io::Error) -> Self {
+        ModifierError::Io(e)
+    }
+}
+
+impl From<serde_json::Error> for ModifierError {
+    fn from(e: serde_json::Error) -> Self {
+        ModifierError::JsonParse { file: None, source: e }
+    }
+}
+
+/// Lets code still working with [io::Error], like the command handlers, propagate a [ModifierError] with `?`.
+impl From<ModifierError> for io::Error {
+    fn from(e: ModifierError) -> Self {
+        let kind = match &e {
+            ModifierError::FileNotFound(_) => io::ErrorKind::NotFound,
+            ModifierError::Io(e) => e.kind(),
+            ModifierError::JsonParse { .. } | ModifierError::YamlParse { .. } => io::ErrorKind::InvalidData,
+            ModifierError::Validation(_) | ModifierError::PatternCompile { .. } => io::ErrorKind::InvalidInput,
+        };
+
+        match e {
+            ModifierError::Io(e) => e,
+            e => io::Error::new(kind, e),
+        }
+    }
+}
@@ -1,32 +1,33 @@
 use std::fs;
 use std::io;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering as AtomicOrdering};
+
+use crate::error::ModifierError;
+use crate::log::info;
 
 pub(crate) trait PathAsserts {
-    fn assert_exists(&self) -> Result<&Path, io::Error>;
-    fn assert_is_file(&self) -> Result<&Path, io::Error>;
+    fn assert_exists(&self) -> Result<&Path, ModifierError>;
+    fn assert_is_file(&self) -> Result<&Path, ModifierError>;
 }
 
 impl PathAsserts for Path {
-    fn assert_exists(&self) -> Result<&Path, io::Error> {
+    fn assert_exists(&self) -> Result<&Path, ModifierError> {
         if !self.exists() {
-            return Err(
-                io::Error::new(
-                    io::ErrorKind::NotFound,
-                    format!("File not found: {}", self.display()),
-                )
-            );
+            return Err(ModifierError::FileNotFound(self.to_path_buf()));
         }
 
         Ok(self)
     }
 
-    fn assert_is_file(&self) -> Result<&Path, io::Error> {
+    fn assert_is_file(&self) -> Result<&Path, ModifierError> {
         if !self.is_file() {
             return Err(
-                io::Error::new(
-                    io::ErrorKind::InvalidInput,
-                    format!("Path is not a file: {}", self.display()),
+                ModifierError::Io(
+                    io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        format!("Path is not a file: {}", self.display()),
+                    )
                 )
             );
         }
@@ -35,10 +36,297 @@ impl PathAsserts for Path {
     }
 }
 
-pub(crate) fn get_file_path(s: &str) -> Result<&Path, io::Error> {
+pub(crate) fn get_file_path(s: &str) -> Result<&Path, ModifierError> {
     Path::new(s).assert_exists()?.assert_is_file()
 }
 
-pub(crate) fn get_file_contents(file_name: &str) -> Result<String, io::Error> {
-    fs::read_to_string(get_file_path(file_name)?)
+/// Resolves the composer.json file to read, falling back to `<file>.dist` when `allow_dist` is set
+/// and `file_name` itself does not exist.
+pub(crate) fn resolve_composer_json_path(file_name: &str, allow_dist: &bool) -> String {
+    let dist = format!("{}.dist", file_name);
+
+    if *allow_dist && !Path::new(file_name).exists() && Path::new(&dist).is_file() {
+        info!("{} not found, using {}", file_name, dist);
+
+        return dist;
+    }
+
+    file_name.to_string()
+}
+
+/// Finds all `composer.json` files in `root` and its subdirectories, sorted by path, not descending
+/// into directories named in `skip`, such as `vendor`, nor following symbolic links to directories.
+pub(crate) fn find_composer_json_files(root: &Path, skip: &[String]) -> Result<Vec<PathBuf>, ModifierError> {
+    let mut files = Vec::new();
+    let mut dirs = vec![root.to_path_buf()];
+
+    while let Some(dir) = dirs.pop() {
+        for entry in fs::read_dir(&dir)? {
+            let entry = entry?;
+            let path = entry.path();
+            let name = path.file_name().and_then(|name| name.to_str()).unwrap_or_default();
+
+            if entry.file_type()?.is_dir() {
+                if !skip.iter().any(|s| s == name) {
+                    dirs.push(path);
+                }
+            } else if name == "composer.json" {
+                files.push(path);
+            }
+        }
+    }
+
+    files.sort();
+
+    Ok(files)
+}
+
+/// The UTF-8 byte order mark, which some Windows toolchains write at the start of text files.
+const UTF8_BOM: &str = "\u{feff}";
+
+/// Default for [set_max_file_size]: 16 MiB, far larger than any sensible composer.json file.
+pub(crate) const DEFAULT_MAX_FILE_SIZE: u64 = 16 * 1024 * 1024;
+
+static MAX_FILE_SIZE: AtomicU64 = AtomicU64::new(DEFAULT_MAX_FILE_SIZE);
+
+/// Sets the size in bytes above which [get_file_contents] refuses to read a file.
+pub(crate) fn set_max_file_size(bytes: u64) {
+    MAX_FILE_SIZE.store(bytes, AtomicOrdering::Relaxed);
+}
+
+/// Reads the contents of `file_name`, stripping a leading UTF-8 byte order mark.
+///
+/// Fails without reading the file if it is larger than the size set by [set_max_file_size].
+pub(crate) fn get_file_contents(file_name: &str) -> Result<String, ModifierError> {
+    get_file_contents_with_limit(file_name, MAX_FILE_SIZE.load(AtomicOrdering::Relaxed))
+}
+
+fn get_file_contents_with_limit(file_name: &str, max_file_size: u64) -> Result<String, ModifierError> {
+    let path = get_file_path(file_name)?;
+    let size = fs::metadata(path)?.len();
+
+    if size > max_file_size {
+        return Err(
+            ModifierError::validation(
+                format!("File is too large: {} is {} bytes, the maximum is {} bytes", path.display(), size, max_file_size),
+            )
+        );
+    }
+
+    let contents = fs::read_to_string(path)?;
+
+    Ok(match contents.strip_prefix(UTF8_BOM) {
+        Some(stripped) => stripped.to_string(),
+        None => contents,
+    })
+}
+
+/// Writes `contents` to the existing file `path`, preceded by a UTF-8 byte order mark if `write_bom` is set.
+///
+/// Like Composer, the file is terminated with exactly one newline, whether `contents` ends with none or several.
+pub(crate) fn write_file_contents(path: &str, contents: &str, write_bom: &bool) -> Result<(), ModifierError> {
+    Ok(fs::write(get_file_path(path)?, with_bom(&with_trailing_newline(contents), write_bom))?)
+}
+
+/// Writes `contents` to `path` like [write_file_contents], creating the file if it does not exist.
+/// The directory containing `path` must exist.
+pub(crate) fn create_file_contents(path: &str, contents: &str, write_bom: &bool) -> Result<(), ModifierError> {
+    let path = Path::new(path);
+
+    match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() && !parent.is_dir() => {
+            return Err(
+                ModifierError::Io(
+                    io::Error::new(
+                        io::ErrorKind::NotFound,
+                        format!("Directory not found: {} (writing {})", parent.display(), path.display()),
+                    )
+                )
+            );
+        },
+        _ => {},
+    }
+
+    Ok(fs::write(path, with_bom(&with_trailing_newline(contents), write_bom))?)
+}
+
+/// Copies the existing file `path` to `<path>.bak`, returning the path of the backup.
+///
+/// An existing backup is overwritten, unless `numbered` is set, in which case the first free
+/// `<path>.bak.<n>`, counting from 1, is used instead.
+pub(crate) fn backup_file(path: &str, numbered: &bool) -> Result<String, ModifierError> {
+    let source = get_file_path(path)?;
+    let mut backup = format!("{}.bak", path);
+
+    if *numbered {
+        let mut n = 1;
+
+        while Path::new(&backup).exists() {
+            backup = format!("{}.bak.{}", path, n);
+            n += 1;
+        }
+    }
+
+    fs::copy(source, &backup)?;
+
+    Ok(backup)
+}
+
+fn with_trailing_newline(contents: &str) -> String {
+    format!("{}\n", contents.trim_end_matches('\n'))
+}
+
+fn with_bom(contents: &str, write_bom: &bool) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(UTF8_BOM.len() + contents.len());
+
+    if *write_bom {
+        bytes.extend_from_slice(UTF8_BOM.as_bytes());
+    }
+
+    bytes.extend_from_slice(contents.as_bytes());
+
+    bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use crate::error::ModifierError;
+    use crate::fs::{backup_file, create_file_contents, find_composer_json_files, get_file_contents, get_file_contents_with_limit, write_file_contents};
+    use crate::temp_dir::TempDir;
+
+    #[test]
+    fn write_bom_only_when_enabled() {
+        let dir = TempDir::new("bom");
+
+        let file = dir.join("composer.json");
+        let file_name = file.to_str().unwrap();
+        fs::write(&file, "").unwrap();
+
+        write_file_contents(file_name, "{}", &true).unwrap();
+        let with_bom = fs::read(&file).unwrap();
+        let read_back = get_file_contents(file_name).unwrap();
+
+        write_file_contents(file_name, "{}", &false).unwrap();
+        let without_bom = fs::read(&file).unwrap();
+
+        assert_eq!(with_bom, [0xEF, 0xBB, 0xBF, b'{', b'}', b'\n']);
+        assert_eq!(read_back, "{}\n");
+        assert_eq!(without_bom, b"{}\n");
+    }
+
+    #[test]
+    fn max_file_size_guard() {
+        let dir = TempDir::new("size");
+
+        let file = dir.join("composer.json");
+        let file_name = file.to_str().unwrap();
+        fs::write(&file, r#"{ "name": "foo/bar", "require": {} }"#).unwrap();
+
+        let too_large = get_file_contents_with_limit(file_name, 16);
+        let within_limit = get_file_contents_with_limit(file_name, 1024);
+
+        assert!(matches!(too_large, Err(ModifierError::Validation(_))));
+        assert!(within_limit.is_ok());
+    }
+
+    #[test]
+    fn create_file_contents_requires_existing_directory() {
+        let dir = TempDir::new("create");
+
+        let file = dir.join("out.json");
+        let missing = dir.join("missing").join("out.json");
+
+        create_file_contents(file.to_str().unwrap(), "{}", &false).unwrap();
+        let contents = fs::read_to_string(&file).unwrap();
+        let err = create_file_contents(missing.to_str().unwrap(), "{}", &false).unwrap_err();
+
+        assert_eq!(contents, "{}\n");
+        assert!(matches!(&err, ModifierError::Io(e) if e.kind() == std::io::ErrorKind::NotFound));
+        assert!(err.to_string().contains("missing"));
+    }
+
+    #[test]
+    fn write_file_contents_ends_with_single_newline() {
+        let dir = TempDir::new("newline");
+
+        let file = dir.join("composer.json");
+        let file_name = file.to_str().unwrap();
+        fs::write(&file, "").unwrap();
+
+        let mut written = Vec::new();
+
+        for contents in ["{\n    \"name\": \"foo/bar\"\n}", "{\n    \"name\": \"foo/bar\"\n}\n", "{\n    \"name\": \"foo/bar\"\n}\n\n"] {
+            write_file_contents(file_name, contents, &false).unwrap();
+            written.push(fs::read_to_string(&file).unwrap());
+        }
+
+        create_file_contents(dir.join("out.json").to_str().unwrap(), "{}", &false).unwrap();
+        let created = fs::read_to_string(dir.join("out.json")).unwrap();
+
+        assert!(written.iter().all(|w| w == "{\n    \"name\": \"foo/bar\"\n}\n"));
+        assert_eq!(created, "{}\n");
+    }
+
+    #[test]
+    fn backup_file_overwrites_or_numbers() {
+        let dir = TempDir::new("backup");
+
+        let file = dir.join("composer.json");
+        let file_name = file.to_str().unwrap();
+
+        fs::write(&file, "first").unwrap();
+        let first = backup_file(file_name, &false).unwrap();
+        fs::write(&file, "second").unwrap();
+        let overwritten = backup_file(file_name, &false).unwrap();
+        let overwritten_contents = fs::read_to_string(&overwritten).unwrap();
+        fs::write(&file, "third").unwrap();
+        let numbered = backup_file(file_name, &true).unwrap();
+        let numbered_contents = fs::read_to_string(&numbered).unwrap();
+
+        assert_eq!(first, format!("{}.bak", file_name));
+        assert_eq!(overwritten, first);
+        assert_eq!(overwritten_contents, "second");
+        assert_eq!(numbered, format!("{}.bak.1", file_name));
+        assert_eq!(numbered_contents, "third");
+    }
+
+    #[test]
+    fn get_file_contents_of_missing_file() {
+        let dir = TempDir::new("missing");
+        let missing = dir.join("composer.json");
+
+        let err = get_file_contents(missing.to_str().unwrap()).unwrap_err();
+
+        assert!(matches!(err, ModifierError::FileNotFound(path) if path == missing));
+    }
+
+    #[test]
+    fn find_composer_json_files_skips_directories() {
+        let dir = TempDir::new("find");
+        fs::create_dir_all(dir.join("packages/foo")).unwrap();
+        fs::create_dir_all(dir.join("vendor/foo/bar")).unwrap();
+
+        fs::write(dir.join("composer.json"), "{}").unwrap();
+        fs::write(dir.join("packages/foo/composer.json"), "{}").unwrap();
+        fs::write(dir.join("vendor/foo/bar/composer.json"), "{}").unwrap();
+
+        let files = find_composer_json_files(&dir, &["vendor".to_string()]).unwrap();
+
+        assert_eq!(files, [dir.join("composer.json"), dir.join("packages/foo/composer.json")]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn find_composer_json_files_does_not_follow_symlinks() {
+        let dir = TempDir::new("find-symlink");
+        fs::create_dir_all(dir.join("packages/foo")).unwrap();
+        fs::write(dir.join("packages/foo/composer.json"), "{}").unwrap();
+        std::os::unix::fs::symlink(&dir, dir.join("packages/foo/loop")).unwrap();
+
+        let files = find_composer_json_files(&dir, &[]).unwrap();
+
+        assert_eq!(files, [dir.join("packages/foo/composer.json")]);
+    }
 }
@@ -0,0 +1,46 @@
+//! Parsing and modification of `composer.json` files, as used by the `composer-json-modifier` binary.
+//!
+//! ```no_run
+//! use composer_json_modifier::{modify, ComposerJson, ModifyComposerJson};
+//!
+//! let composer: ComposerJson = serde_json::from_str(r#"{ "name": "foo/bar", "require": { "psr/log": "^1.0" } }"#)?;
+//! let spec: ModifyComposerJson = serde_json::from_str(r#"{ "add": { "require": { "psr/log": "^3.0" } } }"#)?;
+//!
+//! let modified = modify(composer, &spec)?;
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use crate::modify::{apply, ModifyOptions};
+use crate::profile::Profile;
+
+pub use crate::composer_json::{CommandEvent, ComposerJson, Config, InstallerEvent, PackageEvent, PackageLinks, PluginEvent, Repository, RepositoryType, ScriptEventType, Stability};
+pub use crate::error::ModifierError;
+pub use crate::modify_composer_json::{ModifyComposerJson, ModifyComposerJsonBuilder, PackagePattern};
+
+pub mod cli;
+
+mod composer_json;
+mod constraint;
+mod diff;
+mod error;
+mod modify_composer_json;
+mod parse_handler;
+mod profile;
+mod fs;
+mod lock;
+mod log;
+mod merge;
+mod modify;
+mod patch;
+mod report;
+mod spdx;
+mod validate;
+
+#[cfg(test)]
+mod temp_dir;
+
+/// Applies `spec` to `composer` in memory, the way `modify run` applies a modify-composer.json
+/// file with default options.
+pub fn modify(composer: ComposerJson, spec: &ModifyComposerJson) -> Result<ComposerJson, ModifierError> {
+    apply(composer, spec, &ModifyOptions::default(), &mut Profile::default())
+}
@@ -0,0 +1,162 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+
+use serde::{Deserialize, Serialize};
+
+use crate::composer_json::{is_platform_package, ComposerJson, Stability};
+use crate::parse_handler::{ParseFile, ParseFileType};
+
+/// Notice written to the `_readme` field of a [ComposerLockStub], in place of Composer's own.
+const STUB_README: [&str; 2] = [
+    "This file is a stub generated by composer-json-modifier, not by Composer, and has not been resolved.",
+    "The versions listed are the constraints declared in composer.json, not installed versions.",
+];
+
+/// A minimal `composer.lock`-shaped document listing the declared packages and their constraints,
+/// for tools that expect a lock file to be present.
+///
+/// Reference: [Commit your composer.lock file](https://getcomposer.org/doc/01-basic-usage.md#commit-your-composer-lock-file-to-version-control).
+#[derive(Debug, Clone, Serialize)]
+pub struct ComposerLockStub {
+    #[serde(rename = "_readme")]
+    pub readme: Vec<String>,
+
+    pub packages: Vec<LockedPackageStub>,
+
+    #[serde(rename = "packages-dev")]
+    pub packages_dev: Vec<LockedPackageStub>,
+
+    #[serde(rename = "minimum-stability")]
+    pub minimum_stability: Stability,
+}
+
+/// A package of a [ComposerLockStub], with its declared constraint in place of a resolved version.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LockedPackageStub {
+    pub name: String,
+
+    pub version: String,
+}
+
+impl ComposerLockStub {
+    /// Builds a stub from the `require` and `require-dev` links of `c`, sorted by package name.
+    /// Platform packages, like `php` and `ext-json`, are left out, as Composer does not lock them.
+    pub fn new(c: &ComposerJson) -> ComposerLockStub {
+        ComposerLockStub {
+            readme: STUB_README.iter().map(|line| line.to_string()).collect(),
+            packages: locked_packages(c.package_links.require.iter()),
+            packages_dev: locked_packages(c.package_links.require_dev.iter().flatten()),
+            minimum_stability: c.effective_minimum_stability(),
+        }
+    }
+
+    /// Writes the stub as JSON to `path`.
+    pub(crate) fn write(&self, path: &str) -> Result<(), io::Error> {
+        fs::write(path, format!("{}\n", serde_json::to_string_pretty(self)?))
+    }
+}
+
+/// A `composer.lock` file, as written by Composer, with the installed packages and the hash of the
+/// composer.json contents they were resolved from.
+///
+/// Only the fields needed to cross-check it against composer.json are read.
+///
+/// Reference: [Commit your composer.lock file](https://getcomposer.org/doc/01-basic-usage.md#commit-your-composer-lock-file-to-version-control).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LockFile {
+    #[serde(rename = "content-hash")]
+    pub content_hash: String,
+
+    #[serde(default)]
+    pub packages: Vec<LockedPackage>,
+
+    #[serde(rename = "packages-dev")]
+    #[serde(default)]
+    pub packages_dev: Vec<LockedPackage>,
+}
+
+impl ParseFile for LockFile {
+    fn parse_file_type() -> ParseFileType {
+        ParseFileType::ComposerLock
+    }
+}
+
+/// A package installed by a [LockFile], with its resolved version, e.g. `v6.3.0`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LockedPackage {
+    pub name: String,
+
+    pub version: String,
+}
+
+fn locked_packages<'a>(links: impl Iterator<Item = (&'a String, &'a String)>) -> Vec<LockedPackageStub> {
+    links.filter(|(name, _)| !is_platform_package(name))
+        .collect::<BTreeMap<&String, &String>>()
+        .into_iter()
+        .map(|(name, constraint)| LockedPackageStub { name: name.clone(), version: constraint.clone() })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::composer_json::ComposerJson;
+    use crate::lock::{ComposerLockStub, LockFile, LockedPackage, LockedPackageStub};
+
+    fn composer_json(json: &str) -> ComposerJson {
+        serde_json::from_str(json).unwrap()
+    }
+
+    fn package(name: &str, version: &str) -> LockedPackageStub {
+        LockedPackageStub { name: name.to_string(), version: version.to_string() }
+    }
+
+    #[test]
+    fn stub_lists_each_require_package_but_platform_packages() {
+        let c = composer_json(r#"{
+            "name": "foo/bar",
+            "require": { "symfony/yaml": "^6.0", "php": ">=8.1", "ext-json": "*", "monolog/monolog": "^3.0" },
+            "require-dev": { "phpunit/phpunit": "^10.0", "ext-xdebug": "*" }
+        }"#);
+        let stub = ComposerLockStub::new(&c);
+
+        assert_eq!(stub.packages, vec![
+            package("monolog/monolog", "^3.0"),
+            package("symfony/yaml", "^6.0"),
+        ]);
+        assert_eq!(stub.packages_dev, vec![package("phpunit/phpunit", "^10.0")]);
+    }
+
+    #[test]
+    fn parse_lock_file() {
+        let lock: LockFile = serde_json::from_str(r#"{
+            "_readme": ["This file locks the dependencies of your project to a known state"],
+            "content-hash": "d41d8cd98f00b204e9800998ecf8427e",
+            "packages": [
+                { "name": "psr/log", "version": "3.0.0", "type": "library" },
+                { "name": "symfony/yaml", "version": "v6.3.0", "type": "library" }
+            ],
+            "packages-dev": [
+                { "name": "phpunit/phpunit", "version": "10.2.0" }
+            ],
+            "minimum-stability": "stable"
+        }"#).unwrap();
+
+        assert_eq!(lock.content_hash, "d41d8cd98f00b204e9800998ecf8427e");
+        assert_eq!(lock.packages, vec![
+            LockedPackage { name: "psr/log".to_string(), version: "3.0.0".to_string() },
+            LockedPackage { name: "symfony/yaml".to_string(), version: "v6.3.0".to_string() },
+        ]);
+        assert_eq!(lock.packages_dev.len(), 1);
+    }
+
+    #[test]
+    fn stub_is_marked_as_unresolved() {
+        let stub = ComposerLockStub::new(&composer_json(r#"{ "name": "foo/bar", "require": {} }"#));
+        let json = serde_json::to_value(&stub).unwrap();
+
+        assert!(json["_readme"][0].as_str().unwrap().contains("stub"));
+        assert_eq!(json["packages-dev"], serde_json::json!([]));
+        assert_eq!(json["minimum-stability"], "stable");
+    }
+}
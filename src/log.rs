@@ -0,0 +1,90 @@
+use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
+
+/// How much informational output to print, set once from `--quiet` and `--debug` by [set_verbosity].
+///
+/// Errors, warnings and the output a command was asked for, like `--print` or a report, are
+/// printed regardless.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) enum Verbosity {
+    /// Print no informational messages, as given by `--quiet`.
+    Quiet = 0,
+
+    /// Print informational messages, such as which file is being modified.
+    Normal = 1,
+
+    /// Also print debugging information, as given by `--debug`.
+    Debug = 2,
+}
+
+impl Verbosity {
+    /// The verbosity for the `--quiet` flag and the number of times `--debug` was given.
+    pub(crate) fn from_flags(quiet: bool, debug: u8) -> Verbosity {
+        match (quiet, debug) {
+            (true, _) => Verbosity::Quiet,
+            (false, 0) => Verbosity::Normal,
+            (false, _) => Verbosity::Debug,
+        }
+    }
+}
+
+static VERBOSITY: AtomicU8 = AtomicU8::new(Verbosity::Normal as u8);
+
+/// Sets the verbosity checked by [enabled], and thus by [info] and [debug].
+pub(crate) fn set_verbosity(verbosity: Verbosity) {
+    VERBOSITY.store(verbosity as u8, Ordering::Relaxed);
+}
+
+/// Whether messages of `verbosity` are printed.
+pub(crate) fn enabled(verbosity: Verbosity) -> bool {
+    VERBOSITY.load(Ordering::Relaxed) >= verbosity as u8
+}
+
+static INFO_TO_STDERR: AtomicBool = AtomicBool::new(false);
+
+/// Makes [info] print to stderr instead of stdout, for when stdout carries the output a command
+/// was asked for, like a JSON Patch, that informational messages must not be mixed into.
+pub(crate) fn set_info_to_stderr(to_stderr: bool) {
+    INFO_TO_STDERR.store(to_stderr, Ordering::Relaxed);
+}
+
+/// Whether [info] prints to stderr, see [set_info_to_stderr].
+pub(crate) fn info_to_stderr() -> bool {
+    INFO_TO_STDERR.load(Ordering::Relaxed)
+}
+
+/// Prints an informational message to stdout like `println!`, unless running with `--quiet`, or to
+/// stderr if [set_info_to_stderr] was set.
+macro_rules! info {
+    ($($arg:tt)*) => {
+        if $crate::log::enabled($crate::log::Verbosity::Normal) {
+            match $crate::log::info_to_stderr() {
+                true => eprintln!($($arg)*),
+                false => println!($($arg)*),
+            }
+        }
+    }
+}
+
+/// Prints a debugging message to stderr like `eprintln!`, only when running with `--debug`.
+macro_rules! debug {
+    ($($arg:tt)*) => {
+        if $crate::log::enabled($crate::log::Verbosity::Debug) {
+            eprintln!($($arg)*);
+        }
+    }
+}
+
+pub(crate) use {debug, info};
+
+#[cfg(test)]
+mod tests {
+    use crate::log::Verbosity;
+
+    #[test]
+    fn verbosity_from_flags() {
+        assert_eq!(Verbosity::from_flags(false, 0), Verbosity::Normal);
+        assert_eq!(Verbosity::from_flags(false, 2), Verbosity::Debug);
+        assert_eq!(Verbosity::from_flags(true, 1), Verbosity::Quiet);
+        assert!(Verbosity::Quiet < Verbosity::Normal && Verbosity::Normal < Verbosity::Debug);
+    }
+}
@@ -0,0 +1,122 @@
+use std::io;
+
+use clap::ValueEnum;
+use serde_json::Value;
+
+use crate::composer_json::ComposerJson;
+use crate::fs::create_file_contents;
+use crate::parse_handler::ParseFileType;
+
+/// How [merge] combines arrays present in both files, as given to `--arrays`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub(crate) enum ArrayStrategy {
+    /// Append the overlay's items that the base does not already contain
+    #[default]
+    Append,
+
+    /// Use the overlay's array instead of the base's
+    Replace,
+}
+
+/// Deep-merges `overlay` into `base`.
+///
+/// Objects, such as `require` or `config`, are merged key by key, with values from `overlay` taking
+/// precedence. Scalars from `overlay` replace those of `base`, and arrays, such as `authors`,
+/// are combined according to `arrays`.
+pub(crate) fn merge(base: &ComposerJson, overlay: &ComposerJson, arrays: ArrayStrategy) -> Result<ComposerJson, io::Error> {
+    let mut merged = serde_json::to_value(base)?;
+
+    merge_values(&mut merged, serde_json::to_value(overlay)?, arrays);
+
+    Ok(serde_json::from_value(merged)?)
+}
+
+fn merge_values(base: &mut Value, overlay: Value, arrays: ArrayStrategy) {
+    match (base, overlay) {
+        (Value::Object(base), Value::Object(overlay)) => {
+            for (key, value) in overlay {
+                match base.get_mut(&key) {
+                    Some(existing) => merge_values(existing, value, arrays),
+                    None => {
+                        base.insert(key, value);
+                    },
+                }
+            }
+        },
+        (Value::Array(base), Value::Array(overlay)) if arrays == ArrayStrategy::Append => {
+            for item in overlay {
+                if !base.contains(&item) {
+                    base.push(item);
+                }
+            }
+        },
+        (base, overlay) => *base = overlay,
+    }
+}
+
+/// Merges the composer.json file `overlay_file_name` into `base_file_name`, writing the result
+/// to `output` or printing it when no output file is given.
+pub(crate) fn handle_merge(base_file_name: &str, overlay_file_name: &str, output: Option<&str>, arrays: ArrayStrategy) -> Result<(), io::Error> {
+    let base = ParseFileType::ComposerJson._handle_parse_and_return::<ComposerJson>(base_file_name)?;
+    let overlay = ParseFileType::ComposerJson._handle_parse_and_return::<ComposerJson>(overlay_file_name)?;
+    let contents = format!("{}\n", serde_json::to_string_pretty(&merge(&base, &overlay, arrays)?)?);
+
+    match output {
+        Some(output) => Ok(create_file_contents(output, &contents, &false)?),
+        None => {
+            print!("{}", contents);
+
+            Ok(())
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::composer_json::ComposerJson;
+    use crate::merge::{merge, ArrayStrategy};
+
+    fn composer_json(value: serde_json::Value) -> ComposerJson {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn merge_unions_require_with_overlay_precedence() {
+        let base = composer_json(json!({ "name": "foo/base", "require": { "php": ">=8.1", "psr/log": "^1.0" } }));
+        let overlay = composer_json(json!({ "name": "foo/base", "require": { "psr/log": "^3.0", "monolog/monolog": "^3.0" } }));
+
+        let merged = merge(&base, &overlay, ArrayStrategy::Append).unwrap();
+
+        assert_eq!(serde_json::to_value(&merged.package_links.require).unwrap(), json!({
+            "php": ">=8.1",
+            "psr/log": "^3.0",
+            "monolog/monolog": "^3.0",
+        }));
+    }
+
+    #[test]
+    fn merge_overrides_scalars() {
+        let base = composer_json(json!({ "name": "foo/base", "description": "Base", "prefer-stable": false, "require": {} }));
+        let overlay = composer_json(json!({ "name": "foo/overlay", "prefer-stable": true, "require": {} }));
+
+        let merged = merge(&base, &overlay, ArrayStrategy::Append).unwrap();
+
+        assert_eq!(merged.name, "foo/overlay");
+        assert_eq!(merged.description.as_deref(), Some("Base"));
+        assert_eq!(merged.prefer_stable, Some(true));
+    }
+
+    #[test]
+    fn merge_appends_or_replaces_authors() {
+        let base = composer_json(json!({ "name": "foo/bar", "require": {}, "authors": [{ "name": "Alice", "email": "alice@example.org" }, { "name": "Bob", "email": "bob@example.org" }] }));
+        let overlay = composer_json(json!({ "name": "foo/bar", "require": {}, "authors": [{ "name": "Bob", "email": "bob@example.org" }, { "name": "Carol", "email": "carol@example.org" }] }));
+
+        let appended = merge(&base, &overlay, ArrayStrategy::Append).unwrap();
+        let replaced = merge(&base, &overlay, ArrayStrategy::Replace).unwrap();
+
+        assert_eq!(serde_json::to_value(&appended.authors).unwrap(), json!([{ "name": "Alice", "email": "alice@example.org" }, { "name": "Bob", "email": "bob@example.org" }, { "name": "Carol", "email": "carol@example.org" }]));
+        assert_eq!(serde_json::to_value(&replaced.authors).unwrap(), json!([{ "name": "Bob", "email": "bob@example.org" }, { "name": "Carol", "email": "carol@example.org" }]));
+    }
+}
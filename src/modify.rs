@@ -1,40 +1,2507 @@
-use std::io;
+use std::cmp::Ordering;
+use std::fmt;
+use std::str::FromStr;
 
-use crate::composer_json::ComposerJson;
-use crate::modify_composer_json::{ModifyComposerJson,Require};
+use clap::ValueEnum;
+use indexmap::IndexMap;
+use regex::Regex;
+use serde::Serialize;
+use serde_json::ser::PrettyFormatter;
+use serde_json::{json, Map, Value};
+
+use crate::composer_json::{compare_package_names, AllowPlugins, ComposerJson, Config, EnsureResult, Repository};
+use crate::constraint::{to_caret, ConstraintStyle};
+use crate::diff::{unified_diff, DEFAULT_CONTEXT_LINES};
+use crate::error::ModifierError;
+use crate::fs::{backup_file, create_file_contents, get_file_contents, resolve_composer_json_path, write_file_contents};
+use crate::lock::ComposerLockStub;
+use crate::log::{debug, info};
+use crate::modify_composer_json::{AddConfig,ModifyComposerJson,ModifyConfig,PackagePattern,RemoveConfig,ReplaceConfig,Require,VersionConstraint};
 use crate::parse_handler::ParseFileType;
+use crate::patch::diff_to_json;
+use crate::profile::Profile;
+use crate::report::{MapSelection, Report, ReportFormat};
+use crate::validate::new_require_entry;
+
+/// Key in `extra` under which [annotate] records which directives modified the file.
+const ANNOTATION_KEY: &str = "_modifier";
+
+/// A migration of deprecated composer.json features, as given to `--convert`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub(crate) enum Conversion {
+    /// Move PSR-0 autoload mappings to PSR-4 where possible
+    #[value(name = "psr0-to-psr4")]
+    Psr0ToPsr4,
+}
+
+/// What adding a package does when it is already required with another constraint, as given to `--on-conflict`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub(crate) enum OnConflict {
+    /// Keep the existing constraint
+    Skip,
+
+    /// Replace the existing constraint
+    #[default]
+    Overwrite,
+
+    /// Abort the run
+    Error,
+}
+
+/// What [handle_modify] outputs, as given to `--output-format`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub(crate) enum OutputFormat {
+    /// Write the modified composer.json
+    #[default]
+    File,
+
+    /// Print a JSON Patch (RFC 6902) transforming the original composer.json into the modified one, leaving the file as is
+    Patch,
+}
+
+/// Indentation of the written composer.json, as given to `--indent`: a number of spaces, or `tab`.
+///
+/// Defaults to four spaces, the indentation Composer itself writes composer.json files with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Indent {
+    Spaces(usize),
+    Tab,
+}
+
+impl Default for Indent {
+    fn default() -> Self {
+        Indent::Spaces(4)
+    }
+}
+
+impl fmt::Display for Indent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Indent::Spaces(n) => write!(f, "{}", n),
+            Indent::Tab => f.write_str("tab"),
+        }
+    }
+}
+
+impl FromStr for Indent {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "tab" => Ok(Indent::Tab),
+            _ => s.parse::<usize>()
+                .map(Indent::Spaces)
+                .map_err(|_| format!("expected a number of spaces or \"tab\", got \"{}\"", s)),
+        }
+    }
+}
+
+impl Indent {
+    /// Serializes `value` as pretty-printed JSON, indenting each level with this indentation.
+    pub(crate) fn format_json<T: Serialize>(&self, value: &T) -> Result<String, serde_json::Error> {
+        let indent = match self {
+            Indent::Spaces(n) => " ".repeat(*n),
+            Indent::Tab => "\t".to_string(),
+        };
+        let mut bytes = Vec::new();
+        let mut serializer = serde_json::Serializer::with_formatter(&mut bytes, PrettyFormatter::with_indent(indent.as_bytes()));
+
+        value.serialize(&mut serializer)?;
+
+        Ok(String::from_utf8(bytes).expect("serde_json only writes valid UTF-8"))
+    }
+}
+
+/// Options controlling how [handle_modify] applies a modify-composer.json file.
+#[derive(Debug, Default)]
+pub(crate) struct ModifyOptions {
+    pub dry_run: bool,
+    pub report_format: Option<ReportFormat>,
+    pub validate_names: bool,
+    pub annotate: bool,
+    pub prune_platform_duplicates: bool,
+
+    /// `package=constraint` pairs to add or replace in `require`, on top of the modify-composer.json file.
+    pub ensure: Vec<String>,
+
+    /// Package patterns to remove from `require` and `require-dev`, on top of the modify-composer.json file.
+    pub drop: Vec<String>,
+
+    /// Path to write the duration of each phase of the run to, as JSON.
+    pub profile: Option<String>,
+
+    /// Read (and write) `composer.json.dist` if the given composer.json file does not exist.
+    pub allow_dist: bool,
+
+    /// Path to write a `composer.lock`-shaped stub of the modified requirements to, whenever the
+    /// modified composer.json is written.
+    pub emit_lock_stub: Option<String>,
+
+    /// `from=to` rewrites to apply to repository URLs, see [UrlReplacement::parse].
+    pub replace_url: Vec<String>,
+
+    /// Also apply [ModifyOptions::replace_url] to `homepage` and `support.source`.
+    pub replace_metadata_urls: bool,
+
+    /// Prepend a UTF-8 byte order mark to the written composer.json file.
+    pub write_bom: bool,
+
+    /// Copy the composer.json file to `<file>.bak` before overwriting it.
+    pub backup: bool,
+
+    /// Keep existing backups, writing to the first free `<file>.bak.<n>` instead, see [backup_file].
+    pub backup_numbered: bool,
+
+    /// Rewrite plain single-version constraints to this style.
+    pub constraint_style: Option<ConstraintStyle>,
+
+    /// Path to a file listing packages to require, one `name constraint` pair per line.
+    pub from_package_list: Option<String>,
+
+    /// Require the packages of [ModifyOptions::from_package_list] in `require-dev` instead of `require`.
+    pub dev: bool,
+
+    /// Whether to write the modified file, or print a patch of the changes instead.
+    pub output_format: OutputFormat,
+
+    /// Indentation of the written file.
+    pub indent: Indent,
+
+    /// Print each directive of the modify-composer.json file and how many entries it affected.
+    pub print_applied: bool,
+
+    /// What adding a package already required with another constraint does, for `add.require`,
+    /// `--ensure` and `--from-package-list` alike.
+    pub on_conflict: OnConflict,
+
+    /// Migrations to apply after the modifications.
+    pub convert: Vec<Conversion>,
+
+    /// Path to write the modified composer.json to, instead of overwriting the original.
+    pub output: Option<String>,
+
+    /// Like [ModifyOptions::dry_run], but fail with [ModifierError::Validation] if the modifications
+    /// change the file, e.g. to assert in CI that it is up to date.
+    pub check: bool,
+
+    /// Number of unchanged lines to show around each change in the dry-run diff, defaulting to
+    /// [DEFAULT_CONTEXT_LINES].
+    pub diff_context: Option<usize>,
+
+    /// Apply `m` to the parsed JSON with [apply_from_value] instead of through [ComposerJson],
+    /// leaving everything but the package links untouched.
+    pub lossless: bool,
+
+    /// Fail with [ModifierError::Validation] if a `remove` pattern matches no package, instead of
+    /// only warning, see [Applied::unmatched_removal_patterns].
+    pub error_on_unmatched: bool,
+
+    /// Fail with [ModifierError::Validation] if the composer.json file has an object key given
+    /// more than once, instead of using its last value.
+    pub reject_duplicate_keys: bool,
+}
+
+impl ModifyOptions {
+    /// Whether [handle_modify] prints a JSON Patch to stdout, rather than writing it to [ModifyOptions::output].
+    pub(crate) fn prints_patch(&self) -> bool {
+        self.output_format == OutputFormat::Patch && (self.output.is_none() || self.dry_run || self.check)
+    }
+}
+
+pub(crate) fn handle_modify(composer_json_file_name: &str, modify_file_name: Option<&str>, options: &ModifyOptions) -> Result<ComposerJson, ModifierError> {
+    let mut profile = Profile::default();
+    let composer_json_file_name = &resolve_composer_json_path(composer_json_file_name, &options.allow_dist);
 
-pub(crate) fn handle_modify(composer_json_file_name: &str, modify_file_name: &str, _print: &bool, dry_run: &bool) -> Result<(), io::Error> {
     //let mut c = ParseFileType::ComposerJson.parse(composer_json_file_name)?;
     //let m = ParseFileType::ModifyComposerJson.parse(modify_file_name)?;
-    let c = ParseFileType::ComposerJson._handle_parse_and_return::<ComposerJson>(composer_json_file_name)?;
-    let m = ParseFileType::ModifyComposerJson._handle_parse_and_return::<ModifyComposerJson>(modify_file_name)?;
+    let (c, m) = profile.time("parse", || -> Result<(ComposerJson, ModifyComposerJson), ModifierError> {
+        let c = ParseFileType::ComposerJson.parse_checked::<ComposerJson>(composer_json_file_name, &options.reject_duplicate_keys)?;
+        let m = match modify_file_name {
+            Some(modify_file_name) => ParseFileType::ModifyComposerJson._handle_parse_and_return::<ModifyComposerJson>(modify_file_name)?,
+            None => ModifyComposerJson::default(),
+        };
+        let m = with_ensured(m, &options.ensure)?;
+        let m = with_dropped(m, &options.drop)?;
 
-    let d = remove(c, &m, &dry_run)?;
+        Ok((c, m))
+    })?;
+    let original = c.clone();
+    let on_disk = get_file_contents(composer_json_file_name)?;
+    let mut applied = Applied::new(options.print_applied || options.annotate);
+    let (d, original_contents, contents) = if options.lossless {
+        let mut value: Value = serde_json::from_str(&on_disk)?;
+        let original_contents = format!("{}\n", options.indent.format_json(&value)?);
 
-    Ok(())
+        profile.time("apply", || apply_from_value(&mut value, &m, options, &mut applied))?;
+
+        (serde_json::from_value(value.clone())?, original_contents, format!("{}\n", options.indent.format_json(&value)?))
+    } else {
+        let d = transform(c, &m, options, &mut profile, &mut applied)?;
+        let contents = format!("{}\n", options.indent.format_json(&d)?);
+
+        (d, format!("{}\n", options.indent.format_json(&original)?), contents)
+    };
+    let unmatched = applied.unmatched_removal_patterns();
+
+    for pattern in unmatched.iter() {
+        eprintln!("warning: remove pattern \"{}\" matched no package", pattern);
+    }
+
+    if options.error_on_unmatched && !unmatched.is_empty() {
+        return Err(ModifierError::validation(format!("{} remove pattern(s) matched no package in {}", unmatched.len(), composer_json_file_name)));
+    }
+
+    let dry_run = options.dry_run || options.check;
+
+    profile.time("write", || -> Result<(), ModifierError> {
+        match options.report_format {
+            Some(format) => println!("{}", Report::new(&original, &d, MapSelection::Both).render(format)),
+            None => info!("{}", original.diff_summary_text(&d).trim_end()),
+        }
+
+        if options.output_format == OutputFormat::Patch {
+            let patch = diff_to_json(&serde_json::from_str(&original_contents)?, &serde_json::from_str(&contents)?)?;
+
+            match &options.output {
+                Some(output) if !options.prints_patch() => create_file_contents(output, &format!("{}\n", patch), &false)?,
+                _ => println!("{}", patch),
+            }
+        } else if dry_run {
+            let context = options.diff_context.unwrap_or(DEFAULT_CONTEXT_LINES);
+            let before = match options.check {
+                true => &on_disk,
+                false => &original_contents,
+            };
+
+            print!("{}", unified_diff(before, &contents, composer_json_file_name, composer_json_file_name, context));
+        } else if let Some(output) = &options.output {
+            create_file_contents(output, &contents, &options.write_bom)?;
+        } else {
+            if options.backup {
+                info!("backed up {} to {}", composer_json_file_name, backup_file(composer_json_file_name, &options.backup_numbered)?);
+            }
+
+            write_file_contents(composer_json_file_name, &contents, &options.write_bom)?;
+        }
+
+        if let (Some(path), OutputFormat::File, false) = (&options.emit_lock_stub, options.output_format, dry_run) {
+            ComposerLockStub::new(&d).write(path)?;
+        }
+
+        Ok(())
+    })?;
+
+    if options.print_applied {
+        for directive in applied.directives.iter() {
+            println!("{}", directive);
+        }
+    }
+
+    if let Some(path) = &options.profile {
+        profile.write(path)?;
+    }
+
+    if options.check && on_disk != contents {
+        return Err(ModifierError::validation(format!("{} is not up to date, modifying it would change it", composer_json_file_name)));
+    }
+
+    Ok(d)
 }
 
-fn remove(c: ComposerJson, m: &ModifyComposerJson, dry_run: &bool) -> Result<ComposerJson, io::Error> {
-    return match &m.remove {
-        Some(remove) => {
-            return match &remove.require {
-                Some(require) => remove_require(c, &require, dry_run),
-                None => Ok(c)
+/// Removes the packages matching `package` from every package link section of the composer.json
+/// file, printing which sections were affected, and writes the file unless `dry_run` is set.
+pub(crate) fn handle_purge_package(composer_json_file_name: &str, package: &str, dry_run: &bool) -> Result<ComposerJson, ModifierError> {
+    let pattern = PackagePattern::new(package)
+        .map_err(|e| ModifierError::PatternCompile { pattern: package.to_string(), source: e })?;
+    let mut c = ParseFileType::ComposerJson._handle_parse_and_return::<ComposerJson>(composer_json_file_name)?;
+
+    let purged = c.purge_package(&pattern);
+
+    match purged.is_empty() {
+        true => info!("{} is not in any package link section", package),
+        false => info!("removed {} from {} section(s): {}", package, purged.len(), purged.join(", ")),
+    }
+
+    if !purged.is_empty() && !*dry_run {
+        write_file_contents(composer_json_file_name, &Indent::default().format_json(&c)?, &false)?;
+    }
+
+    Ok(c)
+}
+
+/// Applies `m` to `c` through the typed [ComposerJson], along with the options that only the typed
+/// path supports, such as URL replacement and annotation. What each directive of `m` and each of
+/// these options did is recorded in `applied`.
+fn transform(c: ComposerJson, m: &ModifyComposerJson, options: &ModifyOptions, profile: &mut Profile, applied: &mut Applied) -> Result<ComposerJson, ModifierError> {
+    let package_list = match &options.from_package_list {
+        Some(file_name) => parse_package_list(&get_file_contents(file_name)?)?,
+        None => Vec::new(),
+    };
+    let url_replacements = options.replace_url.iter()
+        .map(|r| UrlReplacement::parse(r))
+        .collect::<Result<Vec<UrlReplacement>, ModifierError>>()?;
+
+    let original = c.clone();
+    let d = apply_recording(c, m, options, profile, applied)?;
+
+    let before = serde_json::to_value(&d)?;
+    let mut d = profile.time("replace_url", || replace_urls(d, &url_replacements, &options.replace_metadata_urls));
+
+    if !url_replacements.is_empty() {
+        let changed = before != serde_json::to_value(&d)?;
+        applied.push(|| format!("replace-url {}", options.replace_url.join(" ")), usize::from(changed), "rewritten");
+    }
+
+    profile.time("package_list", || -> Result<(), ModifierError> {
+        let required = |c: &ComposerJson, name: &str| match options.dev {
+            true => c.package_links.require_dev.as_ref().and_then(|packages| packages.get(name)).cloned(),
+            false => c.package_links.require.get(name).cloned(),
+        };
+
+        for (name, constraint) in package_list.iter() {
+            let before = required(&d, name);
+
+            add_package(&mut d, name, constraint, options.dev, options.on_conflict)?;
+
+            let changed = before != required(&d, name);
+            applied.push(|| format!("from-package-list {} {}", name, constraint), usize::from(changed), "added");
+        }
+
+        Ok(())
+    })?;
+
+    if !package_list.is_empty() {
+        d = profile.time("sort", || sort(d, m, &mut Applied::default()));
+    }
+
+    if let Some(style) = options.constraint_style {
+        let before = serde_json::to_value(&d)?;
+        profile.time("constraint_style", || d.apply_version_policy(style));
+
+        let changed = before != serde_json::to_value(&d)?;
+        applied.push(|| format!("constraint-style {}", value_name(style)), usize::from(changed), "restyled");
+    }
+
+    for conversion in options.convert.iter() {
+        let before = serde_json::to_value(&d)?;
+
+        match conversion {
+            Conversion::Psr0ToPsr4 => {
+                for prefix in profile.time("convert", || d.convert_psr0_to_psr4()) {
+                    eprintln!("warning: autoload.psr-0 entry \"{}\" cannot be converted to PSR-4 and was left unchanged", prefix);
+                }
+            },
+        }
+
+        let changed = before != serde_json::to_value(&d)?;
+        applied.push(|| format!("convert {}", value_name(*conversion)), usize::from(changed), "converted");
+    }
+
+    if options.prune_platform_duplicates {
+        let pruned = profile.time("prune_platform_duplicates", || d.prune_platform_duplicates());
+
+        for name in pruned.iter() {
+            info!("removed platform requirement {} already satisfied by config.platform", name);
+        }
+
+        applied.push(|| "prune-platform-duplicates".to_string(), pruned.len(), "removed");
+    }
+
+    if options.annotate && serde_json::to_value(&original)? != serde_json::to_value(&d)? {
+        d = profile.time("annotate", || annotate(d, applied, &chrono::Utc::now().to_rfc3339()));
+    }
+
+    Ok(d)
+}
+
+/// Applies the phases of `m` to `c` in a fixed order, timing each phase in `profile`:
+///
+/// 1. `remove` drops packages matching the given patterns,
+/// 2. `modify` changes the constraints of required packages matching the given patterns,
+/// 3. `add` adds packages, overwriting the constraints of those already required,
+/// 4. `replace` changes the constraints of packages that are required by now.
+///
+/// A package removed by `remove` can thus be re-added by `add`, ending up with the added constraint.
+///
+/// No files are read or written; [handle_modify] runs the same phases, through [transform], between
+/// reading and writing the files.
+pub(crate) fn apply(c: ComposerJson, m: &ModifyComposerJson, options: &ModifyOptions, profile: &mut Profile) -> Result<ComposerJson, ModifierError> {
+    apply_recording(c, m, options, profile, &mut Applied::default())
+}
+
+/// Like [apply], additionally recording in `applied` what each directive of `m` did, as reported
+/// by the phases themselves.
+pub(crate) fn apply_recording(c: ComposerJson, m: &ModifyComposerJson, options: &ModifyOptions, profile: &mut Profile, applied: &mut Applied) -> Result<ComposerJson, ModifierError> {
+    let c = profile.time("remove", || remove(c, m, &options.dry_run, applied))?;
+    let c = profile.time("modify", || modify(c, m, applied))?;
+    let c = profile.time("add", || add(c, m, options, applied))?;
+    let c = profile.time("replace", || replace(c, m, applied));
+    let c = profile.time("config", || merge_config(c, m, applied));
+    let c = profile.time("sort", || sort(c, m, applied));
+
+    let c = match (&m.unset, &m.set) {
+        (None, None) => c,
+        (unset, set) => profile.time("set", || -> Result<ComposerJson, ModifierError> {
+            let mut value = serde_json::to_value(&c)?;
+
+            for pointer in unset.iter().flatten() {
+                let removed = unset_pointer(&mut value, pointer)?;
+                applied.push(|| format!("unset {}", pointer), usize::from(removed), "removed");
+            }
+
+            let set = match set {
+                Some(set) => set,
+                None => return Ok(serde_json::from_value(value)?),
+            };
+
+            set_pointers(&mut value, set)?;
+
+            let c: ComposerJson = serde_json::from_value(value.clone())?;
+
+            if let Some(pointer) = unmodelled_pointer(set, &value, &c)? {
+                return Err(ModifierError::validation(format!("cannot set JSON Pointer \"{}\": it is not a field of composer.json that is kept outside of --lossless mode", pointer)));
+            }
+
+            for pointer in set.keys() {
+                applied.push(|| format!("set {}", pointer), 1, "set");
             }
+
+            Ok(c)
+        })?,
+    };
+
+    Ok(c)
+}
+
+/// Sets each JSON Pointer of `set` in `value` to its value, creating missing objects along the
+/// way, so `/config/platform/php` can be set in a file without `config`.
+///
+/// The last reference token of a pointer into an array may be an existing index, or `-` or the
+/// array length to append to it.
+///
+/// Reference: [RFC 6901](https://www.rfc-editor.org/rfc/rfc6901).
+pub(crate) fn set_pointers(value: &mut Value, set: &IndexMap<String, Value>) -> Result<(), ModifierError> {
+    for (pointer, new) in set.iter() {
+        let invalid = |reason: String| ModifierError::validation(format!("cannot set JSON Pointer \"{}\": {}", pointer, reason));
+
+        let tokens = pointer_tokens(pointer)?;
+        let (last, parents) = match tokens.split_last() {
+            Some(split) => split,
+            None => {
+                *value = new.clone();
+                continue;
+            },
+        };
+
+        let mut target = &mut *value;
+
+        for token in parents.iter() {
+            target = match target {
+                Value::Object(map) => map.entry(token.clone()).or_insert_with(|| Value::Object(Map::new())),
+                Value::Array(items) => {
+                    let len = items.len();
+
+                    token.parse::<usize>().ok()
+                        .and_then(|i| items.get_mut(i))
+                        .ok_or_else(|| invalid(format!("\"{}\" is not an index of an array of {} item(s)", token, len)))?
+                },
+                _ => return Err(invalid(format!("\"{}\" is not in an object or array", token))),
+            };
+        }
+
+        match target {
+            Value::Object(map) => {
+                map.insert(last.clone(), new.clone());
+            },
+            Value::Array(items) => match last.as_str() {
+                "-" => items.push(new.clone()),
+                index => match index.parse::<usize>() {
+                    Ok(i) if i < items.len() => items[i] = new.clone(),
+                    Ok(i) if i == items.len() => items.push(new.clone()),
+                    _ => return Err(invalid(format!("\"{}\" is not an index of an array of {} item(s)", index, items.len()))),
+                },
+            },
+            _ => return Err(invalid(format!("\"{}\" is not in an object or array", last))),
+        }
+    }
+
+    Ok(())
+}
+
+/// The first pointer of `set` whose value in `value` did not survive parsing `value` into `c`, e.g.
+/// an option under `config` that [Config] does not model, and which would thus silently not be set.
+fn unmodelled_pointer<'a>(set: &'a IndexMap<String, Value>, value: &Value, c: &ComposerJson) -> Result<Option<&'a String>, ModifierError> {
+    let kept = serde_json::to_value(c)?;
+
+    Ok(set.keys().find(|pointer| {
+        let pointer = pointer.strip_suffix("/-").unwrap_or(pointer);
+
+        value.pointer(pointer) != kept.pointer(pointer)
+    }))
+}
+
+/// A single directive of a modify-composer.json file and the number of entries it affected,
+/// as listed by `--print-applied`, e.g. `remove symfony/* -> 3 removed`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct AppliedDirective {
+    /// The directive and its arguments, e.g. `remove symfony/*` or `add psr/log ^3.0`.
+    pub directive: String,
+
+    /// Number of affected entries, zero if the directive had no effect.
+    pub count: usize,
+
+    /// What happened to the affected entries, e.g. `removed`.
+    pub outcome: &'static str,
+}
+
+impl AppliedDirective {
+    fn new(directive: String, count: usize, outcome: &'static str) -> AppliedDirective {
+        AppliedDirective { directive, count, outcome }
+    }
+}
+
+impl fmt::Display for AppliedDirective {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} -> {} {}", self.directive, self.count, self.outcome)
+    }
+}
+
+/// What the directives of a modify-composer.json file did, as recorded by the phases of [apply_recording].
+#[derive(Debug, Default)]
+pub(crate) struct Applied {
+    /// Whether to record [Applied::directives], which are only listed by `--print-applied`.
+    pub record_directives: bool,
+
+    /// Each directive and the number of entries it affected, in the order they were applied.
+    pub directives: Vec<AppliedDirective>,
+
+    /// The number of packages each pattern of `remove.require` and `remove.require-dev` matched,
+    /// summed over both sections, in the order given.
+    pub removal_hits: IndexMap<PackagePattern, usize>,
+}
+
+impl Applied {
+    /// An [Applied] recording [Applied::directives] only if `record_directives` is set.
+    pub(crate) fn new(record_directives: bool) -> Applied {
+        Applied { record_directives, ..Default::default() }
+    }
+
+    /// Records that `directive` affected `count` entries, only building it if directives are recorded.
+    fn push(&mut self, directive: impl FnOnce() -> String, count: usize, outcome: &'static str) {
+        if self.record_directives {
+            self.directives.push(AppliedDirective::new(directive(), count, outcome));
+        }
+    }
+
+    /// Records the number of packages each pattern of `require` removed, see [remove_matching].
+    fn removed(&mut self, directive: &str, require: &Require, hits: &[usize]) {
+        for (pattern, count) in require.keys().zip(hits) {
+            *self.removal_hits.entry(pattern.clone()).or_insert(0) += count;
+            self.push(|| format!("{} {}", directive, pattern), *count, "removed");
+        }
+    }
+
+    /// The `remove` patterns that matched no package, in the order given. A pattern given for both
+    /// sections, like those of `--drop`, is only unmatched if it matches a package in neither.
+    pub(crate) fn unmatched_removal_patterns(&self) -> Vec<&PackagePattern> {
+        self.removal_hits.iter().filter(|(_, hits)| **hits == 0).map(|(pattern, _)| pattern).collect()
+    }
+
+    /// Records the number of packages each pattern of `require` set the constraint of, see [modify_matching].
+    fn modified(&mut self, directive: &str, require: &Require, hits: &[usize]) {
+        for ((pattern, constraint), count) in require.iter().zip(hits) {
+            self.push(|| format!("{} {} {}", directive, pattern, constraint), *count, "modified");
+        }
+    }
+}
+
+/// Removes the field at the JSON Pointer `pointer` from `value`, returning whether it existed.
+/// A pointer to a missing field is only warned about, so removing is idempotent.
+pub(crate) fn unset_pointer(value: &mut Value, pointer: &str) -> Result<bool, ModifierError> {
+    let tokens = pointer_tokens(pointer)?;
+    let last = tokens.last().ok_or_else(|| ModifierError::validation("cannot unset the empty JSON Pointer, which refers to the whole document"))?;
+    let parent = &pointer[..pointer.rfind('/').unwrap_or(0)];
+
+    let removed = match value.pointer_mut(parent) {
+        Some(Value::Object(map)) if map.contains_key(last) => {
+            map.retain(|key, _| key != last);
+            true
         },
-        None => Ok(c)
+        Some(Value::Array(items)) => match last.parse::<usize>() {
+            Ok(i) if i < items.len() => {
+                items.remove(i);
+                true
+            },
+            _ => false,
+        },
+        _ => false,
+    };
+
+    if !removed {
+        eprintln!("warning: not unsetting JSON Pointer \"{}\", it does not exist", pointer);
     }
+
+    Ok(removed)
 }
 
-fn remove_require(c: ComposerJson, require: &Require, dry_run: &bool) -> Result<ComposerJson, io::Error> {
-    for (k, v) in require.iter() {
-        println!("[R] (k, v) = (\"{}\", \"{}\")", k, v);
+/// The unescaped reference tokens of the JSON Pointer `pointer`, e.g. `["require", "symfony/yaml"]`
+/// for `/require/symfony~1yaml`.
+fn pointer_tokens(pointer: &str) -> Result<Vec<String>, ModifierError> {
+    match pointer.strip_prefix('/') {
+        Some(rest) => Ok(rest.split('/').map(|t| t.replace("~1", "/").replace("~0", "~")).collect()),
+        None if pointer.is_empty() => Ok(Vec::new()),
+        None => Err(ModifierError::validation(format!("invalid JSON Pointer \"{}\": it must be empty or start with \"/\"", pointer))),
     }
+}
 
-    for (k, v) in c.package_links.require.iter() {
-        println!("[O] (k, v) = (\"{}\", \"{}\")", k, v);
+/// Applies the `require` and `require-dev` directives of `m` directly to a parsed composer.json `value`,
+/// in the same order as [apply]. Unlike [apply], unknown keys and the order of all other keys are
+/// left exactly as they are. Added packages are validated and resolved against existing ones
+/// according to [ModifyOptions::validate_names] and [ModifyOptions::on_conflict], like [apply] does.
+///
+/// Directives for anything other than package links, like `remove.scripts`, are not applied.
+pub(crate) fn apply_from_value(value: &mut Value, m: &ModifyComposerJson, options: &ModifyOptions, applied: &mut Applied) -> Result<(), ModifierError> {
+    let root = value.as_object_mut().ok_or_else(|| ModifierError::validation("composer.json must contain a JSON object"))?;
+
+    for directive in m.directives().iter().filter(|d| !d.ends_with(".require") && !d.ends_with(".require-dev") && !["modify.sort-packages", "unset", "set"].contains(*d)) {
+        eprintln!("warning: {} is not supported in lossless mode and was not applied", directive);
     }
 
-    Ok(c)
-}
\ No newline at end of file
+    if let Some(remove) = &m.remove {
+        for (directive, key, require) in [("remove", "require", &remove.require), ("remove require-dev", "require-dev", &remove.require_dev)] {
+            if let Some(require) = require {
+                let hits = match links_mut(root, key)? {
+                    Some(links) => remove_matching_links(links, require),
+                    None => vec![0; require.len()],
+                };
+                applied.removed(directive, require, &hits);
+            }
+        }
+    }
+
+    if let Some(modify) = &m.modify {
+        if let (Some(require), Some(links)) = (&modify.require, links_mut(root, "require")?) {
+            modify_matching_links(links, require);
+        }
+
+        if let (Some(require_dev), Some(links)) = (&modify.require_dev, links_mut(root, "require-dev")?) {
+            modify_matching_links(links, require_dev);
+        }
+    }
+
+    if let Some(AddConfig { require: Some(require), .. }) = &m.add {
+        root.entry("require").or_insert_with(|| Value::Object(Map::new()));
+
+        if let Some(links) = links_mut(root, "require")? {
+            for (name, constraint) in require.iter() {
+                let (name, constraint) = new_require_entry(name, constraint, &options.validate_names)
+                    .map_err(|e| ModifierError::validation(e.to_string()))?;
+
+                if resolve_conflict("require", &name, links.get(&name).and_then(Value::as_str), &constraint, options.on_conflict)? {
+                    links.insert(name, Value::String(constraint));
+                }
+            }
+        }
+    }
+
+    if let (Some(ReplaceConfig { require: Some(require) }), Some(links)) = (&m.replace, links_mut(root, "require")?) {
+        for (name, existing) in links.iter_mut() {
+            if let Some(constraint) = require.get(name) {
+                *existing = Value::String(constraint.clone());
+            }
+        }
+    }
+
+    if let Some(ModifyConfig { sort_packages: Some(true), .. }) = &m.modify {
+        for key in ["require", "require-dev"] {
+            if let Some(links) = links_mut(root, key)? {
+                let mut entries: Vec<(String, Value)> = std::mem::take(links).into_iter().collect();
+                entries.sort_by(|(a, _), (b, _)| compare_package_names(a, b));
+                links.extend(entries);
+            }
+        }
+    }
+
+    for pointer in m.unset.iter().flatten() {
+        unset_pointer(value, pointer)?;
+    }
+
+    if let Some(set) = &m.set {
+        set_pointers(value, set)?;
+    }
+
+    Ok(())
+}
+
+/// The package links under `key` in a composer.json object, if there are any.
+fn links_mut<'a>(root: &'a mut Map<String, Value>, key: &str) -> Result<Option<&'a mut Map<String, Value>>, ModifierError> {
+    match root.get_mut(key) {
+        Some(Value::Object(links)) => Ok(Some(links)),
+        Some(_) => Err(ModifierError::validation(format!("\"{}\" must be a JSON object", key))),
+        None => Ok(None),
+    }
+}
+
+/// Like [remove_matching], but for the package links of a parsed composer.json.
+fn remove_matching_links(links: &mut Map<String, Value>, require: &Require) -> Vec<usize> {
+    let hits = require.keys()
+        .map(|pattern| links.keys().filter(|name| pattern.matches(name)).count())
+        .collect();
+
+    links.retain(|name, _| !require.keys().any(|pattern| pattern.matches(name)));
+
+    hits
+}
+
+fn modify_matching_links(links: &mut Map<String, Value>, require: &Require) {
+    for (pattern, constraint) in require.iter() {
+        for (_, existing) in links.iter_mut().filter(|(name, _)| pattern.matches(name)) {
+            *existing = Value::String(constraint.clone());
+        }
+    }
+}
+
+/// Outcome of modifying a single composer.json file in a batch run.
+#[derive(Debug)]
+pub(crate) struct BatchResult {
+    pub file: String,
+    pub result: Result<(), ModifierError>,
+}
+
+/// Applies the same modifications to each of `files`, collecting the outcome for each file.
+///
+/// Failing files do not stop the run, unless `fail_fast` is set, in which case no files are
+/// processed after the first failure.
+pub(crate) fn modify_all(files: &[String], modify_file_name: Option<&str>, options: &ModifyOptions, fail_fast: &bool) -> Vec<BatchResult> {
+    let mut results = Vec::new();
+
+    for file in files.iter() {
+        let result = handle_modify(file, modify_file_name, options).map(|_| ());
+        let failed = result.is_err();
+
+        results.push(BatchResult { file: file.clone(), result });
+
+        if failed && *fail_fast {
+            break;
+        }
+    }
+
+    results
+}
+
+/// Runs [modify_all] and prints a summary, failing if any of the files failed. The files in
+/// `unparsed` already failed to parse, and count as failed without being modified; with
+/// `fail_fast`, none of `files` are modified if there are any.
+pub(crate) fn handle_modify_all(files: &[String], unparsed: Vec<BatchResult>, modify_file_name: Option<&str>, options: &ModifyOptions, fail_fast: &bool) -> Result<(), ModifierError> {
+    let total = files.len() + unparsed.len();
+    let mut results = unparsed;
+
+    if results.is_empty() || !*fail_fast {
+        results.extend(modify_all(files, modify_file_name, options, fail_fast));
+    }
+
+    let failed: Vec<&BatchResult> = results.iter().filter(|r| r.result.is_err()).collect();
+
+    info!("\n{} of {} file(s) modified, {} failed", results.len() - failed.len(), total, failed.len());
+
+    for r in failed.iter() {
+        if let Err(e) = &r.result {
+            eprintln!("{}: {}", r.file, e);
+        }
+    }
+
+    if failed.is_empty() {
+        return Ok(());
+    }
+
+    Err(ModifierError::validation(format!("{} of {} file(s) failed", failed.len(), total)))
+}
+
+/// A rewrite of URLs, as given to `--replace-url`, e.g. to point repositories at an internal mirror.
+#[derive(Debug, Clone)]
+pub(crate) struct UrlReplacement {
+    from: Regex,
+    to: String,
+}
+
+impl UrlReplacement {
+    /// Parses `<from>=<to>`, where `from` is a plain substring, or a regular expression when
+    /// prefixed with `regex:`, in which case `to` may refer to its capture groups, e.g. `$1`.
+    pub(crate) fn parse(s: &str) -> Result<UrlReplacement, ModifierError> {
+        let invalid = |reason: String| ModifierError::validation(format!("invalid --replace-url value \"{}\": {}", s, reason));
+
+        let (from, to) = match s.split_once('=') {
+            Some((from, to)) if !from.is_empty() => (from, to),
+            _ => return Err(invalid("expected <from>=<to>".to_string())),
+        };
+
+        let from = match from.strip_prefix("regex:") {
+            Some(pattern) => Regex::new(pattern).map_err(|e| ModifierError::PatternCompile { pattern: pattern.to_string(), source: e })?,
+            None => Regex::new(&regex::escape(from)).map_err(|e| ModifierError::PatternCompile { pattern: from.to_string(), source: e })?,
+        };
+
+        let to = match from.captures_len() {
+            1 => to.replace('$', "$$"),
+            _ => to.to_string(),
+        };
+
+        Ok(UrlReplacement { from, to })
+    }
+
+    /// The rewritten `url`, or `None` if this replacement does not apply to it.
+    fn apply(&self, url: &str) -> Option<String> {
+        match self.from.is_match(url) {
+            true => Some(self.from.replace_all(url, self.to.as_str()).into_owned()),
+            false => None,
+        }
+    }
+}
+
+/// Applies `replacements` to the URLs of `repositories`, and of `homepage` and `support.source`
+/// if `metadata` is set, reporting each rewritten URL.
+fn replace_urls(mut c: ComposerJson, replacements: &[UrlReplacement], metadata: &bool) -> ComposerJson {
+    if replacements.is_empty() {
+        return c;
+    }
+
+    let rewrite = |path: String, url: &mut String| {
+        for replacement in replacements.iter() {
+            if let Some(rewritten) = replacement.apply(url) {
+                info!("rewrote {}: {} -> {}", path, url, rewritten);
+                *url = rewritten;
+            }
+        }
+    };
+
+    for (i, repository) in c.repositories.iter_mut().flatten().enumerate() {
+        rewrite(format!("repositories[{}].url", i), &mut repository.url);
+    }
+
+    if *metadata {
+        if let Some(homepage) = c.homepage.as_mut() {
+            rewrite("homepage".to_string(), homepage);
+        }
+
+        if let Some(source) = c.support.as_mut().and_then(|s| s.source.as_mut()) {
+            rewrite("support.source".to_string(), source);
+        }
+    }
+
+    c
+}
+
+/// Parses a package list, as given to `--from-package-list`: one `name constraint` pair per line,
+/// separated by whitespace. Blank lines and lines starting with `#` are skipped.
+fn parse_package_list(contents: &str) -> Result<Vec<(String, String)>, ModifierError> {
+    let mut packages = Vec::new();
+
+    for (i, line) in contents.lines().enumerate() {
+        let line = line.trim();
+
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        match line.split_once(char::is_whitespace) {
+            Some((name, constraint)) if !constraint.trim().is_empty() => {
+                packages.push((name.to_string(), constraint.trim().to_string()));
+            },
+            _ => return Err(
+                ModifierError::validation(
+                    format!("invalid package list entry on line {}: \"{}\", expected <package> <constraint>", i + 1, line),
+                )
+            ),
+        }
+    }
+
+    Ok(packages)
+}
+
+/// Adds `package=constraint` pairs, as given to `--ensure`, to the `add.require` directive of `m`.
+fn with_ensured(mut m: ModifyComposerJson, ensure: &[String]) -> Result<ModifyComposerJson, ModifierError> {
+    if ensure.is_empty() {
+        return Ok(m);
+    }
+
+    let require = m.add.get_or_insert_with(AddConfig::default)
+        .require.get_or_insert_with(IndexMap::new);
+
+    for entry in ensure.iter() {
+        match entry.split_once('=') {
+            Some((name, constraint)) if !name.trim().is_empty() && !constraint.trim().is_empty() => {
+                require.insert(name.trim().to_string(), constraint.trim().to_string());
+            },
+            _ => return Err(
+                ModifierError::validation(
+                    format!("invalid --ensure value \"{}\", expected <package>=<constraint>", entry),
+                )
+            ),
+        }
+    }
+
+    Ok(m)
+}
+
+/// Adds package patterns, as given to `--drop`, to the `remove.require` and `remove.require-dev`
+/// directives of `m`.
+fn with_dropped(mut m: ModifyComposerJson, drop: &[String]) -> Result<ModifyComposerJson, ModifierError> {
+    if drop.is_empty() {
+        return Ok(m);
+    }
+
+    let remove = m.remove.get_or_insert_with(RemoveConfig::default);
+
+    for pattern in drop.iter() {
+        let pattern = PackagePattern::new(pattern)
+            .map_err(|e| ModifierError::PatternCompile { pattern: pattern.clone(), source: e })?;
+
+        remove.require.get_or_insert_with(IndexMap::new).insert(pattern.clone(), "*".to_string());
+        remove.require_dev.get_or_insert_with(IndexMap::new).insert(pattern, "*".to_string());
+    }
+
+    Ok(m)
+}
+
+/// The name `value` is given by on the command line, e.g. `psr0-to-psr4`.
+fn value_name(value: impl ValueEnum) -> String {
+    value.to_possible_value().map(|v| v.get_name().to_string()).unwrap_or_default()
+}
+
+/// Leaves an audit breadcrumb in `extra._modifier`, recording when the file was modified
+/// and which of the directives in `applied` took effect, e.g. `remove symfony/*`.
+fn annotate(mut c: ComposerJson, applied: &Applied, timestamp: &str) -> ComposerJson {
+    let directives: Vec<&str> = applied.directives.iter()
+        .filter(|a| a.count > 0)
+        .map(|a| a.directive.as_str())
+        .collect();
+    let annotation = json!({
+        "timestamp": timestamp,
+        "directives": directives,
+    });
+
+    match c.extra.get_or_insert_with(|| Value::Object(Map::new())) {
+        Value::Object(extra) => {
+            extra.insert(ANNOTATION_KEY.into(), annotation);
+        },
+        _ => eprintln!("warning: not annotating modifications, \"extra\" is not an object"),
+    }
+
+    c
+}
+
+fn remove(c: ComposerJson, m: &ModifyComposerJson, dry_run: &bool, applied: &mut Applied) -> Result<ComposerJson, ModifierError> {
+    return match &m.remove {
+        Some(remove) => {
+            let c = match &remove.require {
+                Some(require) => remove_require(c, &require, dry_run, applied)?,
+                None => c
+            };
+
+            let c = match &remove.require_dev {
+                Some(require_dev) => remove_require_dev(c, &require_dev, dry_run, applied)?,
+                None => c
+            };
+
+            let c = match &remove.repositories {
+                Some(urls) => remove_repositories(c, urls, applied)?,
+                None => c
+            };
+
+            return match &remove.scripts {
+                Some(names) => remove_scripts(c, names, applied),
+                None => Ok(c)
+            }
+        },
+        None => Ok(c)
+    }
+}
+
+/// Compiles the URL patterns of `remove.repositories`, see [RemoveConfig::repositories].
+fn repository_patterns(urls: &[String]) -> Result<Vec<PackagePattern>, ModifierError> {
+    urls.iter()
+        .map(|url| PackagePattern::new(url).map_err(|e| ModifierError::PatternCompile { pattern: url.clone(), source: e }))
+        .collect()
+}
+
+/// Whether `pattern` matches the URL of `repository`, or `packagist.org` if it is the entry disabling Packagist.
+fn repository_matches(pattern: &PackagePattern, repository: &Repository) -> bool {
+    match repository.disables_packagist() {
+        true => pattern.matches("packagist.org"),
+        false => pattern.matches(&repository.url),
+    }
+}
+
+/// Removes the repositories matching any of the given `urls`, dropping `repositories` altogether if none are left.
+fn remove_repositories(mut c: ComposerJson, urls: &[String], applied: &mut Applied) -> Result<ComposerJson, ModifierError> {
+    let patterns = repository_patterns(urls)?;
+    let mut hits = vec![0; patterns.len()];
+
+    if let Some(repositories) = c.repositories.as_mut() {
+        repositories.retain(|r| match patterns.iter().position(|p| repository_matches(p, r)) {
+            Some(i) => {
+                hits[i] += 1;
+                false
+            },
+            None => true,
+        });
+
+        if repositories.is_empty() {
+            c.repositories = None;
+        }
+    }
+
+    for (pattern, count) in patterns.iter().zip(hits) {
+        applied.push(|| format!("remove repository {}", pattern), count, "removed");
+    }
+
+    Ok(c)
+}
+
+/// Removes the scripts with any of the given `names`, dropping `scripts` altogether if none are left.
+fn remove_scripts(mut c: ComposerJson, names: &[String], applied: &mut Applied) -> Result<ComposerJson, ModifierError> {
+    let mut removed = vec![false; names.len()];
+
+    if let Some(scripts) = c.scripts.take() {
+        let mut scripts = serde_json::to_value(scripts)?;
+
+        if let Some(map) = scripts.as_object_mut() {
+            removed = names.iter().map(|name| map.contains_key(name)).collect();
+            map.retain(|name, _| !names.contains(name));
+        }
+
+        c.scripts = match scripts.as_object() {
+            Some(map) if map.is_empty() => None,
+            _ => Some(serde_json::from_value(scripts)?),
+        };
+    }
+
+    for (name, removed) in names.iter().zip(removed) {
+        applied.push(|| format!("remove script {}", name), usize::from(removed), "removed");
+    }
+
+    Ok(c)
+}
+
+fn remove_require(mut c: ComposerJson, require: &Require, _dry_run: &bool, applied: &mut Applied) -> Result<ComposerJson, ModifierError> {
+    let hits = remove_matching(&mut c.package_links.require, require);
+    applied.removed("remove", require, &hits);
+
+    Ok(c)
+}
+
+/// Like [remove_require], but for `require-dev`, dropping `require-dev` altogether if no packages are left.
+fn remove_require_dev(mut c: ComposerJson, require_dev: &Require, _dry_run: &bool, applied: &mut Applied) -> Result<ComposerJson, ModifierError> {
+    let hits = match c.package_links.require_dev.as_mut() {
+        Some(packages) => {
+            let hits = remove_matching(packages, require_dev);
+
+            if packages.is_empty() {
+                c.package_links.require_dev = None;
+            }
+
+            hits
+        },
+        None => vec![0; require_dev.len()],
+    };
+    applied.removed("remove require-dev", require_dev, &hits);
+
+    Ok(c)
+}
+
+/// Removes all packages matching any of the patterns in `require`, returning the number of packages
+/// each pattern matched, in the order of `require`. A package matching several patterns counts for each.
+fn remove_matching(packages: &mut IndexMap<String, VersionConstraint>, require: &Require) -> Vec<usize> {
+    let hits = require.keys()
+        .map(|pattern| packages.keys().filter(|name| pattern.matches(name)).count())
+        .collect();
+
+    packages.retain(|name, _| !require.keys().any(|pattern| pattern.matches(name)));
+
+    hits
+}
+
+fn modify(c: ComposerJson, m: &ModifyComposerJson, applied: &mut Applied) -> Result<ComposerJson, ModifierError> {
+    return match &m.modify {
+        Some(modify) => {
+            let mut c = c;
+
+            if let Some(require) = &modify.require {
+                let hits = modify_matching(&mut c.package_links.require, require);
+                applied.modified("modify", require, &hits);
+            }
+
+            if let Some(require_dev) = &modify.require_dev {
+                let hits = match c.package_links.require_dev.as_mut() {
+                    Some(packages) => modify_matching(packages, require_dev),
+                    None => vec![0; require_dev.len()],
+                };
+                applied.modified("modify require-dev", require_dev, &hits);
+            }
+
+            if let Some(config) = &modify.config {
+                modify_config(&mut c, config)?;
+            }
+
+            if let Some(stability) = modify.minimum_stability {
+                c.minimum_stability = Some(stability);
+            }
+
+            if modify.normalize_constraints == Some(true) {
+                normalize_constraints(&mut c.package_links.require);
+            }
+
+            if modify.normalize_psr_4 == Some(true) {
+                for fixed in c.normalize_psr4() {
+                    info!("normalized {}", fixed);
+                }
+            }
+
+            Ok(c)
+        },
+        None => Ok(c)
+    }
+}
+
+/// Rewrites each constraint of `packages` to its equivalent caret constraint, see [to_caret],
+/// noting those that are left unchanged because they have none.
+fn normalize_constraints(packages: &mut IndexMap<String, VersionConstraint>) {
+    for (name, constraint) in packages.iter_mut() {
+        match to_caret(constraint) {
+            Some(caret) => *constraint = caret,
+            None if !constraint.trim_start().starts_with('^') => {
+                debug!("left constraint \"{}\" of {} unchanged, it has no equivalent caret constraint", constraint, name);
+            },
+            None => {},
+        }
+    }
+}
+
+/// Sets the options of `modify.config` in `config`, creating it if absent.
+///
+/// Values are given as strings and coerced to the type of the option, e.g. `"600"` for `process-timeout`.
+/// Only `process-timeout`, `vendor-dir`, `bin-dir` and `sort-packages` are supported.
+fn modify_config(c: &mut ComposerJson, options: &IndexMap<String, String>) -> Result<(), ModifierError> {
+    let mut config = match &c.config {
+        Some(config) => serde_json::to_value(config)?,
+        None => json!({}),
+    };
+
+    for (key, value) in options.iter() {
+        let invalid = |expected: &str| ModifierError::validation(
+            format!("invalid modify.config value for \"{}\": expected {}, got \"{}\"", key, expected, value),
+        );
+
+        config[key.as_str()] = match key.as_str() {
+            "process-timeout" => Value::from(value.parse::<u32>().map_err(|_| invalid("a number of seconds"))?),
+            "vendor-dir" | "bin-dir" => Value::String(value.clone()),
+            "sort-packages" => Value::Bool(value.parse::<bool>().map_err(|_| invalid("true or false"))?),
+            _ => return Err(ModifierError::validation(format!("modify.config option \"{}\" is not supported", key))),
+        };
+    }
+
+    c.config = Some(serde_json::from_value(config)?);
+
+    Ok(())
+}
+
+/// Merges the `config` section of `m` into `config`, creating it if absent, see [ModifierConfig](crate::modify_composer_json::ModifierConfig).
+fn merge_config(mut c: ComposerJson, m: &ModifyComposerJson, applied: &mut Applied) -> ComposerJson {
+    let spec = match &m.config {
+        Some(spec) => spec,
+        None => return c,
+    };
+
+    let config = c.config.get_or_insert_with(Config::default);
+
+    if let Some(platform) = &spec.platform {
+        config.platform.get_or_insert_with(IndexMap::new).extend(platform.clone());
+
+        for (name, constraint) in platform.iter() {
+            applied.push(|| format!("config platform {} {}", name, constraint), 1, "set");
+        }
+    }
+
+    if let Some(allow_plugins) = &spec.allow_plugins {
+        config.allow_plugins = Some(merge_allow_plugins(config.allow_plugins.take(), allow_plugins));
+        applied.push(|| "config allow-plugins".to_string(), 1, "merged");
+    }
+
+    c
+}
+
+/// Merges `spec` into the `existing` allowed plugins: maps are unioned with the values of `spec`
+/// winning, while anything else is replaced by `spec`.
+fn merge_allow_plugins(existing: Option<AllowPlugins>, spec: &AllowPlugins) -> AllowPlugins {
+    match (existing, spec) {
+        (Some(AllowPlugins::Map(mut plugins)), AllowPlugins::Map(spec)) => {
+            plugins.extend(spec.iter().map(|(name, allowed)| (name.clone(), *allowed)));
+
+            AllowPlugins::Map(plugins)
+        },
+        (_, spec) => spec.clone(),
+    }
+}
+
+/// Sorts `require` and `require-dev` if `modify.sort-packages` is set. Runs after the other phases of
+/// [apply], and again after [transform] adds the packages of `--from-package-list`, so added and
+/// replaced packages end up in place.
+fn sort(c: ComposerJson, m: &ModifyComposerJson, applied: &mut Applied) -> ComposerJson {
+    let mut c = c;
+
+    if let Some(ModifyConfig { sort_packages: Some(true), .. }) = &m.modify {
+        let unsorted = std::iter::once(&c.package_links.require)
+            .chain(c.package_links.require_dev.as_ref())
+            .filter(|packages| packages.keys().zip(packages.keys().skip(1)).any(|(a, b)| compare_package_names(a, b) == Ordering::Greater))
+            .count();
+
+        c.sort_packages();
+        applied.push(|| "modify sort-packages".to_string(), unsorted, "sorted");
+    }
+
+    c
+}
+
+/// Sets the constraint of all packages matching a pattern in `require` to the constraint given for it,
+/// returning the number of packages each pattern matched, in the order of `require`.
+fn modify_matching(packages: &mut IndexMap<String, VersionConstraint>, require: &Require) -> Vec<usize> {
+    require.iter()
+        .map(|(pattern, constraint)| {
+            let mut modified = 0;
+
+            for (_, existing) in packages.iter_mut().filter(|(name, _)| pattern.matches(name)) {
+                *existing = constraint.clone();
+                modified += 1;
+            }
+
+            modified
+        })
+        .collect()
+}
+
+fn add(c: ComposerJson, m: &ModifyComposerJson, options: &ModifyOptions, applied: &mut Applied) -> Result<ComposerJson, ModifierError> {
+    match &m.add {
+        Some(add) => {
+            let c = match &add.require {
+                Some(require) => add_require(c, require, &options.validate_names, options.on_conflict, applied)?,
+                None => c
+            };
+
+            match &add.repositories {
+                Some(repositories) => Ok(add_repositories(c, repositories)),
+                None => Ok(c)
+            }
+        },
+        None => Ok(c)
+    }
+}
+
+/// Appends the `repositories` whose `url` is not already in `repositories`, initializing it if needed.
+fn add_repositories(mut c: ComposerJson, repositories: &[Repository]) -> ComposerJson {
+    let existing = c.repositories.get_or_insert_with(Vec::new);
+
+    for repository in repositories.iter() {
+        // Package repositories and the packagist.org entry have no URL to tell them apart by.
+        let (present, label) = match repository.url.as_str() {
+            "" => (existing.contains(repository), serde_json::to_string(repository).unwrap_or_default()),
+            url => (existing.iter().any(|r| r.url == url), url.to_string()),
+        };
+
+        match present {
+            true => info!("repository {} is already present", label),
+            false => {
+                info!("added repository {}", label);
+                existing.push(repository.clone());
+            },
+        }
+    }
+
+    c
+}
+
+fn replace(c: ComposerJson, m: &ModifyComposerJson, applied: &mut Applied) -> ComposerJson {
+    match &m.replace {
+        Some(replace) => {
+            match &replace.require {
+                Some(require) => replace_require(c, require, applied),
+                None => c
+            }
+        },
+        None => c
+    }
+}
+
+/// Replaces the constraints of the packages in `require` that are already required,
+/// skipping (with a warning) those that are not.
+fn replace_require(mut c: ComposerJson, require: &IndexMap<String, VersionConstraint>, applied: &mut Applied) -> ComposerJson {
+    for (name, constraint) in require.iter() {
+        let count = match c.package_links.require.get_mut(name) {
+            Some(existing) => {
+                *existing = constraint.clone();
+                1
+            },
+            None => {
+                eprintln!("warning: not replacing {}, it is not required", name);
+                0
+            },
+        };
+        applied.push(|| format!("replace {} {}", name, constraint), count, "replaced");
+    }
+
+    c
+}
+
+fn add_require(mut c: ComposerJson, require: &IndexMap<String, VersionConstraint>, validate_names: &bool, on_conflict: OnConflict, applied: &mut Applied) -> Result<ComposerJson, ModifierError> {
+    for (given_name, given_constraint) in require.iter() {
+        let (name, constraint) = new_require_entry(given_name, given_constraint, validate_names)
+            .map_err(|e| ModifierError::validation(e.to_string()))?;
+        let before = c.package_links.require.get(&name).cloned();
+
+        add_package(&mut c, &name, &constraint, false, on_conflict)?;
+
+        let (count, outcome) = match (before, c.package_links.require.get(&name)) {
+            (None, Some(_)) => (1, "added"),
+            (Some(old), Some(new)) if &old != new => (1, "replaced"),
+            _ => (0, "added"),
+        };
+        applied.push(|| format!("add {} {}", given_name, given_constraint), count, outcome);
+    }
+
+    Ok(c)
+}
+
+/// Requires `name` with `constraint` in `require`, or `require-dev` if `dev` is set, resolving
+/// an existing requirement with another constraint according to `on_conflict`.
+fn add_package(c: &mut ComposerJson, name: &str, constraint: &str, dev: bool, on_conflict: OnConflict) -> Result<(), ModifierError> {
+    let section = if dev { "require-dev" } else { "require" };
+    let existing = match dev {
+        true => c.package_links.require_dev.as_ref().and_then(|packages| packages.get(name)),
+        false => c.package_links.require.get(name),
+    }.cloned();
+
+    if resolve_conflict(section, name, existing.as_deref(), constraint, on_conflict)? {
+        if let (EnsureResult::Updated, Some(old)) = (c.ensure_require(name, constraint, dev), existing) {
+            info!("replaced existing {} entry {} {} with {}", section, name, old, constraint);
+        }
+    }
+
+    Ok(())
+}
+
+/// Whether to require `name` with `constraint` in `section`, where it is required with `existing`,
+/// according to `on_conflict`, failing for [OnConflict::Error] if the constraints differ.
+fn resolve_conflict(section: &str, name: &str, existing: Option<&str>, constraint: &str, on_conflict: OnConflict) -> Result<bool, ModifierError> {
+    match (existing, on_conflict) {
+        (Some(old), OnConflict::Skip) if old != constraint => {
+            info!("kept existing {} entry {} {}, not adding {}", section, name, old, constraint);
+
+            Ok(false)
+        },
+        (Some(old), OnConflict::Error) if old != constraint => Err(
+            ModifierError::validation(
+                format!("{} is already required in {} with {}, not adding {}", name, section, old, constraint),
+            )
+        ),
+        _ => Ok(true),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use indexmap::IndexMap;
+    use serde_json::{json, Value};
+
+    use crate::composer_json::{AllowPlugins, ComposerJson, Config};
+    use crate::error::ModifierError;
+    use crate::modify::{add, add_require, annotate, apply, apply_from_value, apply_recording, handle_modify, Applied, merge_config, modify_all, parse_package_list, remove, remove_require, remove_require_dev, replace_require, replace_urls, set_pointers, unset_pointer, with_dropped, with_ensured, Conversion, Indent, ModifyOptions, OnConflict, OutputFormat, UrlReplacement};
+    use crate::patch::PatchOperation;
+    use crate::modify_composer_json::{ModifierConfig, ModifyComposerJson, ModifyComposerJsonBuilder, PackagePattern, Require};
+    use crate::profile::Profile;
+    use crate::temp_dir::TempDir;
+
+    fn composer_json(json: &str) -> ComposerJson {
+        serde_json::from_str(json).unwrap()
+    }
+
+    fn require(entries: &[(&str, &str)]) -> IndexMap<String, String> {
+        entries.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+    }
+
+    #[test]
+    fn add_require_rejects_invalid_package_name() {
+        let c = composer_json(r#"{ "name": "foo/bar", "require": {} }"#);
+        let error = add_require(c, &require(&[("Foo Bar/baz", "^1.0")]), &false, OnConflict::Overwrite, &mut Applied::default()).unwrap_err();
+
+        assert_eq!(error.to_string(), "require: \"Foo Bar/baz\" is not a valid package name");
+    }
+
+    #[test]
+    fn add_require_accepts_valid_package_name() {
+        let c = composer_json(r#"{ "name": "foo/bar", "require": {} }"#);
+        let c = add_require(c, &require(&[("monolog/monolog", "^3.0")]), &true, OnConflict::Overwrite, &mut Applied::default()).unwrap();
+
+        assert_eq!(c.package_links.require.get("monolog/monolog").map(String::as_str), Some("^3.0"));
+    }
+
+    #[test]
+    fn add_require_adds_new_package() {
+        let c = composer_json(r#"{ "name": "foo/bar", "require": { "php": ">=8.1" } }"#);
+        let c = add_require(c, &require(&[("psr/log", "^3.0")]), &false, OnConflict::Overwrite, &mut Applied::default()).unwrap();
+
+        assert_eq!(c.package_links.require, require(&[("php", ">=8.1"), ("psr/log", "^3.0")]));
+    }
+
+    #[test]
+    fn modify_keeps_order_of_untouched_and_added_packages() {
+        let c = composer_json(r#"{ "name": "foo/bar", "require": { "symfony/yaml": "^6.0", "php": ">=8.1", "psr/log": "^1.0", "monolog/monolog": "^3.0" } }"#);
+        let m: ModifyComposerJson = serde_json::from_str(r#"{
+            "remove": { "require": { "php": "*" } },
+            "add": { "require": { "psr/log": "^3.0", "symfony/console": "^6.3", "doctrine/dbal": "^3.0" } }
+        }"#).unwrap();
+
+        let c = apply(c, &m, &ModifyOptions::default(), &mut Profile::default()).unwrap();
+        let names: Vec<&String> = c.package_links.require.keys().collect();
+
+        assert_eq!(names, ["symfony/yaml", "psr/log", "monolog/monolog", "symfony/console", "doctrine/dbal"]);
+    }
+
+    #[test]
+    fn modify_sort_packages_sorts_platform_packages_first() {
+        let c = composer_json(r#"{
+            "name": "foo/bar",
+            "require": { "symfony/yaml": "^6.0", "ext-json": "*", "monolog/monolog": "^3.0", "php": ">=8.1" },
+            "require-dev": { "phpunit/phpunit": "^10.0", "ext-xdebug": "*" }
+        }"#);
+        let m: ModifyComposerJson = serde_json::from_str(r#"{
+            "modify": { "sort-packages": true },
+            "add": { "require": { "doctrine/dbal": "^3.0", "ext-ctype": "*" } }
+        }"#).unwrap();
+
+        let c = apply(c, &m, &ModifyOptions::default(), &mut Profile::default()).unwrap();
+        let json = serde_json::to_string(&c).unwrap();
+
+        assert_eq!(c.package_links.require.keys().collect::<Vec<_>>(), ["php", "ext-ctype", "ext-json", "doctrine/dbal", "monolog/monolog", "symfony/yaml"]);
+        assert_eq!(c.package_links.require_dev.unwrap().keys().collect::<Vec<_>>(), ["ext-xdebug", "phpunit/phpunit"]);
+        assert!(json.contains(r#""require":{"php":">=8.1","ext-ctype":"*","ext-json":"*","doctrine/dbal":"^3.0""#));
+    }
+
+    #[test]
+    fn modify_without_sort_packages_keeps_order() {
+        let c = composer_json(r#"{ "name": "foo/bar", "require": { "symfony/yaml": "^6.0", "php": ">=8.1" } }"#);
+        let m: ModifyComposerJson = serde_json::from_str(r#"{ "modify": { "sort-packages": false } }"#).unwrap();
+
+        let c = apply(c, &m, &ModifyOptions::default(), &mut Profile::default()).unwrap();
+
+        assert_eq!(c.package_links.require.keys().collect::<Vec<_>>(), ["symfony/yaml", "php"]);
+    }
+
+    #[test]
+    fn modify_config_sets_options_with_their_types() {
+        let c = composer_json(r#"{ "name": "foo/bar", "require": {}, "config": { "vendor-dir": "vendor", "optimize-autoloader": true } }"#);
+        let m: ModifyComposerJson = serde_json::from_str(r#"{
+            "modify": { "config": { "process-timeout": "600", "vendor-dir": "lib/vendor", "bin-dir": "bin", "sort-packages": "true" } }
+        }"#).unwrap();
+
+        let c = apply(c, &m, &ModifyOptions::default(), &mut Profile::default()).unwrap();
+
+        assert_eq!(serde_json::to_value(&c).unwrap()["config"], json!({
+            "process-timeout": 600,
+            "vendor-dir": "lib/vendor",
+            "bin-dir": "bin",
+            "optimize-autoloader": true,
+            "sort-packages": true,
+        }));
+    }
+
+    #[test]
+    fn modify_config_rejects_invalid_value() {
+        let c = composer_json(r#"{ "name": "foo/bar", "require": {} }"#);
+        let m: ModifyComposerJson = serde_json::from_str(r#"{ "modify": { "config": { "process-timeout": "five minutes" } } }"#).unwrap();
+
+        let e = apply(c, &m, &ModifyOptions::default(), &mut Profile::default()).unwrap_err();
+
+        assert!(matches!(&e, ModifierError::Validation(message) if message.contains("process-timeout")));
+    }
+
+    #[test]
+    fn unmatched_removal_patterns_of_matching_pattern() {
+        let c = composer_json(r#"{ "name": "foo/bar", "require": { "symfony/yaml": "^6.0" }, "require-dev": { "phpunit/phpunit": "^10.0" } }"#);
+        let m: ModifyComposerJson = serde_json::from_str(r#"{ "remove": { "require": { "symfony/*": "*" }, "require-dev": { "phpunit/*": "*", "symfony/*": "*" } } }"#).unwrap();
+
+        let mut applied = Applied::default();
+        apply_recording(c, &m, &ModifyOptions::default(), &mut Profile::default(), &mut applied).unwrap();
+
+        assert!(applied.unmatched_removal_patterns().is_empty());
+    }
+
+    #[test]
+    fn unmatched_removal_patterns_of_stale_pattern() {
+        let c = composer_json(r#"{ "name": "foo/bar", "require": { "symfony/yaml": "^6.0" } }"#);
+        let m: ModifyComposerJson = serde_json::from_str(r#"{ "remove": { "require": { "symfony/*": "*", "laminas/*": "*" }, "require-dev": { "phpunit/*": "*" } } }"#).unwrap();
+
+        let mut applied = Applied::default();
+        apply_recording(c, &m, &ModifyOptions::default(), &mut Profile::default(), &mut applied).unwrap();
+        let unmatched: Vec<String> = applied.unmatched_removal_patterns().iter().map(|p| p.to_string()).collect();
+
+        assert_eq!(unmatched, ["laminas/*", "phpunit/*"]);
+    }
+
+    #[test]
+    fn set_existing_scalar() {
+        let c = composer_json(r#"{ "name": "foo/bar", "require": {}, "config": { "process-timeout": 300, "vendor-dir": "vendor" } }"#);
+        let m: ModifyComposerJson = serde_json::from_str(r#"{ "set": { "/config/process-timeout": 600, "/description": "Foo bar" } }"#).unwrap();
+
+        let c = apply(c, &m, &ModifyOptions::default(), &mut Profile::default()).unwrap();
+        let value = serde_json::to_value(&c).unwrap();
+
+        assert_eq!(value["config"], json!({ "process-timeout": 600, "vendor-dir": "vendor" }));
+        assert_eq!(value["description"], "Foo bar");
+    }
+
+    #[test]
+    fn set_creates_nested_path() {
+        let c = composer_json(r#"{ "name": "foo/bar", "require": {} }"#);
+        let m = ModifyComposerJsonBuilder::default()
+            .set("/extra/x~1custom/enabled", json!(true))
+            .set("/config/platform/php", json!("8.1.2"))
+            .build()
+            .unwrap();
+
+        let c = apply(c, &m, &ModifyOptions::default(), &mut Profile::default()).unwrap();
+        let value = serde_json::to_value(&c).unwrap();
+
+        assert_eq!(value["extra"], json!({ "x/custom": { "enabled": true } }));
+        assert_eq!(value["config"]["platform"], json!({ "php": "8.1.2" }));
+    }
+
+    #[test]
+    fn set_unmodelled_config_option_fails() {
+        let c = composer_json(r#"{ "name": "foo/bar", "require": {}, "config": { "process-timeout": 300 } }"#);
+        let m = ModifyComposerJsonBuilder::default()
+            .set("/config/process-timeout", json!(600))
+            .set("/config/unknown-opt", json!(true))
+            .build()
+            .unwrap();
+        let mut applied = Applied::new(true);
+
+        let result = apply_recording(c, &m, &ModifyOptions::default(), &mut Profile::default(), &mut applied);
+
+        assert!(matches!(result, Err(ModifierError::Validation(message)) if message.contains("/config/unknown-opt")));
+        assert!(applied.directives.iter().all(|directive| !directive.directive.starts_with("set ")));
+    }
+
+    #[test]
+    fn unset_nested_key() {
+        let c = composer_json(r#"{
+            "name": "foo/bar",
+            "require": { "php": ">=8.1", "symfony/yaml": "^6.0" },
+            "scripts": { "post-install-cmd": "echo installed", "test": "phpunit" }
+        }"#);
+        let m = ModifyComposerJsonBuilder::default()
+            .unset("/scripts/post-install-cmd")
+            .unset("/require/symfony~1yaml")
+            .build()
+            .unwrap();
+
+        let c = apply(c, &m, &ModifyOptions::default(), &mut Profile::default()).unwrap();
+        let value = serde_json::to_value(&c).unwrap();
+
+        assert_eq!(value["scripts"], json!({ "test": "phpunit" }));
+        assert_eq!(c.package_links.require, require(&[("php", ">=8.1")]));
+    }
+
+    #[test]
+    fn unset_missing_pointer_is_noop() {
+        let mut value = json!({ "name": "foo/bar", "require": { "php": ">=8.1" }, "keywords": ["a"] });
+        let original = value.clone();
+
+        for pointer in ["/scripts/post-install-cmd", "/require/psr~1log", "/keywords/1", "/name/first"] {
+            assert!(!unset_pointer(&mut value, pointer).unwrap(), "{} should not exist", pointer);
+        }
+
+        assert_eq!(value, original);
+        assert!(unset_pointer(&mut value, "").is_err());
+        assert!(unset_pointer(&mut value, "name").is_err());
+        assert!(unset_pointer(&mut value, "/keywords/0").unwrap());
+        assert_eq!(value["keywords"], json!([]));
+    }
+
+    #[test]
+    fn set_pointers_into_arrays() {
+        let mut value = json!({ "keywords": ["a", "b"], "name": "foo/bar" });
+        let set: IndexMap<String, Value> = [
+            ("/keywords/0".to_string(), json!("x")),
+            ("/keywords/-".to_string(), json!("c")),
+        ].into_iter().collect();
+
+        set_pointers(&mut value, &set).unwrap();
+
+        assert_eq!(value["keywords"], json!(["x", "b", "c"]));
+        assert!(set_pointers(&mut value, &[("/keywords/9".to_string(), json!("y"))].into_iter().collect()).is_err());
+        assert!(set_pointers(&mut value, &[("/name/first".to_string(), json!("y"))].into_iter().collect()).is_err());
+        assert!(set_pointers(&mut value, &[("name".to_string(), json!("y"))].into_iter().collect()).is_err());
+    }
+
+    #[test]
+    fn modify_normalize_constraints() {
+        let c = composer_json(r#"{ "name": "foo/bar", "require": { "php": ">=8.1", "psr/log": "~1.2", "symfony/yaml": ">=5.0 <5.4", "monolog/monolog": "^3.0" } }"#);
+        let m: ModifyComposerJson = serde_json::from_str(r#"{ "modify": { "normalize-constraints": true } }"#).unwrap();
+
+        let c = apply(c, &m, &ModifyOptions::default(), &mut Profile::default()).unwrap();
+
+        assert_eq!(c.package_links.require, require(&[("php", ">=8.1"), ("psr/log", "^1.2"), ("symfony/yaml", ">=5.0 <5.4"), ("monolog/monolog", "^3.0")]));
+    }
+
+    #[test]
+    fn apply_without_files() {
+        let c = composer_json(r#"{ "name": "foo/bar", "require": { "php": ">=8.1", "psr/log": "^1.0" }, "require-dev": { "phpunit/phpunit": "^9.0" } }"#);
+        let m = ModifyComposerJsonBuilder::default()
+            .remove_require_dev("phpunit/*")
+            .add_require("monolog/monolog", "^3.0")
+            .replace_require("psr/log", "^3.0")
+            .build()
+            .unwrap();
+
+        let c = apply(c, &m, &ModifyOptions::default(), &mut Profile::default()).unwrap();
+
+        assert_eq!(c.package_links.require, require(&[("php", ">=8.1"), ("psr/log", "^3.0"), ("monolog/monolog", "^3.0")]));
+        assert_eq!(c.package_links.require_dev, None);
+    }
+
+    #[test]
+    fn apply_with_empty_spec_is_identity() {
+        let c = composer_json(r#"{ "name": "foo/bar", "require": { "php": ">=8.1" }, "minimum-stability": "dev" }"#);
+
+        let d = apply(c.clone(), &ModifyComposerJson::default(), &ModifyOptions::default(), &mut Profile::default()).unwrap();
+
+        assert_eq!(serde_json::to_value(&d).unwrap(), serde_json::to_value(&c).unwrap());
+    }
+
+    #[test]
+    fn modify_minimum_stability() {
+        for (stability, expected) in [("dev", "dev"), ("RC", "RC")] {
+            let c = composer_json(r#"{ "name": "foo/bar", "require": {}, "minimum-stability": "stable" }"#);
+            let m: ModifyComposerJson = serde_json::from_value(json!({ "modify": { "minimum-stability": stability } })).unwrap();
+
+            let c = apply(c, &m, &ModifyOptions::default(), &mut Profile::default()).unwrap();
+
+            assert_eq!(serde_json::to_value(&c).unwrap()["minimum-stability"], json!(expected));
+        }
+    }
+
+    #[test]
+    fn add_require_overwrites_existing_constraint() {
+        let c = composer_json(r#"{ "name": "foo/bar", "require": { "php": ">=8.1", "psr/log": "^1.0" } }"#);
+        let c = add_require(c, &require(&[("psr/log", "^3.0")]), &false, OnConflict::Overwrite, &mut Applied::default()).unwrap();
+
+        assert_eq!(c.package_links.require, require(&[("php", ">=8.1"), ("psr/log", "^3.0")]));
+    }
+
+    #[test]
+    fn replace_require_updates_existing_package() {
+        let c = composer_json(r#"{ "name": "foo/bar", "require": { "php": ">=8.1", "psr/log": "^1.0" } }"#);
+        let c = replace_require(c, &require(&[("psr/log", "^3.0")]), &mut Applied::default());
+
+        assert_eq!(c.package_links.require, require(&[("php", ">=8.1"), ("psr/log", "^3.0")]));
+    }
+
+    #[test]
+    fn replace_require_does_not_insert_missing_package() {
+        let c = composer_json(r#"{ "name": "foo/bar", "require": { "php": ">=8.1" } }"#);
+        let c = replace_require(c, &require(&[("psr/log", "^3.0")]), &mut Applied::default());
+
+        assert_eq!(c.package_links.require, require(&[("php", ">=8.1")]));
+    }
+
+    #[test]
+    fn annotate_records_applied_directives() {
+        let c = composer_json(r#"{
+            "name": "foo/bar",
+            "require": { "symfony/yaml": "^6.0" },
+            "extra": { "branch-alias": { "dev-main": "1.x-dev" } }
+        }"#);
+        let m: ModifyComposerJson = serde_json::from_str(r#"{
+            "remove": { "require": { "symfony/*": "*" }, "require-dev": { "laminas/*": "*" } },
+            "add": { "require": { "monolog/monolog": "^3.0" } }
+        }"#).unwrap();
+
+        let mut applied = Applied::new(true);
+        let c = apply_recording(c, &m, &ModifyOptions::default(), &mut Profile::default(), &mut applied).unwrap();
+        let extra = annotate(c, &applied, "2023-09-01T12:00:00+00:00").extra.unwrap();
+
+        assert_eq!(extra["_modifier"]["timestamp"], "2023-09-01T12:00:00+00:00");
+        assert_eq!(extra["_modifier"]["directives"], serde_json::json!(["remove symfony/*", "add monolog/monolog ^3.0"]));
+        assert_eq!(extra["branch-alias"]["dev-main"], "1.x-dev");
+    }
+
+    #[test]
+    fn annotate_creates_extra() {
+        let c = composer_json(r#"{ "name": "foo/bar", "require": {} }"#);
+        let m: ModifyComposerJson = serde_json::from_str(r#"{ "add": { "require": { "psr/log": "^3.0" } } }"#).unwrap();
+
+        let mut applied = Applied::new(true);
+        let c = apply_recording(c, &m, &ModifyOptions::default(), &mut Profile::default(), &mut applied).unwrap();
+        let extra = annotate(c, &applied, "2023-09-01T12:00:00+00:00").extra.unwrap();
+
+        assert_eq!(extra["_modifier"]["directives"], serde_json::json!(["add psr/log ^3.0"]));
+    }
+
+    #[test]
+    fn modify_annotates_conversions_but_not_unmatched_directives() {
+        let dir = TempDir::new("annotate-conversions");
+
+        let file = dir.join("composer.json");
+        std::fs::write(&file, r#"{ "name": "foo/bar", "require": { "php": ">=8.1" }, "autoload": { "psr-0": { "Foo\\": "src/" } } }"#).unwrap();
+
+        let options = ModifyOptions {
+            annotate: true,
+            convert: vec![Conversion::Psr0ToPsr4],
+            drop: vec!["laminas/*".to_string()],
+            ..Default::default()
+        };
+        let c = handle_modify(file.to_str().unwrap(), None, &options).unwrap();
+
+        assert_eq!(c.extra.unwrap()["_modifier"]["directives"], serde_json::json!(["convert psr0-to-psr4"]));
+    }
+
+    #[test]
+    fn ensure_replaces_existing_constraint() {
+        let c = composer_json(r#"{ "name": "foo/bar", "require": { "php": ">=8.1", "monolog/monolog": "^2.0" } }"#);
+        let m = with_ensured(ModifyComposerJson::default(), &["monolog/monolog=^3.0".to_string()]).unwrap();
+
+        let c = add(c, &m, &ModifyOptions::default(), &mut Applied::default()).unwrap();
+
+        assert_eq!(c.package_links.require.get("monolog/monolog").map(String::as_str), Some("^3.0"));
+        assert_eq!(c.package_links.require.get("php").map(String::as_str), Some(">=8.1"));
+    }
+
+    #[test]
+    fn ensure_adds_missing_package() {
+        let c = composer_json(r#"{ "name": "foo/bar", "require": {} }"#);
+        let m = with_ensured(ModifyComposerJson::default(), &["psr/log=>=1.0 <4.0".to_string()]).unwrap();
+
+        let c = add(c, &m, &ModifyOptions::default(), &mut Applied::default()).unwrap();
+
+        assert_eq!(c.package_links.require.get("psr/log").map(String::as_str), Some(">=1.0 <4.0"));
+    }
+
+    #[test]
+    fn add_repositories_appends_new_repository() {
+        let c = composer_json(r#"{ "name": "foo/bar", "require": {} }"#);
+        let m: ModifyComposerJson = serde_json::from_str(r#"{
+            "add": { "repositories": [{ "type": "vcs", "url": "https://github.com/foo/fork" }] }
+        }"#).unwrap();
+
+        let c = add(c, &m, &ModifyOptions::default(), &mut Applied::default()).unwrap();
+
+        assert_eq!(serde_json::to_value(&c.repositories).unwrap(), json!([{ "type": "vcs", "url": "https://github.com/foo/fork" }]));
+    }
+
+    #[test]
+    fn add_repositories_skips_existing_url() {
+        let c = composer_json(r#"{
+            "name": "foo/bar",
+            "require": {},
+            "repositories": [{ "type": "vcs", "url": "https://github.com/foo/fork" }]
+        }"#);
+        let m: ModifyComposerJson = serde_json::from_str(r#"{
+            "add": { "repositories": [{ "type": "vcs", "url": "https://github.com/foo/fork" }] }
+        }"#).unwrap();
+
+        let c = add(c, &m, &ModifyOptions::default(), &mut Applied::default()).unwrap();
+        let c = add(c, &m, &ModifyOptions::default(), &mut Applied::default()).unwrap();
+
+        assert_eq!(c.repositories.unwrap().len(), 1);
+    }
+
+    #[test]
+    fn add_repositories_compares_entries_without_url() {
+        let c = composer_json(r#"{
+            "name": "foo/bar",
+            "require": {},
+            "repositories": [{ "type": "package", "package": { "name": "foo/a", "version": "1.0.0" } }]
+        }"#);
+        let m: ModifyComposerJson = serde_json::from_str(r#"{
+            "add": { "repositories": [
+                { "type": "package", "package": { "name": "foo/a", "version": "1.0.0" } },
+                { "type": "package", "package": { "name": "foo/b", "version": "1.0.0" } },
+                { "packagist.org": false }
+            ] }
+        }"#).unwrap();
+
+        let c = add(c, &m, &ModifyOptions::default(), &mut Applied::default()).unwrap();
+        let c = add(c, &m, &ModifyOptions::default(), &mut Applied::default()).unwrap();
+
+        assert_eq!(serde_json::to_value(&c.repositories).unwrap(), json!([
+            { "type": "package", "package": { "name": "foo/a", "version": "1.0.0" } },
+            { "type": "package", "package": { "name": "foo/b", "version": "1.0.0" } },
+            { "packagist.org": false },
+        ]));
+    }
+
+    #[test]
+    fn ensure_rejects_malformed_value() {
+        assert!(with_ensured(ModifyComposerJson::default(), &["monolog/monolog".to_string()]).is_err());
+        assert!(with_ensured(ModifyComposerJson::default(), &["monolog/monolog=".to_string()]).is_err());
+    }
+
+    fn patterns(patterns: &[&str]) -> Require {
+        patterns.iter().map(|p| (PackagePattern::new(p).unwrap(), "*".to_string())).collect()
+    }
+
+    fn require_composer_json() -> ComposerJson {
+        composer_json(r#"{
+            "name": "foo/bar",
+            "require": { "php": ">=8.1", "monolog/monolog": "^3.0", "monolog/monolog-bundle": "^3.8", "symfony/yaml": "^6.0", "symfony/console": "^6.3" }
+        }"#)
+    }
+
+    #[test]
+    fn remove_require_exact_name() {
+        let c = remove_require(require_composer_json(), &patterns(&["monolog/monolog"]), &false, &mut Applied::default()).unwrap();
+
+        assert_eq!(c.package_links.require, require(&[
+            ("php", ">=8.1"),
+            ("monolog/monolog-bundle", "^3.8"),
+            ("symfony/yaml", "^6.0"),
+            ("symfony/console", "^6.3"),
+        ]));
+    }
+
+    #[test]
+    fn remove_require_wildcard() {
+        let c = remove_require(require_composer_json(), &patterns(&["symfony/*"]), &false, &mut Applied::default()).unwrap();
+
+        assert_eq!(c.package_links.require, require(&[
+            ("php", ">=8.1"),
+            ("monolog/monolog", "^3.0"),
+            ("monolog/monolog-bundle", "^3.8"),
+        ]));
+    }
+
+    #[test]
+    fn remove_require_without_matches() {
+        let c = remove_require(require_composer_json(), &patterns(&["psr/log", "laravel/*"]), &false, &mut Applied::default()).unwrap();
+
+        assert_eq!(c.package_links.require, require_composer_json().package_links.require);
+    }
+
+    #[test]
+    fn remove_require_dev_removes_matching_packages() {
+        let c = composer_json(r#"{
+            "name": "foo/bar",
+            "require": { "phpunit/phpunit": "^10.0" },
+            "require-dev": { "phpunit/phpunit": "^10.0", "symfony/var-dumper": "^6.0" }
+        }"#);
+        let c = remove_require_dev(c, &patterns(&["phpunit/*"]), &false, &mut Applied::default()).unwrap();
+
+        assert_eq!(c.package_links.require, require(&[("phpunit/phpunit", "^10.0")]));
+        assert_eq!(c.package_links.require_dev, Some(require(&[("symfony/var-dumper", "^6.0")])));
+    }
+
+    #[test]
+    fn remove_require_dev_drops_empty_require_dev() {
+        let c = composer_json(r#"{ "name": "foo/bar", "require": {}, "require-dev": { "phpunit/phpunit": "^10.0" } }"#);
+        let c = remove_require_dev(c, &patterns(&["phpunit/phpunit"]), &false, &mut Applied::default()).unwrap();
+
+        assert_eq!(c.package_links.require_dev, None);
+        assert!(!serde_json::to_string(&c).unwrap().contains("require-dev"));
+    }
+
+    #[test]
+    fn drop_removes_matching_packages() {
+        let c = composer_json(r#"{
+            "name": "foo/bar",
+            "require": { "php": ">=8.1", "symfony/yaml": "^6.0", "symfony/console": "^6.3", "monolog/monolog": "^3.0" },
+            "require-dev": { "symfony/var-dumper": "^6.0", "phpunit/phpunit": "^10.0" }
+        }"#);
+        let m = with_dropped(ModifyComposerJson::default(), &["symfony/*".to_string()]).unwrap();
+
+        let c = remove(c, &m, &false, &mut Applied::default()).unwrap();
+
+        let mut require: Vec<&String> = c.package_links.require.keys().collect();
+        require.sort();
+        let require_dev: Vec<&String> = c.package_links.require_dev.as_ref().unwrap().keys().collect();
+
+        assert_eq!(require, vec!["monolog/monolog", "php"]);
+        assert_eq!(require_dev, vec!["phpunit/phpunit"]);
+    }
+
+    #[test]
+    fn drop_with_invalid_pattern_fails_to_compile() {
+        let error = with_dropped(ModifyComposerJson::default(), &["symfony/{yaml,console".to_string()]).unwrap_err();
+
+        assert!(matches!(error, ModifierError::PatternCompile { pattern, .. } if pattern == "symfony/{yaml,console"));
+    }
+
+    #[test]
+    fn modify_with_invalid_json_fails_to_parse() {
+        let dir = TempDir::new("invalid");
+
+        let file = dir.join("composer.json");
+        std::fs::write(&file, r#"{ "name": "foo/bar", "require": { "#).unwrap();
+
+        let options = ModifyOptions { ensure: vec!["psr/log=^3.0".to_string()], dry_run: true, ..Default::default() };
+        let error = handle_modify(file.to_str().unwrap(), None, &options).unwrap_err();
+
+        assert!(matches!(error, ModifierError::JsonParse { file: Some(_), .. }));
+    }
+
+    #[test]
+    fn profile_records_parse_and_write_durations() {
+        let dir = TempDir::new("profile");
+
+        let composer_json = dir.join("composer.json");
+        let profile = dir.join("profile.json");
+        std::fs::write(&composer_json, r#"{ "name": "foo/bar", "require": { "php": ">=8.1" } }"#).unwrap();
+
+        let options = ModifyOptions {
+            ensure: vec!["monolog/monolog=^3.0".to_string()],
+            profile: Some(profile.to_str().unwrap().to_string()),
+            ..Default::default()
+        };
+
+        handle_modify(composer_json.to_str().unwrap(), None, &options).unwrap();
+
+        let json: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&profile).unwrap()).unwrap();
+
+        assert!(json["durations_ms"]["parse"].is_f64());
+        assert!(json["durations_ms"]["write"].is_f64());
+    }
+
+    #[test]
+    fn modify_all_continues_after_invalid_file() {
+        let dir = TempDir::new("batch");
+
+        let files: Vec<String> = ["a", "b", "c"].iter()
+            .map(|name| dir.join(name).join("composer.json"))
+            .map(|path| {
+                std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+                path.to_str().unwrap().to_string()
+            })
+            .collect();
+
+        std::fs::write(&files[0], r#"{ "name": "foo/a", "require": {} }"#).unwrap();
+        std::fs::write(&files[1], r#"{ "name": "foo/b", "require": "#).unwrap();
+        std::fs::write(&files[2], r#"{ "name": "foo/c", "require": {} }"#).unwrap();
+
+        let options = ModifyOptions { ensure: vec!["psr/log=^3.0".to_string()], ..Default::default() };
+        let results = modify_all(&files, None, &options, &false);
+        let fail_fast_results = modify_all(&files, None, &options, &true);
+
+        let outcomes: Vec<(&str, bool)> = results.iter().map(|r| (r.file.as_str(), r.result.is_ok())).collect();
+
+        assert_eq!(outcomes, vec![(files[0].as_str(), true), (files[1].as_str(), false), (files[2].as_str(), true)]);
+        assert_eq!(fail_fast_results.len(), 2);
+    }
+
+    #[test]
+    fn modify_writes_result_unless_dry_run() {
+        let dir = TempDir::new("write");
+
+        let file = dir.join("composer.json");
+        let file_name = file.to_str().unwrap();
+        let contents = r#"{ "name": "foo/bar", "require": { "php": ">=8.1" } }"#;
+        std::fs::write(&file, contents).unwrap();
+
+        let dry_run = ModifyOptions { ensure: vec!["psr/log=^3.0".to_string()], dry_run: true, ..Default::default() };
+        handle_modify(file_name, None, &dry_run).unwrap();
+        let after_dry_run = std::fs::read_to_string(&file).unwrap();
+
+        let run = ModifyOptions { dry_run: false, ..dry_run };
+        handle_modify(file_name, None, &run).unwrap();
+        let after_run = std::fs::read_to_string(&file).unwrap();
+
+        assert_eq!(after_dry_run, contents);
+        assert_ne!(after_run, contents);
+        assert_eq!(composer_json(&after_run).package_links.require, require(&[("php", ">=8.1"), ("psr/log", "^3.0")]));
+    }
+
+    #[test]
+    fn modify_falls_back_to_dist_file_when_allowed() {
+        let dir = TempDir::new("dist");
+
+        let file = dir.join("composer.json");
+        let file_name = file.to_str().unwrap();
+        std::fs::write(dir.join("composer.json.dist"), r#"{ "name": "foo/bar", "require": {} }"#).unwrap();
+
+        let options = ModifyOptions { ensure: vec!["psr/log=^3.0".to_string()], dry_run: true, ..Default::default() };
+        let without_dist = handle_modify(file_name, None, &options);
+        let with_dist = handle_modify(file_name, None, &ModifyOptions { allow_dist: true, ..options });
+
+        assert!(matches!(without_dist, Err(ModifierError::FileNotFound(_))));
+        assert_eq!(with_dist.unwrap().package_links.require, require(&[("psr/log", "^3.0")]));
+    }
+
+    #[test]
+    fn remove_repositories_and_scripts() {
+        let c = composer_json(r#"{
+            "name": "foo/bar",
+            "require": {},
+            "repositories": [
+                { "type": "vcs", "url": "https://github.com/foo/legacy" },
+                { "type": "composer", "url": "https://packages.example.com" }
+            ],
+            "scripts": { "post-install-cmd": "@php artisan optimize", "test": ["phpunit"] }
+        }"#);
+        let m: ModifyComposerJson = serde_json::from_str(r#"{
+            "remove": { "repositories": ["https://github.com/foo/legacy"], "scripts": ["post-install-cmd"] }
+        }"#).unwrap();
+
+        let c = remove(c, &m, &false, &mut Applied::default()).unwrap();
+        let urls: Vec<&str> = c.repositories.as_ref().unwrap().iter().map(|r| r.url.as_str()).collect();
+
+        assert_eq!(urls, vec!["https://packages.example.com"]);
+        assert_eq!(serde_json::to_value(&c.scripts).unwrap(), serde_json::json!({ "test": ["phpunit"] }));
+
+        let m: ModifyComposerJson = serde_json::from_str(r#"{ "remove": { "repositories": ["https://packages.example.com"], "scripts": ["test"] } }"#).unwrap();
+        let c = remove(c, &m, &false, &mut Applied::default()).unwrap();
+
+        assert!(c.repositories.is_none());
+        assert!(c.scripts.is_none());
+    }
+
+    #[test]
+    fn remove_repositories_by_glob() {
+        let c = composer_json(r#"{
+            "name": "foo/bar",
+            "require": {},
+            "repositories": [
+                { "type": "vcs", "url": "https://github.com/foo/legacy" },
+                { "type": "vcs", "url": "https://github.com/foo/fork" },
+                { "type": "composer", "url": "https://packages.example.com" }
+            ]
+        }"#);
+        let m = ModifyComposerJsonBuilder::new().remove_repository("https://github.com/foo/*").build().unwrap();
+
+        let c = remove(c, &m, &false, &mut Applied::default()).unwrap();
+        let urls: Vec<&str> = c.repositories.as_ref().unwrap().iter().map(|r| r.url.as_str()).collect();
+
+        assert_eq!(urls, vec!["https://packages.example.com"]);
+    }
+
+    #[test]
+    fn remove_last_repository_disabling_packagist() {
+        let c = composer_json(r#"{ "name": "foo/bar", "require": {}, "repositories": [{ "packagist.org": false }] }"#);
+        let m = ModifyComposerJsonBuilder::new().remove_repository("packagist.org").build().unwrap();
+
+        let c = remove(c, &m, &false, &mut Applied::default()).unwrap();
+
+        assert!(c.repositories.is_none());
+        assert!(serde_json::to_value(&c).unwrap().get("repositories").is_none());
+    }
+
+    #[test]
+    fn replace_urls_rewrites_repositories_and_support_source() {
+        let c = composer_json(r#"{
+            "name": "foo/bar",
+            "require": {},
+            "homepage": "https://example.com",
+            "support": { "source": "https://github.com/foo/bar/tree/main" },
+            "repositories": [{ "type": "vcs", "url": "https://github.com/foo/legacy" }]
+        }"#);
+        let replacements = [UrlReplacement::parse("https://github.com/=https://git.internal.example/mirror/").unwrap()];
+
+        let c = replace_urls(c, &replacements, &true);
+
+        assert_eq!(c.repositories.unwrap()[0].url, "https://git.internal.example/mirror/foo/legacy");
+        assert_eq!(c.support.unwrap().source.unwrap(), "https://git.internal.example/mirror/foo/bar/tree/main");
+        assert_eq!(c.homepage.unwrap(), "https://example.com");
+    }
+
+    #[test]
+    fn replace_urls_with_regex() {
+        let replacement = UrlReplacement::parse(r"regex:^https://github\.com/([^/]+)/=https://mirror.example/$1-").unwrap();
+
+        assert_eq!(replacement.apply("https://github.com/foo/bar").unwrap(), "https://mirror.example/foo-bar");
+        assert!(replacement.apply("https://gitlab.com/foo/bar").is_none());
+        assert!(UrlReplacement::parse("https://github.com").is_err());
+    }
+
+    #[test]
+    fn apply_readds_removed_package_with_added_constraint() {
+        let c = composer_json(r#"{ "name": "foo/bar", "require": { "php": ">=8.1", "psr/log": "^1.0" } }"#);
+        let m: ModifyComposerJson = serde_json::from_str(r#"{
+            "add": { "require": { "psr/log": "^3.0" } },
+            "remove": { "require": { "psr/*": "*" } }
+        }"#).unwrap();
+
+        let c = apply(c, &m, &ModifyOptions::default(), &mut Profile::default()).unwrap();
+
+        assert_eq!(c.package_links.require, require(&[("php", ">=8.1"), ("psr/log", "^3.0")]));
+    }
+
+    #[test]
+    fn apply_runs_phases_in_order() {
+        let c = composer_json(r#"{ "name": "foo/bar", "require": { "symfony/yaml": "^5.4", "symfony/console": "^5.4" } }"#);
+        let m: ModifyComposerJson = serde_json::from_str(r#"{
+            "replace": { "require": { "symfony/yaml": "^7.0", "psr/log": "^3.0" } },
+            "add": { "require": { "symfony/console": "^6.0" } },
+            "modify": { "require": { "symfony/*": "^6.4" } }
+        }"#).unwrap();
+        let mut profile = Profile::default();
+
+        let c = apply(c, &m, &ModifyOptions::default(), &mut profile).unwrap();
+
+        assert_eq!(c.package_links.require, require(&[("symfony/yaml", "^7.0"), ("symfony/console", "^6.0")]));
+        assert_eq!(profile.phases(), ["remove", "modify", "add", "replace", "config", "sort"]);
+    }
+
+    #[test]
+    fn parse_package_list_skips_blanks_and_comments() {
+        let packages = parse_package_list("# dependencies\nmonolog/monolog ^3.0\n\n  psr/log\t>=1.0 <4.0  \n").unwrap();
+
+        assert_eq!(packages, vec![
+            ("monolog/monolog".to_string(), "^3.0".to_string()),
+            ("psr/log".to_string(), ">=1.0 <4.0".to_string()),
+        ]);
+        assert!(parse_package_list("monolog/monolog").is_err());
+    }
+
+    #[test]
+    fn modify_adds_packages_from_package_list() {
+        let dir = TempDir::new("package-list");
+
+        let file = dir.join("composer.json");
+        let list = dir.join("packages.txt");
+        std::fs::write(&file, r#"{ "name": "foo/bar", "require": { "php": ">=8.1" } }"#).unwrap();
+        std::fs::write(&list, "monolog/monolog ^3.0\npsr/log ^3.0\nsymfony/yaml ^6.0\n").unwrap();
+
+        let options = ModifyOptions { from_package_list: Some(list.to_str().unwrap().to_string()), dry_run: true, ..Default::default() };
+        let c = handle_modify(file.to_str().unwrap(), None, &options).unwrap();
+        let dev = handle_modify(file.to_str().unwrap(), None, &ModifyOptions { dev: true, ..options }).unwrap();
+
+        let added = require(&[("monolog/monolog", "^3.0"), ("psr/log", "^3.0"), ("symfony/yaml", "^6.0")]);
+
+        assert_eq!(c.package_links.require, require(&[("php", ">=8.1"), ("monolog/monolog", "^3.0"), ("psr/log", "^3.0"), ("symfony/yaml", "^6.0")]));
+        assert_eq!(dev.package_links.require_dev, Some(added));
+    }
+
+    #[test]
+    fn modify_sorts_packages_from_package_list() {
+        let dir = TempDir::new("package-list-sort");
+
+        let file = dir.join("composer.json");
+        let modify_file = dir.join("modify-composer.json");
+        let list = dir.join("packages.txt");
+        std::fs::write(&file, r#"{ "name": "foo/bar", "require": { "symfony/yaml": "^6.0", "php": ">=8.1" } }"#).unwrap();
+        std::fs::write(&modify_file, r#"{ "modify": { "sort-packages": true } }"#).unwrap();
+        std::fs::write(&list, "psr/log ^3.0\nmonolog/monolog ^3.0\n").unwrap();
+
+        let options = ModifyOptions { from_package_list: Some(list.to_str().unwrap().to_string()), dry_run: true, ..Default::default() };
+        let c = handle_modify(file.to_str().unwrap(), modify_file.to_str(), &options).unwrap();
+
+        let names: Vec<&String> = c.package_links.require.keys().collect();
+
+        assert_eq!(names, ["php", "monolog/monolog", "psr/log", "symfony/yaml"]);
+    }
+
+    #[test]
+    fn apply_from_value_preserves_unknown_keys_and_order() {
+        let mut value: Value = serde_json::from_str(r#"{
+            "name": "foo/bar",
+            "x-custom": { "keep": [1, 2] },
+            "require": { "symfony/yaml": "^6.0", "psr/log": "^1.0", "php": ">=8.1" },
+            "description": "Foo bar"
+        }"#).unwrap();
+        let m: ModifyComposerJson = serde_json::from_str(r#"{ "remove": { "require": { "psr/*": "*" } } }"#).unwrap();
+
+        apply_from_value(&mut value, &m, &ModifyOptions::default(), &mut Applied::default()).unwrap();
+
+        assert_eq!(
+            serde_json::to_string(&value).unwrap(),
+            r#"{"name":"foo/bar","x-custom":{"keep":[1,2]},"require":{"symfony/yaml":"^6.0","php":">=8.1"},"description":"Foo bar"}"#,
+        );
+    }
+
+    #[test]
+    fn apply_from_value_adds_and_replaces_without_touching_missing_links() {
+        let mut value: Value = serde_json::from_str(r#"{ "name": "foo/bar", "require": { "psr/log": "^1.0" } }"#).unwrap();
+        let m: ModifyComposerJson = serde_json::from_str(r#"{
+            "remove": { "require-dev": { "phpunit/phpunit": "*" } },
+            "add": { "require": { "monolog/monolog": "^3.0" } },
+            "replace": { "require": { "psr/log": "^3.0", "symfony/yaml": "^6.0" } }
+        }"#).unwrap();
+
+        apply_from_value(&mut value, &m, &ModifyOptions::default(), &mut Applied::default()).unwrap();
+
+        assert_eq!(value, json!({ "name": "foo/bar", "require": { "psr/log": "^3.0", "monolog/monolog": "^3.0" } }));
+        assert!(value.get("require-dev").is_none());
+    }
+
+    #[test]
+    fn apply_from_value_resolves_conflicts_and_validates_names() {
+        let apply_with = |add: &str, options: &ModifyOptions| {
+            let mut value: Value = serde_json::from_str(r#"{ "require": { "psr/log": "^1.0" } }"#).unwrap();
+            let m: ModifyComposerJson = serde_json::from_str(&format!(r#"{{ "add": {{ "require": {} }} }}"#, add)).unwrap();
+
+            apply_from_value(&mut value, &m, options, &mut Applied::default()).map(|_| value)
+        };
+
+        let skip = ModifyOptions { on_conflict: OnConflict::Skip, ..Default::default() };
+        assert_eq!(apply_with(r#"{ "psr/log": "^3.0" }"#, &skip).unwrap(), json!({ "require": { "psr/log": "^1.0" } }));
+
+        let error = ModifyOptions { on_conflict: OnConflict::Error, ..Default::default() };
+        assert!(matches!(apply_with(r#"{ "psr/log": "^3.0" }"#, &error), Err(ModifierError::Validation(_))));
+        assert!(apply_with(r#"{ "psr/log": "^1.0" }"#, &error).is_ok());
+
+        let validate = ModifyOptions { validate_names: true, ..Default::default() };
+        assert!(matches!(apply_with(r#"{ "Foo_Bar/baz": "^1.0" }"#, &validate), Err(ModifierError::Validation(_))));
+    }
+
+    #[test]
+    fn modify_lossless_keeps_rest_of_file() {
+        let dir = TempDir::new("lossless");
+
+        let file = dir.join("composer.json");
+        std::fs::write(&file, r#"{ "require": { "php": ">=8.1", "psr/log": "^1.0" }, "name": "foo/bar", "x-custom": true }"#).unwrap();
+
+        let options = ModifyOptions { drop: vec!["psr/log".to_string()], lossless: true, ..Default::default() };
+        handle_modify(file.to_str().unwrap(), None, &options).unwrap();
+        let contents = std::fs::read_to_string(&file).unwrap();
+
+        assert_eq!(contents, "{\n    \"require\": {\n        \"php\": \">=8.1\"\n    },\n    \"name\": \"foo/bar\",\n    \"x-custom\": true\n}\n");
+    }
+
+    #[test]
+    fn modify_writes_four_space_indentation_by_default() {
+        let dir = TempDir::new("indent");
+
+        let file = dir.join("composer.json");
+        std::fs::write(&file, r#"{ "name": "foo/bar", "require": { "php": ">=8.1" } }"#).unwrap();
+
+        let options = ModifyOptions { ensure: vec!["psr/log=^3.0".to_string()], ..Default::default() };
+        handle_modify(file.to_str().unwrap(), None, &options).unwrap();
+        let four_spaces = std::fs::read_to_string(&file).unwrap();
+
+        let options = ModifyOptions { ensure: vec!["psr/log=^3.0".to_string()], indent: Indent::Tab, ..Default::default() };
+        handle_modify(file.to_str().unwrap(), None, &options).unwrap();
+        let tab = std::fs::read_to_string(&file).unwrap();
+
+        assert_eq!(four_spaces, "{\n    \"name\": \"foo/bar\",\n    \"require\": {\n        \"php\": \">=8.1\",\n        \"psr/log\": \"^3.0\"\n    }\n}\n");
+        assert_eq!(tab, "{\n\t\"name\": \"foo/bar\",\n\t\"require\": {\n\t\t\"php\": \">=8.1\",\n\t\t\"psr/log\": \"^3.0\"\n\t}\n}\n");
+    }
+
+    #[test]
+    fn parse_indent() {
+        assert_eq!("2".parse::<Indent>().unwrap(), Indent::Spaces(2));
+        assert_eq!("tab".parse::<Indent>().unwrap(), Indent::Tab);
+        assert!("two".parse::<Indent>().is_err());
+    }
+
+    #[test]
+    fn modify_writes_to_output_instead_of_original() {
+        let dir = TempDir::new("output");
+
+        let file = dir.join("composer.json");
+        let output = dir.join("composer.modified.json");
+        let contents = r#"{ "name": "foo/bar", "require": { "php": ">=8.1" } }"#;
+        std::fs::write(&file, contents).unwrap();
+
+        let options = ModifyOptions {
+            ensure: vec!["psr/log=^3.0".to_string()],
+            output: Some(output.to_str().unwrap().to_string()),
+            ..Default::default()
+        };
+        handle_modify(file.to_str().unwrap(), None, &options).unwrap();
+
+        let missing_dir = ModifyOptions { output: Some(dir.join("missing").join("composer.json").to_str().unwrap().to_string()), ..options };
+        let err = handle_modify(file.to_str().unwrap(), None, &missing_dir).unwrap_err();
+
+        let original = std::fs::read_to_string(&file).unwrap();
+        let written: ComposerJson = serde_json::from_str(&std::fs::read_to_string(&output).unwrap()).unwrap();
+
+        assert_eq!(original, contents);
+        assert_eq!(written.package_links.require, require(&[("php", ">=8.1"), ("psr/log", "^3.0")]));
+        assert!(matches!(err, ModifierError::Io(e) if e.kind() == std::io::ErrorKind::NotFound));
+    }
+
+    #[test]
+    fn modify_converts_psr0_to_psr4() {
+        let dir = TempDir::new("convert");
+
+        let file = dir.join("composer.json");
+        std::fs::write(&file, r#"{ "name": "foo/bar", "require": {}, "autoload": { "psr-0": { "Foo\\": "src/" } } }"#).unwrap();
+
+        let options = ModifyOptions { convert: vec![Conversion::Psr0ToPsr4], dry_run: true, ..Default::default() };
+        let c = handle_modify(file.to_str().unwrap(), None, &options).unwrap();
+
+        let autoload = serde_json::to_value(c.autoload.unwrap()).unwrap();
+
+        assert_eq!(autoload, json!({ "psr-4": { "Foo\\": "src/Foo/" } }));
+    }
+
+    fn add_with(on_conflict: OnConflict) -> Result<ComposerJson, ModifierError> {
+        let c = composer_json(r#"{ "name": "foo/bar", "require": { "php": ">=8.1", "psr/log": "^1.0" } }"#);
+        let m: ModifyComposerJson = serde_json::from_str(r#"{ "add": { "require": { "psr/log": "^3.0", "monolog/monolog": "^3.0" } } }"#).unwrap();
+
+        add(c, &m, &ModifyOptions { on_conflict, ..Default::default() }, &mut Applied::default())
+    }
+
+    #[test]
+    fn on_conflict_skip_keeps_existing_constraint() {
+        let c = add_with(OnConflict::Skip).unwrap();
+
+        assert_eq!(c.package_links.require, require(&[("php", ">=8.1"), ("psr/log", "^1.0"), ("monolog/monolog", "^3.0")]));
+    }
+
+    #[test]
+    fn on_conflict_overwrite_replaces_existing_constraint() {
+        let c = add_with(OnConflict::Overwrite).unwrap();
+
+        assert_eq!(c.package_links.require, require(&[("php", ">=8.1"), ("psr/log", "^3.0"), ("monolog/monolog", "^3.0")]));
+    }
+
+    #[test]
+    fn on_conflict_error_aborts() {
+        let error = add_with(OnConflict::Error).unwrap_err();
+
+        assert!(matches!(&error, ModifierError::Validation(message) if message.contains("psr/log is already required in require with ^1.0")));
+    }
+
+    #[test]
+    fn on_conflict_ignores_unchanged_constraint() {
+        let c = composer_json(r#"{ "name": "foo/bar", "require": { "psr/log": "^3.0" } }"#);
+        let c = add_require(c, &require(&[("psr/log", "^3.0")]), &false, OnConflict::Error, &mut Applied::default()).unwrap();
+
+        assert_eq!(c.package_links.require, require(&[("psr/log", "^3.0")]));
+    }
+
+    #[test]
+    fn apply_recording_lists_applied_directives() {
+        let c = composer_json(r#"{
+            "name": "foo/bar",
+            "require": { "php": ">=8.1", "symfony/yaml": "^6.0", "symfony/console": "^6.0", "psr/log": "^1.0" },
+            "require-dev": { "phpunit/phpunit": "^10.0" }
+        }"#);
+        let m: ModifyComposerJson = serde_json::from_str(r#"{
+            "remove": { "require": { "symfony/*": "*", "laminas/*": "*" } },
+            "modify": { "require-dev": { "phpunit/*": "^11.0" } },
+            "add": { "require": { "psr/log": "^3.0", "monolog/monolog": "^3.0" } },
+            "replace": { "require": { "php": ">=8.2", "doctrine/dbal": "^4.0" } }
+        }"#).unwrap();
+
+        let mut applied = Applied::new(true);
+        apply_recording(c, &m, &ModifyOptions::default(), &mut Profile::default(), &mut applied).unwrap();
+        let lines: Vec<String> = applied.directives.iter().map(|a| a.to_string()).collect();
+
+        assert_eq!(lines, [
+            "remove symfony/* -> 2 removed",
+            "remove laminas/* -> 0 removed",
+            "modify require-dev phpunit/* ^11.0 -> 1 modified",
+            "add psr/log ^3.0 -> 1 replaced",
+            "add monolog/monolog ^3.0 -> 1 added",
+            "replace php >=8.2 -> 1 replaced",
+            "replace doctrine/dbal ^4.0 -> 0 replaced",
+        ]);
+    }
+
+    #[test]
+    fn merge_config_unions_allow_plugins_and_platform() {
+        let mut c = composer_json(r#"{ "name": "foo/bar", "require": {} }"#);
+        c.config = Some(Config {
+            allow_plugins: Some(AllowPlugins::Map(IndexMap::from([("foo/plugin".to_string(), true), ("bar/plugin".to_string(), true)]))),
+            platform: Some(require(&[("php", "8.1.0")])),
+            ..Default::default()
+        });
+        let m = ModifyComposerJsonBuilder::new()
+            .allow_plugin("bar/plugin", false)
+            .allow_plugin("baz/plugin", true)
+            .config_platform("ext-intl", "1.0.0")
+            .build().unwrap();
+
+        let config = merge_config(c, &m, &mut Applied::default()).config.unwrap();
+
+        assert!(matches!(config.allow_plugins, Some(AllowPlugins::Map(plugins)) if plugins == IndexMap::from([
+            ("foo/plugin".to_string(), true),
+            ("bar/plugin".to_string(), false),
+            ("baz/plugin".to_string(), true),
+        ])));
+        assert_eq!(config.platform, Some(require(&[("php", "8.1.0"), ("ext-intl", "1.0.0")])));
+    }
+
+    #[test]
+    fn merge_config_replaces_allow_plugins_with_toggle() {
+        let mut c = composer_json(r#"{ "name": "foo/bar", "require": {} }"#);
+        c.config = Some(Config {
+            allow_plugins: Some(AllowPlugins::Map(IndexMap::from([("foo/plugin".to_string(), true)]))),
+            ..Default::default()
+        });
+        let m = ModifyComposerJson {
+            config: Some(ModifierConfig { allow_plugins: Some(AllowPlugins::Toggle(false)), ..Default::default() }),
+            ..Default::default()
+        };
+
+        let config = merge_config(c, &m, &mut Applied::default()).config.unwrap();
+
+        assert!(matches!(config.allow_plugins, Some(AllowPlugins::Toggle(false))));
+    }
+
+    #[test]
+    fn modify_keeps_unknown_fields() {
+        let c = composer_json(r#"{ "name": "foo/bar", "require": { "psr/log": "^1.0" }, "my-custom-field": { "keep": true } }"#);
+        let m: ModifyComposerJson = serde_json::from_str(r#"{ "remove": { "require": { "psr/log": "*" } }, "add": { "require": { "monolog/monolog": "^3.0" } } }"#).unwrap();
+
+        let c = apply(c, &m, &ModifyOptions::default(), &mut Profile::default()).unwrap();
+        let value = serde_json::to_value(&c).unwrap();
+
+        assert_eq!(value["my-custom-field"], json!({ "keep": true }));
+        assert_eq!(value["require"], json!({ "monolog/monolog": "^3.0" }));
+    }
+
+    #[test]
+    fn modify_emits_lock_stub_only_when_writing_file() {
+        let dir = TempDir::new("lock-stub");
+
+        let file = dir.join("composer.json");
+        let lock = dir.join("composer.lock");
+        std::fs::write(&file, r#"{ "name": "foo/bar", "require": { "php": ">=8.1" } }"#).unwrap();
+
+        let options = |dry_run: bool, output_format: OutputFormat| ModifyOptions {
+            dry_run,
+            output_format,
+            ensure: vec!["psr/log=^3.0".to_string()],
+            emit_lock_stub: Some(lock.to_str().unwrap().to_string()),
+            ..Default::default()
+        };
+        handle_modify(file.to_str().unwrap(), None, &options(true, OutputFormat::File)).unwrap();
+        let emitted_in_dry_run = lock.exists();
+        handle_modify(file.to_str().unwrap(), None, &options(false, OutputFormat::Patch)).unwrap();
+        let emitted_with_patch = lock.exists();
+        handle_modify(file.to_str().unwrap(), None, &options(false, OutputFormat::File)).unwrap();
+        let emitted = lock.exists();
+
+        assert!(!emitted_in_dry_run);
+        assert!(!emitted_with_patch);
+        assert!(emitted);
+    }
+
+    #[test]
+    fn modify_patch_output_leaves_file_and_transforms_original() {
+        let dir = TempDir::new("patch");
+
+        let file = dir.join("composer.json");
+        let output = dir.join("composer.patch.json");
+        let contents = r#"{ "name": "foo/bar", "require": { "php": ">=8.1", "psr/log": "^1.0" } }"#;
+        std::fs::write(&file, contents).unwrap();
+
+        let options = ModifyOptions {
+            ensure: vec!["monolog/monolog=^3.0".to_string()],
+            drop: vec!["psr/log".to_string()],
+            output_format: OutputFormat::Patch,
+            output: Some(output.to_str().unwrap().to_string()),
+            ..Default::default()
+        };
+        let modified = handle_modify(file.to_str().unwrap(), None, &options).unwrap();
+
+        let after = std::fs::read_to_string(&file).unwrap();
+        let operations: Vec<PatchOperation> = serde_json::from_str(&std::fs::read_to_string(&output).unwrap()).unwrap();
+
+        let mut patched: Value = serde_json::from_str(contents).unwrap();
+        crate::patch::tests::apply(&mut patched, &operations).unwrap();
+        let patched: ComposerJson = serde_json::from_value(patched).unwrap();
+
+        assert_eq!(after, contents);
+        assert_eq!(patched.package_links.require, modified.package_links.require);
+        assert_eq!(serde_json::to_value(&patched).unwrap(), serde_json::to_value(&modified).unwrap());
+    }
+}
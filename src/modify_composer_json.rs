@@ -1,14 +1,16 @@
-use std::collections::HashMap;
 use std::hash::Hash;
 use std::fmt;
 
+use indexmap::IndexMap;
 use regex::Regex;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 
-use crate::composer_json::{AllowPlugins, PlatformConstraint};
+use crate::composer_json::{AllowPlugins, PlatformConstraint, Repository, Stability};
+use crate::error::ModifierError;
 use crate::parse_handler::{ParseFile,ParseFileType};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct ModifyComposerJson {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub modify: Option<ModifyConfig>,
@@ -21,6 +23,23 @@ pub struct ModifyComposerJson {
 
     #[serde(skip_serializing_if = "Option::is_none")]
     pub replace: Option<ReplaceConfig>,
+
+    /// Options to merge into `config`, see [ModifierConfig].
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub config: Option<ModifierConfig>,
+
+    /// Values to set, keyed by the JSON Pointer of the field to set, e.g. `/config/process-timeout`.
+    /// Applied last, to the JSON of the composer.json file, so fields not modeled by
+    /// [ComposerJson](crate::composer_json::ComposerJson) can be set too.
+    ///
+    /// Reference: [RFC 6901](https://www.rfc-editor.org/rfc/rfc6901).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub set: Option<IndexMap<String, Value>>,
+
+    /// JSON Pointers of the fields to remove, e.g. `/scripts/post-install-cmd` or
+    /// `/require/symfony~1yaml`. Applied to the JSON of the composer.json file just before [ModifyComposerJson::set].
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unset: Option<Vec<String>>,
 }
 
 impl ParseFile for ModifyComposerJson {
@@ -29,9 +48,101 @@ impl ParseFile for ModifyComposerJson {
     }
 }
 
+impl ModifyComposerJson {
+    /// Names of the directives given in this configuration, in the order they are applied,
+    /// e.g. `remove.require`.
+    pub fn directives(&self) -> Vec<&'static str> {
+        let mut directives = Vec::new();
+
+        if let Some(remove) = &self.remove {
+            if remove.require.is_some() {
+                directives.push("remove.require");
+            }
+
+            if remove.require_dev.is_some() {
+                directives.push("remove.require-dev");
+            }
+
+            if remove.repositories.is_some() {
+                directives.push("remove.repositories");
+            }
+
+            if remove.scripts.is_some() {
+                directives.push("remove.scripts");
+            }
+        }
+
+        if let Some(modify) = &self.modify {
+            if modify.require.is_some() {
+                directives.push("modify.require");
+            }
+
+            if modify.require_dev.is_some() {
+                directives.push("modify.require-dev");
+            }
+
+            if modify.config.is_some() {
+                directives.push("modify.config");
+            }
+
+            if modify.sort_packages == Some(true) {
+                directives.push("modify.sort-packages");
+            }
+
+            if modify.minimum_stability.is_some() {
+                directives.push("modify.minimum-stability");
+            }
+
+            if modify.normalize_constraints == Some(true) {
+                directives.push("modify.normalize-constraints");
+            }
+
+            if modify.normalize_psr_4 == Some(true) {
+                directives.push("modify.normalize-psr-4");
+            }
+        }
+
+        if let Some(add) = &self.add {
+            if add.require.is_some() {
+                directives.push("add.require");
+            }
+
+            if add.repositories.is_some() {
+                directives.push("add.repositories");
+            }
+        }
+
+        if let Some(replace) = &self.replace {
+            if replace.require.is_some() {
+                directives.push("replace.require");
+            }
+        }
+
+        if let Some(config) = &self.config {
+            if config.platform.is_some() {
+                directives.push("config.platform");
+            }
+
+            if config.allow_plugins.is_some() {
+                directives.push("config.allow-plugins");
+            }
+        }
+
+        if self.unset.is_some() {
+            directives.push("unset");
+        }
+
+        if self.set.is_some() {
+            directives.push("set");
+        }
+
+        directives
+    }
+}
+
 // region <<- [ ModifyConfig ] ->>
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct ModifyConfig {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub require: Option<Require>,
@@ -41,21 +152,50 @@ pub struct ModifyConfig {
     pub require_dev: Option<Require>,
 
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub config: Option<HashMap<String, String>>,
+    pub config: Option<IndexMap<String, String>>,
+
+    /// Sort the packages of `require` and `require-dev` the way Composer's `config.sort-packages` does.
+    #[serde(rename = "sort-packages")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sort_packages: Option<bool>,
+
+    /// Set `minimum-stability` to this stability.
+    #[serde(rename = "minimum-stability")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub minimum_stability: Option<Stability>,
+
+    /// Rewrite the constraints of `require` to the equivalent caret constraint where there is
+    /// one, e.g. `~1.2` or `>=1.2 <2.0` to `^1.2`.
+    #[serde(rename = "normalize-constraints")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub normalize_constraints: Option<bool>,
+
+    /// End each PSR-4 namespace prefix of `autoload` and `autoload-dev` with a single `\\`,
+    /// and each path with a single `/`.
+    #[serde(rename = "normalize-psr-4")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub normalize_psr_4: Option<bool>,
 }
 
 // endregion [ ModifyConfig ]
 
 // region <<- [ AddConfig ] ->>
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct AddConfig {}
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AddConfig {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub require: Option<IndexMap<String, VersionConstraint>>,
+
+    /// Repositories to append to `repositories`, skipping those whose `url` is already there.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub repositories: Option<Vec<Repository>>,
+}
 
 // endregion [ AddConfig ]
 
 // region <<- [ RemoveConfig ] ->>
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct RemoveConfig {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub require: Option<Require>,
@@ -63,28 +203,252 @@ pub struct RemoveConfig {
     #[serde(rename = "require-dev")]
     #[serde(skip_serializing_if = "Option::is_none")]
     pub require_dev: Option<Require>,
+
+    /// URLs of the repositories to remove, which may contain the wildcards of [PackagePattern].
+    /// `packagist.org` matches the `{"packagist.org": false}` entry disabling Packagist.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub repositories: Option<Vec<String>>,
+
+    /// Names of the scripts to remove, either event names like `post-install-cmd` or custom script names.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scripts: Option<Vec<String>>,
 }
 
 // endregion [ RemoveConfig ]
 
 // region <<- [ ReplaceConfig ] ->>
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct ReplaceConfig {}
+/// Constraints to replace for packages that are already required. Unlike [AddConfig],
+/// packages that are not already present are left out.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ReplaceConfig {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub require: Option<IndexMap<String, VersionConstraint>>,
+}
 
 // endregion [ ReplaceConfig ]
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+// region <<- [ ModifyComposerJsonBuilder ] ->>
+
+/// Builds a [ModifyComposerJson] directive by directive, as an alternative to deserializing a
+/// modify-composer.json file.
+///
+/// ```no_run
+/// use composer_json_modifier::ModifyComposerJsonBuilder;
+///
+/// let m = ModifyComposerJsonBuilder::new()
+///     .remove_require("symfony/*")
+///     .add_require("monolog/monolog", "^3.0")
+///     .build()?;
+/// # Ok::<(), composer_json_modifier::ModifierError>(())
+/// ```
+#[derive(Debug, Default)]
+pub struct ModifyComposerJsonBuilder {
+    m: ModifyComposerJson,
+    error: Option<ModifierError>,
+}
+
+impl ModifyComposerJsonBuilder {
+    pub fn new() -> ModifyComposerJsonBuilder {
+        ModifyComposerJsonBuilder::default()
+    }
+
+    /// Removes packages matching `pattern` from `require`.
+    pub fn remove_require(mut self, pattern: &str) -> Self {
+        if let Some(pattern) = self.pattern(pattern) {
+            self.remove().require.get_or_insert_with(IndexMap::new).insert(pattern, "*".to_string());
+        }
+
+        self
+    }
+
+    /// Removes packages matching `pattern` from `require-dev`.
+    pub fn remove_require_dev(mut self, pattern: &str) -> Self {
+        if let Some(pattern) = self.pattern(pattern) {
+            self.remove().require_dev.get_or_insert_with(IndexMap::new).insert(pattern, "*".to_string());
+        }
+
+        self
+    }
+
+    /// Removes the repositories matching `url`, see [RemoveConfig::repositories].
+    pub fn remove_repository(mut self, url: &str) -> Self {
+        self.remove().repositories.get_or_insert_with(Vec::new).push(url.to_string());
+
+        self
+    }
+
+    /// Removes the script with the given `name`.
+    pub fn remove_script(mut self, name: &str) -> Self {
+        self.remove().scripts.get_or_insert_with(Vec::new).push(name.to_string());
+
+        self
+    }
+
+    /// Sets the constraint of the packages in `require` matching `pattern`.
+    pub fn modify_require(mut self, pattern: &str, constraint: &str) -> Self {
+        if let Some(pattern) = self.pattern(pattern) {
+            self.modify().require.get_or_insert_with(IndexMap::new).insert(pattern, constraint.to_string());
+        }
+
+        self
+    }
+
+    /// Sets the constraint of the packages in `require-dev` matching `pattern`.
+    pub fn modify_require_dev(mut self, pattern: &str, constraint: &str) -> Self {
+        if let Some(pattern) = self.pattern(pattern) {
+            self.modify().require_dev.get_or_insert_with(IndexMap::new).insert(pattern, constraint.to_string());
+        }
+
+        self
+    }
+
+    /// Sets the `config` option `key` to `value`, see [ModifyConfig::config].
+    pub fn modify_config(mut self, key: &str, value: &str) -> Self {
+        self.modify().config.get_or_insert_with(IndexMap::new).insert(key.to_string(), value.to_string());
+
+        self
+    }
+
+    /// Sorts `require` and `require-dev` the way Composer's `config.sort-packages` does.
+    pub fn sort_packages(mut self, sort_packages: bool) -> Self {
+        self.modify().sort_packages = Some(sort_packages);
+
+        self
+    }
+
+    /// Sets `minimum-stability` to `stability`.
+    pub fn minimum_stability(mut self, stability: Stability) -> Self {
+        self.modify().minimum_stability = Some(stability);
+
+        self
+    }
+
+    /// Rewrites the constraints of `require` to caret constraints, see [ModifyConfig::normalize_constraints].
+    pub fn normalize_constraints(mut self, normalize_constraints: bool) -> Self {
+        self.modify().normalize_constraints = Some(normalize_constraints);
+
+        self
+    }
+
+    /// Normalizes the PSR-4 autoload mappings, see [ModifyConfig::normalize_psr_4].
+    pub fn normalize_psr_4(mut self, normalize_psr_4: bool) -> Self {
+        self.modify().normalize_psr_4 = Some(normalize_psr_4);
+
+        self
+    }
+
+    /// Requires `name` with `constraint` in `require`.
+    pub fn add_require(mut self, name: &str, constraint: &str) -> Self {
+        self.m.add.get_or_insert_with(AddConfig::default)
+            .require.get_or_insert_with(IndexMap::new)
+            .insert(name.to_string(), constraint.to_string());
+
+        self
+    }
+
+    /// Appends `repository` to `repositories`, unless one with the same `url` is already there.
+    pub fn add_repository(mut self, repository: Repository) -> Self {
+        self.m.add.get_or_insert_with(AddConfig::default)
+            .repositories.get_or_insert_with(Vec::new)
+            .push(repository);
+
+        self
+    }
+
+    /// Replaces the constraint of `name` in `require`, if it is required.
+    pub fn replace_require(mut self, name: &str, constraint: &str) -> Self {
+        self.m.replace.get_or_insert_with(ReplaceConfig::default)
+            .require.get_or_insert_with(IndexMap::new)
+            .insert(name.to_string(), constraint.to_string());
+
+        self
+    }
+
+    /// Fakes the platform package `name` with `constraint` in `config.platform`, see [ModifierConfig::platform].
+    pub fn config_platform(mut self, name: &str, constraint: &str) -> Self {
+        self.m.config.get_or_insert_with(ModifierConfig::default)
+            .platform.get_or_insert_with(IndexMap::new)
+            .insert(name.to_string(), constraint.to_string());
+
+        self
+    }
+
+    /// Allows or disallows the plugin `name` in `config.allow-plugins`, see [ModifierConfig::allow_plugins].
+    pub fn allow_plugin(mut self, name: &str, allowed: bool) -> Self {
+        let config = self.m.config.get_or_insert_with(ModifierConfig::default);
+
+        match config.allow_plugins.as_mut() {
+            Some(AllowPlugins::Map(plugins)) => {
+                plugins.insert(name.to_string(), allowed);
+            },
+            _ => config.allow_plugins = Some(AllowPlugins::Map(IndexMap::from([(name.to_string(), allowed)]))),
+        }
+
+        self
+    }
+
+    /// Sets the field at the JSON Pointer `pointer` to `value`, see [ModifyComposerJson::set].
+    pub fn set(mut self, pointer: &str, value: Value) -> Self {
+        self.m.set.get_or_insert_with(IndexMap::new).insert(pointer.to_string(), value);
+
+        self
+    }
+
+    /// Removes the field at the JSON Pointer `pointer`, see [ModifyComposerJson::unset].
+    pub fn unset(mut self, pointer: &str) -> Self {
+        self.m.unset.get_or_insert_with(Vec::new).push(pointer.to_string());
+
+        self
+    }
+
+    /// The built [ModifyComposerJson], or the error of the first invalid package pattern given.
+    pub fn build(self) -> Result<ModifyComposerJson, ModifierError> {
+        match self.error {
+            Some(e) => Err(e),
+            None => Ok(self.m),
+        }
+    }
+
+    fn remove(&mut self) -> &mut RemoveConfig {
+        self.m.remove.get_or_insert_with(RemoveConfig::default)
+    }
+
+    fn modify(&mut self) -> &mut ModifyConfig {
+        self.m.modify.get_or_insert_with(ModifyConfig::default)
+    }
+
+    /// Compiles `pattern`, recording the error if it is the first invalid one.
+    fn pattern(&mut self, pattern: &str) -> Option<PackagePattern> {
+        match PackagePattern::new(pattern) {
+            Ok(pattern) => Some(pattern),
+            Err(e) => {
+                self.error.get_or_insert(ModifierError::PatternCompile { pattern: pattern.to_string(), source: e });
+
+                None
+            },
+        }
+    }
+}
+
+// endregion [ ModifyComposerJsonBuilder ]
+
+/// Options merged into the `config` of a composer.json file, creating it if absent.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct ModifierConfig {
+    /// Platform packages to fake, added to `config.platform`, replacing the constraints of those already there.
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub platform: Option<HashMap<crate::composer_json::PlatformPackage, PlatformConstraint>>,
+    pub platform: Option<IndexMap<crate::composer_json::PlatformPackage, PlatformConstraint>>,
 
+    /// Plugins to allow or disallow. A map is merged into a map in `config.allow-plugins`, with
+    /// these values winning, while `true` or `false`, or a map replacing `true` or `false`,
+    /// replaces `config.allow-plugins` altogether.
     #[serde(rename = "allow-plugins")]
     #[serde(skip_serializing_if = "Option::is_none")]
-    allow_plugins: Option<AllowPlugins>,
+    pub allow_plugins: Option<AllowPlugins>,
 }
 
-pub type Require = HashMap<PackagePattern, VersionConstraint>;
+pub type Require = IndexMap<PackagePattern, VersionConstraint>;
 
 pub type VersionConstraint = String;
 
@@ -147,7 +511,8 @@ impl Into<String> for PackagePattern {
 
 #[cfg(test)]
 mod tests {
-    use crate::modify_composer_json::PackagePattern;
+    use crate::error::ModifierError;
+    use crate::modify_composer_json::{ModifyComposerJson, ModifyComposerJsonBuilder, PackagePattern};
     use regex::Regex;
 
     macro_rules! package_pattern_to_string_tests {
@@ -210,6 +575,169 @@ mod tests {
         package_pattern_to_regex_empty: ("", "^$"),
         package_pattern_to_regex_normal: ("foo/bar", "^foo/bar$"),
         package_pattern_to_regex_wildcard: ("foo/*", "^foo/.*$"),
+        package_pattern_to_regex_escaped: ("a.b/c+d", r"^a\.b/c\+d$"),
+        package_pattern_to_regex_escaped_wildcard: ("a.b/*", r"^a\.b/.*$"),
+        package_pattern_to_regex_single_character: ("vendor/lib?", "^vendor/lib.$"),
+        package_pattern_to_regex_mixed_wildcards: ("*/lib?.x", r"^.*/lib.\.x$"),
+        package_pattern_to_regex_alternation: ("{foo,bar}/*", "^(?:foo|bar)/.*$"),
+        package_pattern_to_regex_nested_alternation: ("{foo,ba{r,z}}/x", "^(?:foo|ba(?:r|z))/x$"),
+        package_pattern_to_regex_empty_alternation: ("foo/bar{}", "^foo/bar(?:)$"),
+        package_pattern_to_regex_comma_outside_alternation: ("a,b/c", "^a,b/c$"),
+        package_pattern_to_regex_raw: ("regex:symfony/(console|yaml)", "^(?:symfony/(console|yaml))$"),
+    }
+
+    #[test]
+    fn package_pattern_matches_regex() {
+        let p = PackagePattern::new("regex:^symfony/(console|yaml)$").unwrap();
+
+        assert!(p.matches("symfony/console"));
+        assert!(p.matches("symfony/yaml"));
+        assert!(!p.matches("symfony/console-extra"));
+        assert!(!p.matches("symfony/*"));
+        assert_eq!(serde_json::to_string(&p).unwrap(), r#""regex:^symfony/(console|yaml)$""#);
+        assert!(PackagePattern::new("regex:symfony/(console").is_err());
+    }
+
+    #[test]
+    fn package_pattern_case_insensitive() {
+        let sensitive = PackagePattern::new("monolog/*").unwrap();
+        let insensitive = PackagePattern::new("(?i)monolog/*").unwrap();
+
+        assert!(!sensitive.matches("Monolog/Monolog"));
+        assert!(insensitive.matches("Monolog/Monolog"));
+        assert!(insensitive.matches("MONOLOG/monolog"));
+        assert!(!insensitive.matches("symfony/Monolog"));
+        assert!(PackagePattern::new("(?i)regex:^Symfony/(Console|Yaml)$").unwrap().matches("symfony/yaml"));
+        assert_eq!(insensitive.to_string(), "(?i)monolog/*");
+        assert_eq!(serde_json::to_string(&insensitive).unwrap(), r#""(?i)monolog/*""#);
+        assert_eq!(serde_json::from_str::<PackagePattern>(r#""(?i)monolog/*""#).unwrap(), insensitive);
+        assert_ne!(insensitive, sensitive);
+    }
+
+    #[test]
+    fn package_pattern_glob_is_not_a_regex() {
+        let p = PackagePattern::new("symfony/(console|yaml)").unwrap();
+
+        assert!(p.matches("symfony/(console|yaml)"));
+        assert!(!p.matches("symfony/console"));
+    }
+
+    #[test]
+    fn package_pattern_matches_single_character() {
+        let p = PackagePattern::new("vendor/lib?").unwrap();
+
+        assert!(p.matches("vendor/lib1"));
+        assert!(!p.matches("vendor/lib10"));
+        assert!(!p.matches("vendor/lib"));
+    }
+
+    #[test]
+    fn package_pattern_matches_alternation() {
+        let p = PackagePattern::new("{foo,bar}/*").unwrap();
+
+        assert!(p.matches("foo/lib"));
+        assert!(p.matches("bar/lib"));
+        assert!(!p.matches("baz/lib"));
+        assert!(!p.matches("foobar/lib"));
+    }
+
+    #[test]
+    fn package_pattern_rejects_malformed_braces() {
+        assert!(PackagePattern::new("{foo,bar/*").is_err());
+        assert!(PackagePattern::new("foo,bar}/*").is_err());
+        assert!(PackagePattern::new("{foo}}/*").is_err());
+    }
+
+    #[test]
+    fn package_pattern_matches_dot_literally() {
+        let p = PackagePattern::new("a.b/c").unwrap();
+
+        assert!(p.matches("a.b/c"));
+        assert!(!p.matches("axb/c"));
+    }
+
+    #[test]
+    fn package_pattern_matches() {
+        assert!(PackagePattern::new("monolog/*").unwrap().matches("monolog/monolog"));
+        assert!(!PackagePattern::new("symfony/*").unwrap().matches("monolog/monolog"));
+        assert!(PackagePattern::new("monolog/*").unwrap().matches_pattern(&PackagePattern::new("monolog/monolog").unwrap()));
+    }
+
+    #[test]
+    fn deserialize_remove_config() {
+        let m: ModifyComposerJson = serde_json::from_str(r#"{
+            "remove": {
+                "require": { "symfony/*": "*" },
+                "require-dev": { "phpunit/phpunit": "*" },
+                "repositories": ["https://github.com/foo/legacy"],
+                "scripts": ["post-install-cmd", "test"]
+            }
+        }"#).unwrap();
+        let remove = m.remove.unwrap();
+
+        assert!(remove.require.unwrap().contains_key(&PackagePattern::new("symfony/*").unwrap()));
+        assert!(remove.require_dev.unwrap().contains_key(&PackagePattern::new("phpunit/phpunit").unwrap()));
+        assert_eq!(remove.repositories.unwrap(), vec!["https://github.com/foo/legacy"]);
+        assert_eq!(remove.scripts.unwrap(), vec!["post-install-cmd", "test"]);
+    }
+
+    #[test]
+    fn serialize_remove_config_skips_missing_fields() {
+        let m: ModifyComposerJson = serde_json::from_str(r#"{ "remove": { "scripts": ["test"] } }"#).unwrap();
+
+        assert_eq!(serde_json::to_string(&m).unwrap(), r#"{"remove":{"scripts":["test"]}}"#);
+    }
+
+    #[test]
+    fn builder_matches_deserialized_spec() {
+        let built = ModifyComposerJsonBuilder::new()
+            .remove_require("symfony/*")
+            .remove_require_dev("phpunit/phpunit")
+            .remove_repository("https://github.com/foo/legacy")
+            .remove_script("test")
+            .modify_require("doctrine/*", "^3.0")
+            .modify_config("sort-packages", "true")
+            .sort_packages(true)
+            .add_require("monolog/monolog", "^3.0")
+            .add_require("psr/log", "^3.0")
+            .replace_require("php", ">=8.2")
+            .build()
+            .unwrap();
+        let deserialized: ModifyComposerJson = serde_json::from_str(r#"{
+            "remove": {
+                "require": { "symfony/*": "*" },
+                "require-dev": { "phpunit/phpunit": "*" },
+                "repositories": ["https://github.com/foo/legacy"],
+                "scripts": ["test"]
+            },
+            "modify": {
+                "require": { "doctrine/*": "^3.0" },
+                "config": { "sort-packages": "true" },
+                "sort-packages": true
+            },
+            "add": { "require": { "monolog/monolog": "^3.0", "psr/log": "^3.0" } },
+            "replace": { "require": { "php": ">=8.2" } }
+        }"#).unwrap();
+
+        assert_eq!(serde_json::to_value(&built).unwrap(), serde_json::to_value(&deserialized).unwrap());
+    }
+
+    #[test]
+    fn empty_builder_matches_empty_spec() {
+        let built = ModifyComposerJsonBuilder::new().build().unwrap();
+
+        assert_eq!(serde_json::to_string(&built).unwrap(), "{}");
+    }
+
+    #[test]
+    fn builder_fails_on_invalid_pattern() {
+        let error = ModifyComposerJsonBuilder::new()
+            .remove_require("{symfony,laminas/*")
+            .add_require("monolog/monolog", "^3.0")
+            .build()
+            .unwrap_err();
+
+        assert!(matches!(error, ModifierError::PatternCompile { pattern, .. } if pattern == "{symfony,laminas/*"));
     }
 }
 
@@ -236,12 +764,74 @@ impl TryFrom<&str> for PackagePattern {
 }
 
 impl PackagePattern {
-    pub(crate) fn matches(&self, package: Self) -> bool {
-        self.regex.is_match(&package.pattern)
+    /// Whether the package name `package` matches this pattern.
+    pub fn matches(&self, package: &str) -> bool {
+        self.regex.is_match(package)
     }
 
-    pub(crate) fn new(pattern: &str) -> Result<PackagePattern, regex::Error> {
-        let p = format!("^{}$", pattern.replace("*", ".*"));
+    /// Whether the literal text of another pattern matches this pattern.
+    pub fn matches_pattern(&self, package: &Self) -> bool {
+        self.matches(&package.pattern)
+    }
+
+    /// Creates a pattern matching package names literally, except for the glob-style wildcards
+    /// `*`, which matches any sequence of characters, and `?`, which matches a single character,
+    /// and brace groups like `{symfony,laminas}`, which match any one of their comma-separated
+    /// alternatives. Brace groups may be nested, and unbalanced braces are an error.
+    ///
+    /// A pattern prefixed with `regex:`, like `regex:^symfony/(console|yaml)$`, is instead compiled
+    /// as a regular expression, anchored to match whole package names. A pattern prefixed with
+    /// `(?i)`, like `(?i)monolog/*` or `(?i)regex:^symfony/.*$`, matches package names regardless
+    /// of case. Prefixes are kept in the pattern, so it is serialized as given.
+    pub fn new(pattern: &str) -> Result<PackagePattern, regex::Error> {
+        if let Some(rest) = pattern.strip_prefix("(?i)") {
+            let regex = Regex::new(&format!("(?i){}", PackagePattern::new(rest)?.regex.as_str()))?;
+
+            return Ok(PackagePattern { pattern: pattern.into(), regex });
+        }
+
+        if let Some(raw) = pattern.strip_prefix("regex:") {
+            let regex = Regex::new(&format!("^(?:{})$", raw))?;
+
+            return Ok(PackagePattern { pattern: pattern.into(), regex });
+        }
+
+        let mut p = String::from("^");
+        let mut literal = String::new();
+        let mut depth = 0;
+
+        for c in pattern.chars() {
+            let translated = match c {
+                '*' => ".*",
+                '?' => ".",
+                '{' => {
+                    depth += 1;
+                    "(?:"
+                },
+                ',' if depth > 0 => "|",
+                '}' if depth > 0 => {
+                    depth -= 1;
+                    ")"
+                },
+                '}' => return Err(regex::Error::Syntax(format!("unmatched '}}' in package pattern \"{}\"", pattern))),
+                _ => {
+                    literal.push(c);
+                    continue;
+                },
+            };
+
+            p.push_str(&regex::escape(&literal));
+            p.push_str(translated);
+            literal.clear();
+        }
+
+        if depth > 0 {
+            return Err(regex::Error::Syntax(format!("unclosed '{{' in package pattern \"{}\"", pattern)));
+        }
+
+        p.push_str(&regex::escape(&literal));
+        p.push('$');
+
         let regex = Regex::new(&p)?;
     
         Ok(PackagePattern { pattern: pattern.into(), regex })
@@ -1,12 +1,134 @@
+use std::collections::HashSet;
 use std::fmt;
-use std::io;
+use std::path::{Path, PathBuf};
 
 use serde_json::to_string_pretty;
+use serde::de::{DeserializeSeed, Deserializer, MapAccess, SeqAccess, Visitor};
 use serde::{Deserialize, Serialize};
 
 use crate::composer_json::ComposerJson;
+use crate::error::ModifierError;
+use crate::lock::LockFile;
+use crate::log::info;
 use crate::modify_composer_json::ModifyComposerJson;
-use crate::fs::get_file_contents;
+use crate::fs::{find_composer_json_files, get_file_contents};
+
+/// A composer.json file found by [load_all], with the result of parsing it.
+pub(crate) type LoadedComposerJson = (PathBuf, Result<ComposerJson, ModifierError>);
+
+/// Parses every `composer.json` file found by [find_composer_json_files] in `root`, skipping
+/// directories named in `skip`, returning each path with its parse result.
+pub(crate) fn load_all(root: &Path, skip: &[String]) -> Result<Vec<LoadedComposerJson>, ModifierError> {
+    let files = find_composer_json_files(root, skip)?;
+
+    Ok(
+        files.into_iter()
+            .map(|path| {
+                let parsed = ParseFileType::ComposerJson._handle_parse_and_return::<ComposerJson>(&path.to_string_lossy());
+
+                (path, parsed)
+            })
+            .collect()
+    )
+}
+
+/// The paths of the object keys in the JSON document `contents` that occur more than once in
+/// their object, e.g. `require.psr/log`, in the order the duplicates occur.
+///
+/// serde_json silently keeps the last value of a duplicated key, which can hide a mistake made
+/// when editing a file by hand.
+pub(crate) fn find_duplicate_keys(contents: &str) -> Result<Vec<String>, serde_json::Error> {
+    let mut duplicates = Vec::new();
+    let mut deserializer = serde_json::Deserializer::from_str(contents);
+
+    DuplicateKeys { path: String::new(), duplicates: &mut duplicates }.deserialize(&mut deserializer)?;
+    deserializer.end()?;
+
+    Ok(duplicates)
+}
+
+/// Walks a JSON value at `path` without keeping it, collecting the paths of duplicated object keys.
+struct DuplicateKeys<'a> {
+    path: String,
+    duplicates: &'a mut Vec<String>,
+}
+
+impl<'de> DeserializeSeed<'de> for DuplicateKeys<'_> {
+    type Value = ();
+
+    fn deserialize<D>(self, deserializer: D) -> Result<(), D::Error> where
+        D: Deserializer<'de> {
+        deserializer.deserialize_any(self)
+    }
+}
+
+impl<'de> Visitor<'de> for DuplicateKeys<'_> {
+    type Value = ();
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("any JSON value")
+    }
+
+    fn visit_bool<E>(self, _: bool) -> Result<(), E> {
+        Ok(())
+    }
+
+    fn visit_i64<E>(self, _: i64) -> Result<(), E> {
+        Ok(())
+    }
+
+    fn visit_u64<E>(self, _: u64) -> Result<(), E> {
+        Ok(())
+    }
+
+    fn visit_f64<E>(self, _: f64) -> Result<(), E> {
+        Ok(())
+    }
+
+    fn visit_str<E>(self, _: &str) -> Result<(), E> {
+        Ok(())
+    }
+
+    fn visit_unit<E>(self) -> Result<(), E> {
+        Ok(())
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<(), A::Error> where
+        A: SeqAccess<'de> {
+        let mut i = 0;
+
+        while seq.next_element_seed(DuplicateKeys { path: format!("{}[{}]", self.path, i), duplicates: &mut *self.duplicates })?.is_some() {
+            i += 1;
+        }
+
+        Ok(())
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<(), A::Error> where
+        A: MapAccess<'de> {
+        let mut keys = HashSet::new();
+
+        while let Some(key) = map.next_key::<String>()? {
+            let path = match self.path.is_empty() {
+                true => key.clone(),
+                false => format!("{}.{}", self.path, key),
+            };
+
+            if !keys.insert(key) {
+                self.duplicates.push(path.clone());
+            }
+
+            map.next_value_seed(DuplicateKeys { path, duplicates: &mut *self.duplicates })?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Whether `file_name` has a `.yaml` or `.yml` extension.
+fn is_yaml_file(file_name: &str) -> bool {
+    matches!(Path::new(file_name).extension().and_then(|e| e.to_str()), Some("yaml" | "yml"))
+}
 
 pub(crate) trait ParseFile {
     fn parse_file_type() -> ParseFileType;
@@ -14,17 +136,20 @@ pub(crate) trait ParseFile {
 
 const COMPOSER_JSON_FILE_NAME : &str = "composer.json";
 const MODIFY_COMPOSER_JSON_FILE_NAME : &str = "modify-composer.json";
+const COMPOSER_LOCK_FILE_NAME : &str = "composer.lock";
 
 pub(crate) enum ParseFileType {
     ComposerJson,
-    ModifyComposerJson
+    ModifyComposerJson,
+    ComposerLock,
 }
 
 impl fmt::Display for ParseFileType {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             ParseFileType::ComposerJson => f.write_str(COMPOSER_JSON_FILE_NAME),
-            ParseFileType::ModifyComposerJson => f.write_str(MODIFY_COMPOSER_JSON_FILE_NAME)
+            ParseFileType::ModifyComposerJson => f.write_str(MODIFY_COMPOSER_JSON_FILE_NAME),
+            ParseFileType::ComposerLock => f.write_str(COMPOSER_LOCK_FILE_NAME),
         }
     }
 }
@@ -33,7 +158,8 @@ impl ParseFileType {
     pub(crate) fn handle_parse(&self, file_name: &str, print: &bool) -> () {
         match self {
             ParseFileType::ComposerJson => self._handle_parse::<ComposerJson>(file_name, print),
-            ParseFileType::ModifyComposerJson => self._handle_parse::<ModifyComposerJson>(file_name, print)
+            ParseFileType::ModifyComposerJson => self._handle_parse::<ModifyComposerJson>(file_name, print),
+            ParseFileType::ComposerLock => self._handle_parse::<LockFile>(file_name, print),
         }
     }
     
@@ -42,10 +168,10 @@ impl ParseFileType {
     {
         match self.parse::<S>(&file_name) {
             Ok(parsed) => {
-                println!("successfully parsed {} file: {}", self, file_name);
+                info!("successfully parsed {} file: {}", self, file_name);
                 self.print_parsed_json::<S>(parsed, file_name, print)
             }
-            Err(e) => eprintln!("error parsing {}: {}", file_name, e),
+            Err(e) => eprintln!("{}", e),
         }
     }
 
@@ -60,29 +186,46 @@ impl ParseFileType {
     }
     */
     
-    pub(crate) fn _handle_parse_and_return<S>(&self, file_name: &str) -> io::Result<S> 
+    pub(crate) fn _handle_parse_and_return<S>(&self, file_name: &str) -> Result<S, ModifierError>
         where S: for<'a> Deserialize<'a>+Serialize
     {
-        return match self.parse::<S>(&file_name) {
-            Ok(parsed) => {
-                Ok(parsed)
-            },
-            Err(e) => {
-                Err(
-                    io::Error::new(
-                        io::ErrorKind::InvalidInput,
-                        format!("error parsing {}: {}", file_name, e),
-                    )
-                )
-            },
-        }
+        self.parse::<S>(&file_name)
     }
 
-    pub(crate) fn parse<S>(&self, file_name: &str) -> io::Result<S>
+    /// Reads and parses `file_name`, failing with [ModifierError::JsonParse] naming the file if
+    /// it is not valid JSON or does not have the structure of `S`.
+    ///
+    /// Files ending in `.yaml` or `.yml`, e.g. `modify-composer.yaml`, are parsed as YAML instead,
+    /// failing with [ModifierError::YamlParse].
+    pub(crate) fn parse<S>(&self, file_name: &str) -> Result<S, ModifierError>
+        where S: for<'a> Deserialize<'a>+Serialize
+    {
+        self.parse_checked(file_name, &false)
+    }
+
+    /// Like [ParseFileType::parse], but if `reject_duplicate_keys` is set, a JSON file with an
+    /// object key given more than once, see [find_duplicate_keys], fails with [ModifierError::Validation].
+    pub(crate) fn parse_checked<S>(&self, file_name: &str, reject_duplicate_keys: &bool) -> Result<S, ModifierError>
         where S: for<'a> Deserialize<'a>+Serialize
     {
         let file_contents = get_file_contents(file_name)?;
-        let result: S = serde_json::from_str(&file_contents)?;
+
+        if is_yaml_file(file_name) {
+            return serde_yaml::from_str(&file_contents)
+                .map_err(|e| ModifierError::YamlParse { file: file_name.to_string(), source: e });
+        }
+
+        let json_parse = |e| ModifierError::JsonParse { file: Some(file_name.to_string()), source: e };
+
+        if *reject_duplicate_keys {
+            let duplicates = find_duplicate_keys(&file_contents).map_err(json_parse)?;
+
+            if !duplicates.is_empty() {
+                return Err(ModifierError::validation(format!("duplicate keys in {}: {}", file_name, duplicates.join(", "))));
+            }
+        }
+
+        let result: S = serde_json::from_str(&file_contents).map_err(json_parse)?;
     
         Ok(result)
     }
@@ -100,3 +243,118 @@ impl ParseFileType {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use crate::composer_json::ComposerJson;
+    use crate::error::ModifierError;
+    use crate::modify_composer_json::ModifyComposerJson;
+    use crate::parse_handler::{find_duplicate_keys, load_all, ParseFileType};
+    use crate::temp_dir::TempDir;
+
+    #[test]
+    fn load_all_returns_valid_and_invalid_manifests() {
+        let dir = TempDir::new("load-all");
+        fs::create_dir_all(dir.join("packages/valid")).unwrap();
+        fs::create_dir_all(dir.join("packages/invalid")).unwrap();
+        fs::create_dir_all(dir.join("vendor/foo/bar")).unwrap();
+
+        fs::write(dir.join("packages/valid/composer.json"), r#"{ "name": "foo/valid", "require": {} }"#).unwrap();
+        fs::write(dir.join("packages/invalid/composer.json"), r#"{ "name": "foo/invalid", "require": "#).unwrap();
+        fs::write(dir.join("vendor/foo/bar/composer.json"), r#"{ "name": "foo/bar", "require": {} }"#).unwrap();
+
+        let loaded = load_all(&dir, &["vendor".to_string()]).unwrap();
+
+        assert_eq!(loaded.len(), 2);
+        assert!(loaded[0].0.ends_with("packages/invalid/composer.json"));
+        assert!(matches!(loaded[0].1, Err(ModifierError::JsonParse { .. })));
+        assert!(loaded[1].0.ends_with("packages/valid/composer.json"));
+        assert_eq!(loaded[1].1.as_ref().unwrap().name, "foo/valid");
+    }
+
+    #[test]
+    fn parse_fails_with_json_parse_error_naming_file() {
+        let dir = TempDir::new("parse-error");
+
+        let syntax = dir.join("syntax.json");
+        let schema = dir.join("schema.json");
+        fs::write(&syntax, r#"{ "name": "foo/bar", "require": "#).unwrap();
+        fs::write(&schema, r#"{ "name": "foo/bar", "require": [] }"#).unwrap();
+
+        let syntax_error = ParseFileType::ComposerJson.parse::<ComposerJson>(syntax.to_str().unwrap()).unwrap_err();
+        let schema_error = ParseFileType::ComposerJson.parse::<ComposerJson>(schema.to_str().unwrap()).unwrap_err();
+
+        assert!(matches!(&syntax_error, ModifierError::JsonParse { file: Some(file), source } if file.ends_with("syntax.json") && source.is_eof()));
+        assert!(matches!(&schema_error, ModifierError::JsonParse { source, .. } if source.is_data()));
+    }
+
+    #[test]
+    fn find_duplicate_keys_in_nested_objects() {
+        let duplicates = find_duplicate_keys(r#"{
+            "name": "foo/bar",
+            "require": { "psr/log": "^1.0", "php": ">=8.1", "psr/log": "^3.0" },
+            "authors": [{ "name": "Alice", "name": "Bob" }],
+            "name": "foo/baz"
+        }"#).unwrap();
+
+        assert_eq!(duplicates, ["require.psr/log", "authors[0].name", "name"]);
+        assert!(find_duplicate_keys(r#"{ "require": { "psr/log": "^1.0" }, "require-dev": { "psr/log": "^3.0" } }"#).unwrap().is_empty());
+    }
+
+    #[test]
+    fn parse_checked_rejects_duplicate_keys() {
+        let dir = TempDir::new("duplicate-keys");
+
+        let file = dir.join("composer.json");
+        let file_name = file.to_str().unwrap();
+        fs::write(&file, r#"{ "name": "foo/bar", "require": { "psr/log": "^1.0", "psr/log": "^3.0" } }"#).unwrap();
+
+        let rejected = ParseFileType::ComposerJson.parse_checked::<ComposerJson>(file_name, &true);
+        let accepted = ParseFileType::ComposerJson.parse_checked::<ComposerJson>(file_name, &false);
+
+        assert!(matches!(rejected, Err(ModifierError::Validation(message)) if message.ends_with("composer.json: require.psr/log")));
+        assert_eq!(accepted.unwrap().package_links.require["psr/log"], "^3.0");
+    }
+
+    #[test]
+    fn parse_fails_with_file_not_found() {
+        let dir = TempDir::new("parse-missing");
+        let missing = dir.join("composer.json");
+
+        let err = ParseFileType::ComposerJson.parse::<ComposerJson>(missing.to_str().unwrap()).unwrap_err();
+
+        assert!(matches!(err, ModifierError::FileNotFound(_)));
+    }
+
+    #[test]
+    fn parse_yaml_modify_spec_like_json() {
+        let dir = TempDir::new("yaml");
+
+        let yaml = dir.join("modify-composer.yaml");
+        let json = dir.join("modify-composer.json");
+        fs::write(&yaml, "remove:\n  require:\n    symfony/*: \"*\"\n  require-dev:\n    phpunit/phpunit: \"*\"\nadd:\n  require:\n    monolog/monolog: ^3.0\n").unwrap();
+        fs::write(&json, r#"{
+            "remove": { "require": { "symfony/*": "*" }, "require-dev": { "phpunit/phpunit": "*" } },
+            "add": { "require": { "monolog/monolog": "^3.0" } }
+        }"#).unwrap();
+
+        let from_yaml = ParseFileType::ModifyComposerJson.parse::<ModifyComposerJson>(yaml.to_str().unwrap()).unwrap();
+        let from_json = ParseFileType::ModifyComposerJson.parse::<ModifyComposerJson>(json.to_str().unwrap()).unwrap();
+
+        assert_eq!(serde_json::to_value(&from_yaml).unwrap(), serde_json::to_value(&from_json).unwrap());
+    }
+
+    #[test]
+    fn parse_invalid_yaml_fails_with_yaml_parse_error() {
+        let dir = TempDir::new("yaml-error");
+
+        let yaml = dir.join("modify-composer.yml");
+        fs::write(&yaml, "remove:\n  require: [symfony/*\n").unwrap();
+
+        let error = ParseFileType::ModifyComposerJson.parse::<ModifyComposerJson>(yaml.to_str().unwrap()).unwrap_err();
+
+        assert!(matches!(error, ModifierError::YamlParse { .. }));
+    }
+}
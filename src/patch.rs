@@ -0,0 +1,157 @@
+use std::io;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// An operation of a JSON Patch, limited to the operations [diff] produces.
+///
+/// Reference: [RFC 6902](https://datatracker.ietf.org/doc/html/rfc6902#section-4).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "lowercase")]
+pub enum PatchOperation {
+    Add { path: String, value: Value },
+    Remove { path: String },
+    Replace { path: String, value: Value },
+}
+
+/// The JSON Patch transforming `old` into `new`.
+///
+/// Objects are compared key by key, removing and replacing keys in the order of `old` before
+/// adding keys in the order of `new`. Arrays and scalars that differ are replaced as a whole.
+pub fn diff(old: &Value, new: &Value) -> Vec<PatchOperation> {
+    let mut operations = Vec::new();
+
+    diff_at("", old, new, &mut operations);
+
+    operations
+}
+
+fn diff_at(path: &str, old: &Value, new: &Value, operations: &mut Vec<PatchOperation>) {
+    match (old, new) {
+        (Value::Object(old), Value::Object(new)) => {
+            for (key, old_value) in old.iter() {
+                let key_path = format!("{}/{}", path, escape(key));
+
+                match new.get(key) {
+                    Some(new_value) => diff_at(&key_path, old_value, new_value, operations),
+                    None => operations.push(PatchOperation::Remove { path: key_path }),
+                }
+            }
+
+            for (key, new_value) in new.iter().filter(|(key, _)| !old.contains_key(*key)) {
+                operations.push(PatchOperation::Add { path: format!("{}/{}", path, escape(key)), value: new_value.clone() });
+            }
+        },
+        _ if old != new => operations.push(PatchOperation::Replace { path: path.to_string(), value: new.clone() }),
+        _ => {},
+    }
+}
+
+/// Escapes `key` for use as a JSON Pointer reference token.
+///
+/// Reference: [RFC 6901](https://datatracker.ietf.org/doc/html/rfc6901#section-3).
+fn escape(key: &str) -> String {
+    key.replace('~', "~0").replace('/', "~1")
+}
+
+/// Wraps [diff] as pretty-printed JSON, as printed by `--output-format patch`.
+pub(crate) fn diff_to_json(old: &Value, new: &Value) -> Result<String, io::Error> {
+    Ok(serde_json::to_string_pretty(&diff(old, new))?)
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use std::io;
+
+    use serde_json::{json, Value};
+
+    use crate::patch::{diff, PatchOperation};
+
+    /// Applies `operations` to `value` in order.
+    pub(crate) fn apply(value: &mut Value, operations: &[PatchOperation]) -> Result<(), io::Error> {
+        for operation in operations.iter() {
+            match operation {
+                PatchOperation::Add { path, value: added } => {
+                    let (parent, key) = parent_mut(value, path)?;
+
+                    match parent {
+                        Value::Object(map) => {
+                            map.insert(key, added.clone());
+                        },
+                        Value::Array(items) if key == "-" => items.push(added.clone()),
+                        Value::Array(items) => items.insert(index(&key, items.len() + 1, path)?, added.clone()),
+                        _ => return Err(invalid_path(path)),
+                    }
+                },
+                PatchOperation::Remove { path } => {
+                    let (parent, key) = parent_mut(value, path)?;
+
+                    match parent {
+                        Value::Object(map) if map.contains_key(&key) => map.retain(|k, _| *k != key),
+                        Value::Array(items) => {
+                            items.remove(index(&key, items.len(), path)?);
+                        },
+                        _ => return Err(invalid_path(path)),
+                    }
+                },
+                PatchOperation::Replace { path, value: replacement } => {
+                    *value.pointer_mut(path).ok_or_else(|| invalid_path(path))? = replacement.clone();
+                },
+            }
+        }
+
+        Ok(())
+    }
+
+    fn unescape(token: &str) -> String {
+        token.replace("~1", "/").replace("~0", "~")
+    }
+
+    /// The value containing the target of `path`, and the unescaped last reference token of `path`.
+    fn parent_mut<'a>(value: &'a mut Value, path: &str) -> Result<(&'a mut Value, String), io::Error> {
+        let (parent, token) = path.rsplit_once('/').ok_or_else(|| invalid_path(path))?;
+        let parent = value.pointer_mut(parent).ok_or_else(|| invalid_path(path))?;
+
+        Ok((parent, unescape(token)))
+    }
+
+    fn index(token: &str, len: usize, path: &str) -> Result<usize, io::Error> {
+        match token.parse::<usize>() {
+            Ok(i) if i < len => Ok(i),
+            _ => Err(invalid_path(path)),
+        }
+    }
+
+    fn invalid_path(path: &str) -> io::Error {
+        io::Error::new(io::ErrorKind::InvalidInput, format!("invalid JSON Patch path: \"{}\"", path))
+    }
+
+    #[test]
+    fn diff_of_require_changes() {
+        let old = json!({ "name": "foo/bar", "require": { "php": ">=8.1", "psr/log": "^1.0", "a/b": "*" } });
+        let new = json!({ "name": "foo/bar", "require": { "php": ">=8.1", "psr/log": "^3.0", "monolog/monolog": "^3.0" } });
+
+        assert_eq!(diff(&old, &new), vec![
+            PatchOperation::Replace { path: "/require/psr~1log".to_string(), value: json!("^3.0") },
+            PatchOperation::Remove { path: "/require/a~1b".to_string() },
+            PatchOperation::Add { path: "/require/monolog~1monolog".to_string(), value: json!("^3.0") },
+        ]);
+        assert_eq!(serde_json::to_value(&diff(&old, &new)[0]).unwrap(), json!({ "op": "replace", "path": "/require/psr~1log", "value": "^3.0" }));
+    }
+
+    #[test]
+    fn applying_diff_yields_new_value() {
+        let old = json!({ "name": "foo/bar", "require": { "psr/log": "^1.0" }, "keywords": ["a"], "extra": { "x~y": 1 } });
+        let new = json!({ "name": "foo/baz", "require": { "monolog/monolog": "^3.0" }, "keywords": ["a", "b"], "license": "MIT" });
+
+        let mut patched = old.clone();
+        apply(&mut patched, &diff(&old, &new)).unwrap();
+
+        assert_eq!(patched, new);
+    }
+
+    #[test]
+    fn equal_values_have_empty_diff() {
+        assert!(diff(&json!({ "a": [1, 2] }), &json!({ "a": [1, 2] })).is_empty());
+    }
+}
@@ -0,0 +1,62 @@
+use std::fs;
+use std::io;
+use std::time::Instant;
+
+use indexmap::IndexMap;
+use serde::Serialize;
+
+/// Durations of the phases of a run, recorded for `--profile`.
+///
+/// Phases are kept in the order they were first recorded; recording a phase again adds to its duration.
+#[derive(Debug, Default, Serialize)]
+pub(crate) struct Profile {
+    /// Duration of each phase, in milliseconds.
+    #[serde(rename = "durations_ms")]
+    durations: IndexMap<String, f64>,
+}
+
+impl Profile {
+    /// Runs `f`, recording how long it took as `phase`.
+    pub(crate) fn time<T>(&mut self, phase: &str, f: impl FnOnce() -> T) -> T {
+        let start = Instant::now();
+        let result = f();
+
+        *self.durations.entry(phase.to_string()).or_insert(0.0) += start.elapsed().as_secs_f64() * 1000.0;
+
+        result
+    }
+
+    /// Names of the recorded phases, in the order they were first recorded.
+    #[cfg(test)]
+    pub(crate) fn phases(&self) -> Vec<&str> {
+        self.durations.keys().map(String::as_str).collect()
+    }
+
+    pub(crate) fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_else(|e| format!("error serializing profile: {}", e))
+    }
+
+    /// Writes the recorded durations as JSON to `path`.
+    pub(crate) fn write(&self, path: &str) -> Result<(), io::Error> {
+        fs::write(path, self.to_json())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::profile::Profile;
+
+    #[test]
+    fn time_records_phases_in_order() {
+        let mut profile = Profile::default();
+
+        assert_eq!(profile.time("parse", || 42), 42);
+        profile.time("write", || ());
+        profile.time("parse", || ());
+
+        let json: serde_json::Value = serde_json::from_str(&profile.to_json()).unwrap();
+
+        assert_eq!(profile.phases(), ["parse", "write"]);
+        assert!(json["durations_ms"]["parse"].as_f64().unwrap() >= 0.0);
+    }
+}
@@ -0,0 +1,387 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+
+use clap::ValueEnum;
+use indexmap::IndexMap;
+use serde::Serialize;
+
+use crate::composer_json::{canonical_name, ComposerJson};
+
+/// Format used when printing a [Report] of the changes made by a modification.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ReportFormat {
+    /// Machine-readable JSON document.
+    Json,
+
+    /// Human-readable Markdown table, e.g. for posting as a PR comment.
+    Markdown,
+}
+
+/// The package link section a [RequireChange] was made in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub enum LinkSection {
+    #[serde(rename = "require")]
+    Require,
+
+    #[serde(rename = "require-dev")]
+    RequireDev,
+}
+
+impl fmt::Display for LinkSection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LinkSection::Require => f.write_str("require"),
+            LinkSection::RequireDev => f.write_str("require-dev"),
+        }
+    }
+}
+
+/// The package link maps to compare when diffing two composer.json files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum MapSelection {
+    /// Only `require`.
+    Require,
+
+    /// Only `require-dev`.
+    RequireDev,
+
+    /// Both `require` and `require-dev`.
+    #[default]
+    Both,
+}
+
+impl MapSelection {
+    fn includes(&self, section: LinkSection) -> bool {
+        matches!((self, section), (MapSelection::Both, _) | (MapSelection::Require, LinkSection::Require) | (MapSelection::RequireDev, LinkSection::RequireDev))
+    }
+}
+
+/// The kind of change made to a single package link.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum ChangeKind {
+    #[serde(rename = "added")]
+    Added,
+
+    #[serde(rename = "removed")]
+    Removed,
+
+    #[serde(rename = "changed")]
+    Changed,
+}
+
+impl fmt::Display for ChangeKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ChangeKind::Added => f.write_str("added"),
+            ChangeKind::Removed => f.write_str("removed"),
+            ChangeKind::Changed => f.write_str("changed"),
+        }
+    }
+}
+
+/// A single added, removed or changed package link.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RequireChange {
+    pub package: String,
+
+    pub section: LinkSection,
+
+    pub change: ChangeKind,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub old: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub new: Option<String>,
+}
+
+impl ComposerJson {
+    /// Computes the changes to the selected package link maps needed to go from `self` to `other`.
+    ///
+    /// The result is sorted by section (`require` first) and then by package name,
+    /// so that it is deterministic regardless of the iteration order of the underlying maps.
+    pub fn diff_requires(&self, other: &ComposerJson, selection: MapSelection) -> Vec<RequireChange> {
+        let mut changes = Vec::new();
+
+        if selection.includes(LinkSection::Require) {
+            changes.extend(diff_links(
+                LinkSection::Require,
+                Some(&self.package_links.require),
+                Some(&other.package_links.require),
+            ));
+        }
+
+        if selection.includes(LinkSection::RequireDev) {
+            changes.extend(diff_links(
+                LinkSection::RequireDev,
+                self.package_links.require_dev.as_ref(),
+                other.package_links.require_dev.as_ref(),
+            ));
+        }
+
+        changes
+    }
+
+    /// Human-readable summary of [ComposerJson::diff_requires] for both `require` and `require-dev`, e.g.
+    ///
+    /// ```text
+    /// 1 added, 0 removed, 1 changed
+    ///   + symfony/yaml ^6.0 (require)
+    ///   ~ monolog/monolog ^2.0 -> ^3.0 (require)
+    /// ```
+    pub fn diff_summary_text(&self, other: &ComposerJson) -> String {
+        Report::new(self, other, MapSelection::Both).to_text()
+    }
+}
+
+fn diff_links(
+    section: LinkSection,
+    old: Option<&IndexMap<String, String>>,
+    new: Option<&IndexMap<String, String>>,
+) -> Vec<RequireChange> {
+    let packages: BTreeSet<&String> = old.into_iter().flat_map(|m| m.keys())
+        .chain(new.into_iter().flat_map(|m| m.keys()))
+        .collect();
+
+    packages.into_iter()
+        .filter_map(|package| {
+            let old = old.and_then(|m| m.get(package)).cloned();
+            let new = new.and_then(|m| m.get(package)).cloned();
+
+            let change = match (&old, &new) {
+                (None, Some(_)) => ChangeKind::Added,
+                (Some(_), None) => ChangeKind::Removed,
+                (Some(o), Some(n)) if o != n => ChangeKind::Changed,
+                _ => return None,
+            };
+
+            Some(RequireChange { package: package.clone(), section, change, old, new })
+        })
+        .collect()
+}
+
+/// Summary of the dependency changes made by a modification.
+#[derive(Debug, Clone, Serialize)]
+pub struct Report {
+    pub changes: Vec<RequireChange>,
+}
+
+impl Report {
+    pub(crate) fn new(original: &ComposerJson, modified: &ComposerJson, selection: MapSelection) -> Report {
+        Report { changes: original.diff_requires(modified, selection) }
+    }
+
+    /// Summary counts of the changes, followed by a line per change. See [ComposerJson::diff_summary_text].
+    pub(crate) fn to_text(&self) -> String {
+        let count = |kind: ChangeKind| self.changes.iter().filter(|c| c.change == kind).count();
+
+        let mut out = format!(
+            "{} added, {} removed, {} changed\n",
+            count(ChangeKind::Added),
+            count(ChangeKind::Removed),
+            count(ChangeKind::Changed),
+        );
+
+        for change in self.changes.iter() {
+            let line = match (&change.old, &change.new) {
+                (Some(old), Some(new)) => format!("~ {} {} -> {}", change.package, old, new),
+                (Some(old), None) => format!("- {} {}", change.package, old),
+                (None, Some(new)) => format!("+ {} {}", change.package, new),
+                (None, None) => continue,
+            };
+
+            out.push_str(&format!("  {} ({})\n", line, change.section));
+        }
+
+        out
+    }
+
+    pub(crate) fn render(&self, format: ReportFormat) -> String {
+        match format {
+            ReportFormat::Json => self.to_json(),
+            ReportFormat::Markdown => self.to_markdown(),
+        }
+    }
+
+    fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_else(|e| format!("error serializing report: {}", e))
+    }
+
+    fn to_markdown(&self) -> String {
+        if self.changes.is_empty() {
+            return "No dependency changes.\n".to_string();
+        }
+
+        let mut out = String::new();
+
+        out.push_str("| Package | Section | Change | Old | New |\n");
+        out.push_str("| --- | --- | --- | --- | --- |\n");
+
+        for change in self.changes.iter() {
+            out.push_str(&format!(
+                "| `{}` | {} | {} | {} | {} |\n",
+                change.package,
+                change.section,
+                change.change,
+                markdown_constraint(&change.old),
+                markdown_constraint(&change.new),
+            ));
+        }
+
+        out
+    }
+}
+
+/// Lists package links grouped under `vendor:` headings, followed by indented `project constraint` lines,
+/// both sorted by canonical name. Platform packages, having no vendor, are listed under `platform:`.
+pub(crate) fn group_by_vendor(entries: &[(&str, &str)]) -> String {
+    let mut vendors: BTreeMap<String, BTreeMap<String, &str>> = BTreeMap::new();
+
+    for (name, constraint) in entries.iter() {
+        let name = canonical_name(name);
+        let (vendor, project) = match name.split_once('/') {
+            Some((vendor, project)) => (vendor.to_string(), project.to_string()),
+            None => ("platform".to_string(), name.clone()),
+        };
+
+        vendors.entry(vendor).or_default().insert(project, constraint);
+    }
+
+    let mut out = String::new();
+
+    for (vendor, projects) in vendors.iter() {
+        out.push_str(&format!("{}:\n", vendor));
+
+        for (project, constraint) in projects.iter() {
+            out.push_str(&format!("  {} {}\n", project, constraint));
+        }
+    }
+
+    out
+}
+
+fn markdown_constraint(constraint: &Option<String>) -> String {
+    match constraint {
+        Some(c) => format!("`{}`", c.replace('|', "\\|")),
+        None => "-".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::composer_json::ComposerJson;
+    use crate::report::{group_by_vendor, ChangeKind, LinkSection, MapSelection, Report, ReportFormat};
+
+    fn composer_json(json: &str) -> ComposerJson {
+        serde_json::from_str(json).unwrap()
+    }
+
+    fn before_and_after() -> (ComposerJson, ComposerJson) {
+        let before = composer_json(r#"{
+            "name": "foo/bar",
+            "require": { "php": ">=7.4", "monolog/monolog": "^2.0", "psr/log": "^1.0" },
+            "require-dev": { "phpunit/phpunit": "^9.5" }
+        }"#);
+
+        let after = composer_json(r#"{
+            "name": "foo/bar",
+            "require": { "php": ">=7.4", "monolog/monolog": "^3.0", "symfony/yaml": "^6.0" },
+            "require-dev": { "phpunit/phpunit": "^9.5" }
+        }"#);
+
+        (before, after)
+    }
+
+    #[test]
+    fn diff_requires_is_sorted() {
+        let (before, after) = before_and_after();
+        let changes = before.diff_requires(&after, MapSelection::Both);
+        let summary: Vec<(&str, LinkSection, ChangeKind)> = changes.iter()
+            .map(|c| (c.package.as_str(), c.section, c.change))
+            .collect();
+
+        assert_eq!(summary, vec![
+            ("monolog/monolog", LinkSection::Require, ChangeKind::Changed),
+            ("psr/log", LinkSection::Require, ChangeKind::Removed),
+            ("symfony/yaml", LinkSection::Require, ChangeKind::Added),
+        ]);
+    }
+
+    #[test]
+    fn diff_summary_text_counts_and_lists_changes() {
+        let (before, after) = before_and_after();
+
+        assert_eq!(before.diff_summary_text(&after), concat!(
+            "1 added, 1 removed, 1 changed\n",
+            "  ~ monolog/monolog ^2.0 -> ^3.0 (require)\n",
+            "  - psr/log ^1.0 (require)\n",
+            "  + symfony/yaml ^6.0 (require)\n",
+        ));
+        assert_eq!(before.diff_summary_text(&before), "0 added, 0 removed, 0 changed\n");
+    }
+
+    #[test]
+    fn require_dev_only_diff_ignores_require_changes() {
+        let (before, mut after) = before_and_after();
+        after.package_links.require_dev = Some([("phpunit/phpunit".to_string(), "^10.0".to_string())].into());
+
+        let changes = before.diff_requires(&after, MapSelection::RequireDev);
+        let summary: Vec<(&str, LinkSection, ChangeKind)> = changes.iter()
+            .map(|c| (c.package.as_str(), c.section, c.change))
+            .collect();
+
+        assert_eq!(summary, vec![("phpunit/phpunit", LinkSection::RequireDev, ChangeKind::Changed)]);
+        assert_eq!(before.diff_requires(&after, MapSelection::Require).len(), 3);
+        assert_eq!(before.diff_requires(&after, MapSelection::Both).len(), 4);
+    }
+
+    #[test]
+    fn markdown_report_has_table_header_and_row_per_change() {
+        let (before, after) = before_and_after();
+        let markdown = Report::new(&before, &after, MapSelection::Both).render(ReportFormat::Markdown);
+        let lines: Vec<&str> = markdown.lines().collect();
+
+        assert_eq!(lines[0], "| Package | Section | Change | Old | New |");
+        assert_eq!(lines[1], "| --- | --- | --- | --- | --- |");
+        assert_eq!(&lines[2..], &[
+            "| `monolog/monolog` | require | changed | `^2.0` | `^3.0` |",
+            "| `psr/log` | require | removed | `^1.0` | - |",
+            "| `symfony/yaml` | require | added | - | `^6.0` |",
+        ]);
+    }
+
+    #[test]
+    fn markdown_report_without_changes() {
+        let (before, _) = before_and_after();
+        let markdown = Report::new(&before, &before, MapSelection::Both).render(ReportFormat::Markdown);
+
+        assert_eq!(markdown, "No dependency changes.\n");
+    }
+
+    #[test]
+    fn json_report_lists_changes() {
+        let (before, after) = before_and_after();
+        let json: serde_json::Value = serde_json::from_str(&Report::new(&before, &after, MapSelection::Both).render(ReportFormat::Json)).unwrap();
+
+        assert_eq!(json["changes"].as_array().unwrap().len(), 3);
+        assert_eq!(json["changes"][0]["package"], "monolog/monolog");
+        assert_eq!(json["changes"][0]["change"], "changed");
+    }
+
+    #[test]
+    fn group_by_vendor_layout() {
+        let c = composer_json(r#"{
+            "name": "foo/bar",
+            "require": { "symfony/yaml": "^6.0", "monolog/monolog": "^3.0", "symfony/console": "^6.3" }
+        }"#);
+        let entries: Vec<(&str, &str)> = c.package_links.require.iter().map(|(k, v)| (k.as_str(), v.as_str())).collect();
+
+        assert_eq!(group_by_vendor(&entries), concat!(
+            "monolog:\n",
+            "  monolog ^3.0\n",
+            "symfony:\n",
+            "  console ^6.3\n",
+            "  yaml ^6.0\n",
+        ));
+    }
+}
@@ -0,0 +1,560 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::sync::OnceLock;
+
+use indexmap::IndexMap;
+use regex::Regex;
+use serde::Deserialize;
+
+use crate::composer_json::{compare_package_names, is_platform_package, ComposerJson, PlatformCheck};
+use crate::error::ModifierError;
+use crate::fs::get_file_contents;
+use crate::log::info;
+use crate::parse_handler::ParseFileType;
+use crate::spdx;
+
+/// The pattern a package name must match since Composer 2.0.
+///
+/// Reference: [The composer.json schema (name)](https://getcomposer.org/doc/04-schema.md#name).
+const PACKAGE_NAME_PATTERN: &str = r"^[a-z0-9]([_.-]?[a-z0-9]+)*/[a-z0-9](([_.]|-{1,2})?[a-z0-9]+)*$";
+
+/// The pattern the `version` field must match: `X.Y.Z` or `vX.Y.Z`, with an optional suffix of
+/// `-dev`, `-patch` (`-p`), `-alpha` (`-a`), `-beta` (`-b`) or `-RC`, all but `-dev` optionally followed by a number.
+///
+/// Reference: [The composer.json schema (version)](https://getcomposer.org/doc/04-schema.md#version).
+const VERSION_PATTERN: &str = r"^v?[0-9]+\.[0-9]+\.[0-9]+(-dev|-(patch|p|alpha|a|beta|b|RC)[0-9]*)?$";
+
+/// A basic pattern an author's `email` must match: a local part and a domain with at least one dot,
+/// without whitespace. Deliberately more lenient than RFC 5322, to only catch clearly malformed addresses.
+const EMAIL_PATTERN: &str = r"^[^@\s]+@[^@\s.]+(\.[^@\s.]+)+$";
+
+/// A problem found while validating a composer.json file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidationError {
+    /// Path of the offending field, e.g. `require` or `authors[0].email`.
+    pub path: String,
+
+    /// Human-readable description of the problem.
+    pub message: String,
+}
+
+impl ValidationError {
+    pub(crate) fn new(path: &str, message: String) -> ValidationError {
+        ValidationError { path: path.into(), message }
+    }
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.path, self.message)
+    }
+}
+
+/// The package links of a composer.json file, in the order they appear in the file.
+#[derive(Debug, Deserialize)]
+struct OrderedPackageLinks {
+    #[serde(default)]
+    require: IndexMap<String, String>,
+
+    #[serde(rename = "require-dev")]
+    #[serde(default)]
+    require_dev: IndexMap<String, String>,
+}
+
+/// Options controlling which checks [handle_validate] runs.
+#[derive(Debug, Default)]
+pub(crate) struct ValidateOptions {
+    pub strict_order: bool,
+    pub require_name: bool,
+
+    /// Report unrecognized license identifiers as errors instead of warnings.
+    pub strict_license: bool,
+
+    /// Report only the first error found, instead of all of them.
+    pub fail_fast: bool,
+}
+
+pub(crate) fn handle_validate(file_name: &str, options: &ValidateOptions) -> Result<(), ModifierError> {
+    let contents = get_file_contents(file_name)?;
+    let errors = validate_contents(&contents, options)?;
+
+    if errors.is_empty() {
+        info!("{} is valid", file_name);
+
+        return Ok(());
+    }
+
+    for error in errors.iter() {
+        eprintln!("{}", error);
+    }
+
+    Err(ModifierError::validation(format!("{} has {} validation error(s)", file_name, errors.len())))
+}
+
+/// Checks the platform requirements of the composer.json file `file_name`, including those of
+/// `require-dev` unless `no_dev` is set, against its `config.platform`, printing the outcome for
+/// each in the style of Composer's `check-platform-reqs`, and fails if any is unsatisfied.
+pub(crate) fn handle_check_platform_reqs(file_name: &str, no_dev: &bool) -> Result<(), ModifierError> {
+    let c = ParseFileType::ComposerJson._handle_parse_and_return::<ComposerJson>(file_name)?;
+    let mut unsatisfied = 0;
+
+    for (name, constraint, check) in c.check_platform_requirements(!no_dev) {
+        match check {
+            PlatformCheck::Satisfied(version) => println!("{} {} success", name, version),
+            PlatformCheck::Unsatisfied(version) => {
+                unsatisfied += 1;
+                println!("{} {} {} requires {} ({}) failed", name, version, c.name, name, constraint);
+            },
+            PlatformCheck::Missing => println!("{} n/a not set in config.platform", name),
+            PlatformCheck::Invalid(e) => eprintln!("warning: cannot check {} ({}): {}", name, constraint, e),
+        }
+    }
+
+    if unsatisfied == 0 {
+        return Ok(());
+    }
+
+    Err(ModifierError::validation(format!("{} has {} unsatisfied platform requirement(s)", file_name, unsatisfied)))
+}
+
+/// Validates the contents of a composer.json file, returning all problems found, or only the
+/// first with [ValidateOptions::fail_fast], in which case no further checks are run once it is found.
+///
+/// A given `name` must always be valid; with [ValidateOptions::require_name] it must also be given.
+pub(crate) fn validate_contents(contents: &str, options: &ValidateOptions) -> Result<Vec<ValidationError>, ModifierError> {
+    let c: ComposerJson = serde_json::from_str(contents)?;
+    let mut errors = Vec::new();
+
+    macro_rules! check {
+        ($found:expr) => {
+            errors.extend($found);
+
+            if options.fail_fast && !errors.is_empty() {
+                errors.truncate(1);
+
+                return Ok(errors);
+            }
+        };
+    }
+
+    if options.require_name {
+        check!(validate_required_name(&c.name).err());
+    } else if !c.name.is_empty() {
+        check!(validate_name(&c.name).err());
+    }
+
+    check!(validate_version(&c).err());
+    check!(validate_autoload_psr4(&c));
+    check!(validate_author_emails(&c));
+
+    if options.strict_license {
+        check!(validate_license(&c));
+    } else {
+        validate_license(&c).iter().for_each(|e| eprintln!("warning: {}", e));
+    }
+
+    if options.strict_order {
+        check!(validate_package_order(contents)?);
+    }
+
+    Ok(errors)
+}
+
+/// Checks that the `version` of `c`, if given, matches [VERSION_PATTERN].
+pub(crate) fn validate_version(c: &ComposerJson) -> Result<(), ValidationError> {
+    static VERSION: OnceLock<Regex> = OnceLock::new();
+
+    match &c.version {
+        Some(version) if !VERSION.get_or_init(|| Regex::new(VERSION_PATTERN).unwrap()).is_match(version) => {
+            Err(ValidationError::new("version", format!("\"{}\" does not match the pattern {}", version, VERSION_PATTERN)))
+        },
+        _ => Ok(()),
+    }
+}
+
+/// Checks the PSR-4 mappings of `autoload` and `autoload-dev` of `c`: namespace prefixes must end
+/// in `\\`, and only the fallback prefix `""` may map to an empty path.
+///
+/// Reference: [The composer.json schema (PSR-4)](https://getcomposer.org/doc/04-schema.md#psr-4).
+pub(crate) fn validate_autoload_psr4(c: &ComposerJson) -> Vec<ValidationError> {
+    let mut errors = Vec::new();
+
+    for (path, autoload) in [("autoload.psr-4", &c.autoload), ("autoload-dev.psr-4", &c.autoload_dev)] {
+        for (prefix, dir) in autoload.iter().flat_map(|a| a.psr_4.iter().flatten()) {
+            if !prefix.is_empty() && !prefix.ends_with('\\') {
+                errors.push(ValidationError::new(path, format!("namespace prefix \"{}\" must end with \"\\\\\"", prefix)));
+            } else if !prefix.is_empty() && dir.trim().is_empty() {
+                errors.push(ValidationError::new(path, format!("namespace prefix \"{}\" maps to an empty path", prefix)));
+            }
+        }
+    }
+
+    errors
+}
+
+/// Checks the `email` of each of the `authors` of `c` against [EMAIL_PATTERN]. Empty emails are
+/// skipped, as some packages leave them out.
+pub(crate) fn validate_author_emails(c: &ComposerJson) -> Vec<ValidationError> {
+    static EMAIL: OnceLock<Regex> = OnceLock::new();
+
+    c.authors.iter().flatten().enumerate()
+        .filter(|(_, a)| !a.email.trim().is_empty())
+        .filter(|(_, a)| !EMAIL.get_or_init(|| Regex::new(EMAIL_PATTERN).unwrap()).is_match(&a.email))
+        .map(|(i, a)| ValidationError::new(&format!("authors[{}]", i), format!("email \"{}\" of {} is not a valid email address", a.email, a.name)))
+        .collect()
+}
+
+/// Checks that each `license` of `c` is an identifier of the SPDX License List, see [spdx::LICENSES],
+/// a custom `LicenseRef-` identifier, or `proprietary`. Identifiers are compared case-insensitively,
+/// and may be combined into an expression like `(LGPL-2.1-only or GPL-3.0-or-later)`, optionally
+/// with a `WITH` exception.
+///
+/// Reference: [The composer.json schema (license)](https://getcomposer.org/doc/04-schema.md#license).
+pub(crate) fn validate_license(c: &ComposerJson) -> Vec<ValidationError> {
+    let mut errors = Vec::new();
+
+    for license in c.license.iter().flat_map(|l| l.as_slice()) {
+        let expression = license.replace(['(', ')'], " ");
+        let mut tokens = expression.split_whitespace();
+
+        while let Some(token) = tokens.next() {
+            match token.to_ascii_lowercase().as_str() {
+                "or" | "and" => continue,
+                "with" => {
+                    tokens.next();
+                    continue;
+                },
+                "proprietary" => continue,
+                lowercase if lowercase.starts_with("licenseref-") => continue,
+                _ => {},
+            }
+
+            if !spdx::LICENSES.iter().any(|id| id.eq_ignore_ascii_case(token)) {
+                errors.push(ValidationError::new("license", format!("\"{}\" is not a recognized SPDX license identifier", token)));
+            }
+        }
+    }
+
+    errors
+}
+
+/// Checks that `name` is a valid package name, i.e. that it matches [PACKAGE_NAME_PATTERN].
+pub(crate) fn validate_name(name: &str) -> Result<(), ValidationError> {
+    static PACKAGE_NAME: OnceLock<Regex> = OnceLock::new();
+
+    if PACKAGE_NAME.get_or_init(|| Regex::new(PACKAGE_NAME_PATTERN).unwrap()).is_match(name) {
+        return Ok(());
+    }
+
+    Err(ValidationError::new("name", format!("\"{}\" does not match the pattern {}", name, PACKAGE_NAME_PATTERN)))
+}
+
+/// Checks that `name` is set and valid, as is required for published packages (libraries).
+pub(crate) fn validate_required_name(name: &str) -> Result<(), ValidationError> {
+    if name.trim().is_empty() {
+        return Err(ValidationError::new("name", "a name is required for published packages".to_string()));
+    }
+
+    validate_name(name)
+}
+
+/// Validates a package link about to be added to `require` or `require-dev`.
+///
+/// Platform packages are always accepted. Other names are rejected when they are clearly
+/// invalid, i.e. empty, containing whitespace or not of the form `vendor/project`.
+/// With `strict` the name must furthermore match [PACKAGE_NAME_PATTERN] exactly.
+pub(crate) fn new_require_entry(name: &str, constraint: &str, strict: &bool) -> Result<(String, String), ValidationError> {
+    let entry = (name.to_string(), constraint.to_string());
+
+    if is_platform_package(name) {
+        return Ok(entry);
+    }
+
+    if *strict {
+        return validate_name(name)
+            .map(|_| entry)
+            .map_err(|e| ValidationError::new("require", e.message));
+    }
+
+    let is_clearly_invalid = name.chars().any(char::is_whitespace)
+        || match name.split_once('/') {
+            Some((vendor, project)) => vendor.is_empty() || project.is_empty() || project.contains('/'),
+            None => true,
+        };
+
+    if is_clearly_invalid {
+        return Err(ValidationError::new("require", format!("\"{}\" is not a valid package name", name)));
+    }
+
+    Ok(entry)
+}
+
+/// Reports `require` and `require-dev` sections whose keys are not in the order Composer
+/// writes them when `sort-packages` is enabled.
+pub(crate) fn validate_package_order(contents: &str) -> Result<Vec<ValidationError>, ModifierError> {
+    let links: OrderedPackageLinks = serde_json::from_str(contents)?;
+    let mut errors = Vec::new();
+
+    for (path, packages) in [("require", &links.require), ("require-dev", &links.require_dev)] {
+        let out_of_order = packages.keys()
+            .zip(packages.keys().skip(1))
+            .find(|(a, b)| compare_package_names(a, b) == Ordering::Greater);
+
+        if let Some((a, b)) = out_of_order {
+            errors.push(ValidationError::new(path, format!("packages are not sorted: \"{}\" should come before \"{}\"", b, a)));
+        }
+    }
+
+    Ok(errors)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::composer_json::ComposerJson;
+    use crate::error::ModifierError;
+    use crate::temp_dir::TempDir;
+    use crate::validate::{handle_check_platform_reqs, new_require_entry, validate_author_emails, validate_autoload_psr4, validate_contents, validate_license, validate_name, validate_package_order, validate_required_name, validate_version, ValidateOptions};
+
+    #[test]
+    fn valid_package_names() {
+        for name in ["monolog/monolog", "igorw/event-source", "symfony/polyfill-php80", "foo.bar/baz_qux", "a/b--c"] {
+            assert!(validate_name(name).is_ok(), "{} should be valid", name);
+        }
+    }
+
+    #[test]
+    fn invalid_package_names() {
+        for name in ["", "monolog", "Monolog/Monolog", "foo bar/baz", "foo/-bar", "foo//bar"] {
+            assert!(validate_name(name).is_err(), "{} should be invalid", name);
+        }
+    }
+
+    fn validate_name_of(name: &str) -> Vec<String> {
+        let contents = serde_json::json!({ "name": name, "require": {} }).to_string();
+
+        validate_contents(&contents, &ValidateOptions::default()).unwrap()
+            .iter()
+            .map(|e| e.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn validate_accepts_valid_name() {
+        assert!(validate_name_of("monolog/monolog").is_empty());
+    }
+
+    #[test]
+    fn validate_rejects_uppercase_name() {
+        let errors = validate_name_of("Monolog/Monolog");
+
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].starts_with("name: \"Monolog/Monolog\" does not match the pattern"));
+    }
+
+    #[test]
+    fn validate_rejects_name_with_whitespace() {
+        let errors = validate_name_of("monolog/mono log");
+
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].starts_with("name: \"monolog/mono log\""));
+    }
+
+    fn with_version(version: &str) -> ComposerJson {
+        serde_json::from_value(serde_json::json!({ "name": "foo/bar", "version": version, "require": {} })).unwrap()
+    }
+
+    #[test]
+    fn valid_versions() {
+        for version in ["1.0.0", "v2.0.4", "1.0.0-dev", "1.0.0-patch", "v2.0.4-p1", "1.0.0-alpha", "1.0.0-a2", "1.0.0-beta", "1.0.0-b3", "1.0.0-RC5"] {
+            assert!(validate_version(&with_version(version)).is_ok(), "{} should be valid", version);
+        }
+    }
+
+    #[test]
+    fn invalid_versions() {
+        for version in ["1.0", "latest", "1.0.0-dev1", "1.0.0-foo", "1.0.0.0"] {
+            let error = validate_version(&with_version(version)).unwrap_err();
+
+            assert_eq!(error.path, "version");
+            assert!(error.message.starts_with(&format!("\"{}\"", version)));
+        }
+    }
+
+    #[test]
+    fn missing_version_is_valid() {
+        let c: ComposerJson = serde_json::from_str(r#"{ "name": "foo/bar", "require": {} }"#).unwrap();
+
+        assert!(validate_version(&c).is_ok());
+    }
+
+    #[test]
+    fn valid_author_emails() {
+        let c: ComposerJson = serde_json::from_str(r#"{ "name": "foo/bar", "require": {}, "authors": [
+            { "name": "Alice", "email": "alice@example.org" },
+            { "name": "Bob", "email": "bob.smith+composer@mail.example.co.uk" },
+            { "name": "Carol", "email": "" }
+        ] }"#).unwrap();
+
+        assert!(validate_author_emails(&c).is_empty());
+    }
+
+    #[test]
+    fn malformed_author_email() {
+        let c: ComposerJson = serde_json::from_str(r#"{ "name": "foo/bar", "require": {}, "authors": [
+            { "name": "Alice", "email": "alice@example.org" },
+            { "name": "Bob", "email": "not-an-email" }
+        ] }"#).unwrap();
+
+        let errors = validate_author_emails(&c);
+
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].to_string(), "authors[1]: email \"not-an-email\" of Bob is not a valid email address");
+    }
+
+    fn with_license(license: serde_json::Value) -> ComposerJson {
+        serde_json::from_value(serde_json::json!({ "name": "foo/bar", "license": license, "require": {} })).unwrap()
+    }
+
+    #[test]
+    fn valid_licenses() {
+        for license in ["MIT", "proprietary", "LicenseRef-Acme-EULA", "Elastic-2.0", "BSD-3-Clause-LBNL", "GPL-3.0-or-later", "(LGPL-2.1-only or GPL-3.0-or-later)", "(MIT and Apache-2.0)", "GPL-2.0-only WITH Classpath-exception-2.0"] {
+            assert!(validate_license(&with_license(serde_json::json!(license))).is_empty(), "{} should be valid", license);
+        }
+
+        assert!(validate_license(&with_license(serde_json::json!(["LGPL-2.1-only", "GPL-3.0-or-later"]))).is_empty());
+    }
+
+    #[test]
+    fn misspelled_license() {
+        let errors = validate_license(&with_license(serde_json::json!(["MIT", "(Apache-2.0 or MITT)"])));
+
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].to_string(), "license: \"MITT\" is not a recognized SPDX license identifier");
+    }
+
+    #[test]
+    fn reports_all_errors_unless_fail_fast() {
+        let contents = r#"{ "name": "Foo Bar", "version": "one", "require": {} }"#;
+
+        let all = validate_contents(contents, &ValidateOptions::default()).unwrap();
+        let first = validate_contents(contents, &ValidateOptions { fail_fast: true, ..Default::default() }).unwrap();
+
+        assert_eq!(all.iter().map(|e| e.path.as_str()).collect::<Vec<_>>(), ["name", "version"]);
+        assert_eq!(first.len(), 1);
+        assert_eq!(first[0].path, "name");
+    }
+
+    #[test]
+    fn fail_fast_stops_at_first_failing_check() {
+        let contents = r#"{ "name": "foo/bar", "version": "one", "license": "MITT", "require": { "psr/log": "^3.0", "monolog/monolog": "^3.0" } }"#;
+        let options = ValidateOptions { strict_license: true, strict_order: true, fail_fast: true, ..Default::default() };
+
+        let errors = validate_contents(contents, &options).unwrap();
+
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].path, "version");
+    }
+
+    #[test]
+    fn misspelled_license_is_error_only_when_strict() {
+        let contents = r#"{ "name": "foo/bar", "license": "MITT", "require": {} }"#;
+
+        assert!(validate_contents(contents, &ValidateOptions::default()).unwrap().is_empty());
+        assert_eq!(validate_contents(contents, &ValidateOptions { strict_license: true, ..Default::default() }).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn required_name() {
+        assert_eq!(validate_required_name("").unwrap_err().message, "a name is required for published packages");
+        assert!(validate_required_name("monolog").is_err());
+        assert!(validate_required_name("monolog/monolog").is_ok());
+    }
+
+    #[test]
+    fn new_require_entry_rejects_clearly_invalid_names() {
+        let error = new_require_entry("Foo Bar/baz", "^1.0", &false).unwrap_err();
+
+        assert_eq!(error.message, "\"Foo Bar/baz\" is not a valid package name");
+        assert!(new_require_entry("", "^1.0", &false).is_err());
+        assert!(new_require_entry("foo", "^1.0", &false).is_err());
+    }
+
+    #[test]
+    fn new_require_entry_accepts_platform_packages() {
+        assert_eq!(new_require_entry("ext-json", "*", &true).unwrap(), ("ext-json".to_string(), "*".to_string()));
+    }
+
+    #[test]
+    fn new_require_entry_strictness() {
+        assert!(new_require_entry("Foo/Bar", "^1.0", &false).is_ok());
+        assert!(new_require_entry("Foo/Bar", "^1.0", &true).is_err());
+    }
+
+    #[test]
+    fn sorted_packages_pass_strict_order() {
+        let errors = validate_package_order(r#"{
+            "name": "foo/bar",
+            "require": { "php": ">=8.1", "ext-json": "*", "monolog/monolog": "^3.0", "symfony/yaml": "^6.0" },
+            "require-dev": { "phpunit/phpunit": "^10.0" }
+        }"#).unwrap();
+
+        assert!(errors.is_empty());
+    }
+
+    #[test]
+    fn unsorted_packages_fail_strict_order() {
+        let errors = validate_package_order(r#"{
+            "name": "foo/bar",
+            "require": { "symfony/yaml": "^6.0", "monolog/monolog": "^3.0" },
+            "require-dev": { "phpunit/phpunit": "^10.0", "php": ">=8.1" }
+        }"#).unwrap();
+
+        assert_eq!(errors.len(), 2);
+        assert_eq!(errors[0].path, "require");
+        assert_eq!(errors[0].message, "packages are not sorted: \"monolog/monolog\" should come before \"symfony/yaml\"");
+        assert_eq!(errors[1].path, "require-dev");
+    }
+
+    #[test]
+    fn psr4_prefix_without_trailing_backslash() {
+        let c: ComposerJson = serde_json::from_str(r#"{
+            "name": "foo/bar",
+            "require": {},
+            "autoload": { "psr-4": { "App": "src/", "Lib\\": "lib/", "": "fallback/" } },
+            "autoload-dev": { "psr-4": { "App\\Tests\\": "" } }
+        }"#).unwrap();
+
+        let errors: Vec<String> = validate_autoload_psr4(&c).iter().map(|e| e.to_string()).collect();
+
+        assert_eq!(errors, [
+            r#"autoload.psr-4: namespace prefix "App" must end with "\\""#,
+            r#"autoload-dev.psr-4: namespace prefix "App\Tests\" maps to an empty path"#,
+        ]);
+    }
+
+    #[test]
+    fn validate_contents_reports_psr4_issues() {
+        let contents = r#"{ "name": "foo/bar", "require": {}, "autoload": { "psr-4": { "App": "src/" } } }"#;
+        let errors = validate_contents(contents, &ValidateOptions::default()).unwrap();
+
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].path, "autoload.psr-4");
+    }
+
+    #[test]
+    fn check_platform_reqs_fails_with_validation_error_when_unsatisfied() {
+        let dir = TempDir::new("platform-reqs");
+
+        let file = dir.join("composer.json");
+        std::fs::write(&file, r#"{
+            "name": "foo/bar",
+            "require": { "php": ">=8.1" },
+            "require-dev": { "ext-xdebug": "*" },
+            "config": { "platform": { "php": "8.0.0" } }
+        }"#).unwrap();
+
+        let error = handle_check_platform_reqs(file.to_str().unwrap(), &false).unwrap_err();
+
+        assert!(matches!(error, ModifierError::Validation(message) if message.ends_with("1 unsatisfied platform requirement(s)")));
+    }
+}
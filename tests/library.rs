@@ -0,0 +1,44 @@
+use composer_json_modifier::{modify, ComposerJson, ModifierError, ModifyComposerJson, ModifyComposerJsonBuilder, PackagePattern, Repository};
+
+#[test]
+fn modify_in_memory() {
+    let composer: ComposerJson = serde_json::from_str(r#"{ "name": "foo/bar", "require": { "php": ">=8.1", "psr/log": "^1.0", "symfony/yaml": "^6.0" } }"#).unwrap();
+    let spec: ModifyComposerJson = serde_json::from_str(r#"{
+        "remove": { "require": { "symfony/*": "*" } },
+        "add": { "require": { "psr/log": "^3.0", "monolog/monolog": "^3.0" } }
+    }"#).unwrap();
+
+    let modified = modify(composer, &spec).unwrap();
+
+    assert_eq!(modified.name, "foo/bar");
+    assert_eq!(serde_json::to_value(&modified.package_links.require).unwrap(), serde_json::json!({
+        "php": ">=8.1",
+        "psr/log": "^3.0",
+        "monolog/monolog": "^3.0",
+    }));
+}
+
+#[test]
+fn modify_in_memory_fails_with_modifier_error() {
+    let composer: ComposerJson = serde_json::from_str(r#"{ "name": "foo/bar", "require": {} }"#).unwrap();
+    let spec: ModifyComposerJson = serde_json::from_str(r#"{ "modify": { "config": { "sort-packages": "sometimes" } } }"#).unwrap();
+
+    assert!(matches!(modify(composer, &spec), Err(ModifierError::Validation(_))));
+}
+
+#[test]
+fn modify_in_memory_with_builder() {
+    let composer: ComposerJson = serde_json::from_str(r#"{ "name": "foo/bar", "require": { "Symfony/Yaml": "^6.0", "psr/log": "^3.0" } }"#).unwrap();
+    let repository: Repository = serde_json::from_str(r#"{ "type": "vcs", "url": "https://github.com/foo/baz" }"#).unwrap();
+    let spec = ModifyComposerJsonBuilder::new()
+        .remove_require("(?i)symfony/*")
+        .add_repository(repository.clone())
+        .build()
+        .unwrap();
+
+    let modified = modify(composer, &spec).unwrap();
+
+    assert!(PackagePattern::new("(?i)symfony/*").unwrap().matches("Symfony/Yaml"));
+    assert_eq!(modified.package_links.require.keys().collect::<Vec<_>>(), ["psr/log"]);
+    assert_eq!(modified.repositories, Some(vec![repository]));
+}
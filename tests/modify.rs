@@ -0,0 +1,350 @@
+use std::fs;
+use std::process::Command;
+
+#[path = "../src/temp_dir.rs"]
+mod temp_dir;
+
+use temp_dir::TempDir;
+
+#[test]
+fn modify_print_outputs_modified_composer_json() {
+    let dir = TempDir::new("print");
+
+    let file = dir.join("composer.json");
+    fs::write(&file, r#"{ "name": "foo/bar", "require": { "php": ">=8.1" } }"#).unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_composer-json-modifier"))
+        .args(["modify", "run", "--print", "--dry-run", "--ensure", "psr/log=^3.0"])
+        .arg(&file)
+        .output()
+        .unwrap();
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    assert!(output.status.success());
+    assert!(!stdout.contains("<placeholder>"));
+    assert!(stdout.contains(r#""psr/log": "^3.0""#));
+}
+
+#[test]
+fn modify_dry_run_prints_diff() {
+    let dir = TempDir::new("dry-run");
+
+    let file = dir.join("composer.json");
+    let contents = r#"{ "name": "foo/bar", "require": { "php": ">=8.1", "psr/log": "^1.0" } }"#;
+    fs::write(&file, contents).unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_composer-json-modifier"))
+        .args(["modify", "run", "--dry-run", "--ensure", "monolog/monolog=^3.0", "--drop", "psr/log"])
+        .arg(&file)
+        .output()
+        .unwrap();
+    let after = fs::read_to_string(&file).unwrap();
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    assert!(output.status.success());
+    assert_eq!(after, contents);
+    assert!(stdout.contains("\n-        \"psr/log\": \"^1.0\""));
+    assert!(stdout.contains("\n+        \"monolog/monolog\": \"^3.0\"\n"));
+    assert!(stdout.contains("\n         \"php\": \">=8.1\""));
+}
+
+#[test]
+fn modify_dry_run_diff_context() {
+    let dir = TempDir::new("diff-context");
+
+    let file = dir.join("composer.json");
+    fs::write(&file, r#"{ "name": "foo/bar", "require": { "php": ">=8.1", "psr/log": "^1.0" } }"#).unwrap();
+
+    let run = |context: &str| {
+        let output = Command::new(env!("CARGO_BIN_EXE_composer-json-modifier"))
+            .args(["modify", "run", "--dry-run", "--ensure", "psr/log=^3.0", "--diff-context", context])
+            .arg(&file)
+            .output()
+            .unwrap();
+
+        String::from_utf8(output.stdout).unwrap()
+    };
+    let without_context = run("0");
+    let with_context = run("3");
+
+    assert!(without_context.contains("@@ -5,1 +5,1 @@\n-        \"psr/log\": \"^1.0\"\n+        \"psr/log\": \"^3.0\"\n"));
+    assert!(!without_context.contains("\n         \"php\""));
+    assert!(with_context.contains("@@ -2,6 +2,6 @@\n     \"name\": \"foo/bar\",\n     \"require\": {\n         \"php\": \">=8.1\",\n-"));
+}
+
+#[test]
+fn modify_patch_output_is_only_the_patch() {
+    let dir = TempDir::new("patch-stdout");
+
+    let file = dir.join("composer.json");
+    fs::write(&file, r#"{ "name": "foo/bar", "require": { "php": ">=8.1" } }"#).unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_composer-json-modifier"))
+        .args(["modify", "run", "--output-format", "patch", "--ensure", "psr/log=^3.0"])
+        .arg(&file)
+        .output()
+        .unwrap();
+
+    let patch: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+
+    assert!(output.status.success());
+    assert_eq!(patch, serde_json::json!([{ "op": "add", "path": "/require/psr~1log", "value": "^3.0" }]));
+    assert!(String::from_utf8(output.stderr).unwrap().contains("Modifying"));
+}
+
+#[test]
+fn modify_check_exits_zero_without_changes() {
+    let dir = TempDir::new("check-unchanged");
+
+    let file = dir.join("composer.json");
+    let contents = "{\n    \"name\": \"foo/bar\",\n    \"require\": {\n        \"php\": \">=8.1\",\n        \"psr/log\": \"^3.0\"\n    }\n}\n";
+    fs::write(&file, contents).unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_composer-json-modifier"))
+        .args(["modify", "run", "--check", "--ensure", "psr/log=^3.0"])
+        .arg(&file)
+        .output()
+        .unwrap();
+    let after = fs::read_to_string(&file).unwrap();
+
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(after, contents);
+}
+
+#[test]
+fn modify_check_exits_one_with_formatting_changes() {
+    let dir = TempDir::new("check-formatting");
+
+    let file = dir.join("composer.json");
+    let contents = r#"{ "name": "foo/bar", "require": { "php": ">=8.1", "psr/log": "^3.0" } }"#;
+    fs::write(&file, contents).unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_composer-json-modifier"))
+        .args(["modify", "run", "--check", "--ensure", "psr/log=^3.0"])
+        .arg(&file)
+        .output()
+        .unwrap();
+    let after = fs::read_to_string(&file).unwrap();
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(after, contents);
+    assert!(stdout.contains("\n+        \"psr/log\": \"^3.0\"\n"));
+}
+
+#[test]
+fn modify_check_exits_one_with_changes() {
+    let dir = TempDir::new("check-changed");
+
+    let file = dir.join("composer.json");
+    let contents = r#"{ "name": "foo/bar", "require": { "php": ">=8.1", "psr/log": "^1.0" } }"#;
+    fs::write(&file, contents).unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_composer-json-modifier"))
+        .args(["modify", "run", "--check", "--ensure", "psr/log=^3.0"])
+        .arg(&file)
+        .output()
+        .unwrap();
+    let after = fs::read_to_string(&file).unwrap();
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(after, contents);
+    assert!(stdout.contains("\n+        \"psr/log\": \"^3.0\"\n"));
+}
+
+#[test]
+fn modify_on_conflict_error_fails() {
+    let dir = TempDir::new("on-conflict-error");
+
+    let file = dir.join("composer.json");
+    let contents = r#"{ "name": "foo/bar", "require": { "php": ">=8.1", "psr/log": "^1.0" } }"#;
+    fs::write(&file, contents).unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_composer-json-modifier"))
+        .args(["modify", "run", "--on-conflict", "error", "--ensure", "psr/log=^3.0"])
+        .arg(&file)
+        .output()
+        .unwrap();
+    let after = fs::read_to_string(&file).unwrap();
+
+    let stderr = String::from_utf8(output.stderr).unwrap();
+
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(after, contents);
+    assert!(stderr.contains("psr/log"));
+    assert!(!stderr.contains("error parsing"));
+}
+
+#[test]
+fn modify_backup_keeps_original_contents() {
+    let dir = TempDir::new("backup");
+
+    let file = dir.join("composer.json");
+    let contents = r#"{ "name": "foo/bar", "require": { "php": ">=8.1" } }"#;
+    fs::write(&file, contents).unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_composer-json-modifier"))
+        .args(["modify", "run", "--backup", "--ensure", "psr/log=^3.0"])
+        .arg(&file)
+        .output()
+        .unwrap();
+    let backup = fs::read_to_string(dir.join("composer.json.bak")).unwrap();
+    let after = fs::read_to_string(&file).unwrap();
+
+    assert!(output.status.success());
+    assert_eq!(backup, contents);
+    assert!(after.contains(r#""psr/log": "^3.0""#));
+}
+
+#[test]
+fn modify_warns_about_unmatched_patterns() {
+    let dir = TempDir::new("unmatched");
+
+    let file = dir.join("composer.json");
+    let contents = r#"{ "name": "foo/bar", "require": { "php": ">=8.1", "psr/log": "^1.0" } }"#;
+    fs::write(&file, contents).unwrap();
+
+    let run = |args: &[&str]| Command::new(env!("CARGO_BIN_EXE_composer-json-modifier"))
+        .args(["modify", "run", "--dry-run"])
+        .args(args)
+        .arg(&file)
+        .output()
+        .unwrap();
+    let matched = run(&["--drop", "psr/*"]);
+    let unmatched = run(&["--drop", "laminas/*"]);
+    let failed = run(&["--drop", "laminas/*", "--error-on-unmatched"]);
+
+    assert!(matched.status.success());
+    assert!(!String::from_utf8(matched.stderr).unwrap().contains("matched no package"));
+    assert!(unmatched.status.success());
+    assert!(String::from_utf8(unmatched.stderr).unwrap().contains("warning: remove pattern \"laminas/*\" matched no package"));
+    assert_eq!(failed.status.code(), Some(1));
+}
+
+#[test]
+fn modify_rejects_duplicate_keys() {
+    let dir = TempDir::new("duplicate-keys");
+
+    let file = dir.join("composer.json");
+    let contents = r#"{ "name": "foo/bar", "require": { "psr/log": "^1.0", "psr/log": "^3.0" } }"#;
+    fs::write(&file, contents).unwrap();
+
+    let run = |args: &[&str]| Command::new(env!("CARGO_BIN_EXE_composer-json-modifier"))
+        .args(["modify", "run", "--ensure", "php=>=8.1"])
+        .args(args)
+        .arg(&file)
+        .output()
+        .unwrap();
+    let rejected = run(&["--reject-duplicate-keys"]);
+    let after = fs::read_to_string(&file).unwrap();
+
+    assert_eq!(rejected.status.code(), Some(1));
+    assert!(String::from_utf8(rejected.stderr).unwrap().contains("psr/log"));
+    assert_eq!(after, contents);
+}
+
+#[test]
+fn modify_fails_on_any_error() {
+    let dir = TempDir::new("any-error");
+
+    let file = dir.join("composer.json");
+    let unparseable = dir.join("unparseable.json");
+    let contents = r#"{ "name": "foo/bar", "require": { "php": ">=8.1" } }"#;
+    fs::write(&file, contents).unwrap();
+    fs::write(&unparseable, r#"{ "name": "foo/bar", "time": "yesterday", "require": {} }"#).unwrap();
+
+    let run = |args: &[&str], file: &std::path::Path| Command::new(env!("CARGO_BIN_EXE_composer-json-modifier"))
+        .args(["modify", "run"])
+        .args(args)
+        .arg(file)
+        .output()
+        .unwrap();
+    let invalid_name = run(&["--validate-names", "--ensure", "Foo_Bar/baz=^1.0"], &file);
+    let malformed_ensure = run(&["--ensure", "nonsense"], &file);
+    let parse_error = run(&["--ensure", "psr/log=^3.0"], &unparseable);
+
+    assert_eq!(invalid_name.status.code(), Some(1));
+    assert_eq!(malformed_ensure.status.code(), Some(1));
+    assert_eq!(parse_error.status.code(), Some(1));
+    assert_eq!(fs::read_to_string(&file).unwrap(), contents);
+}
+
+#[test]
+fn modify_run_all_reports_unparseable_manifests() {
+    let dir = TempDir::new("run-all");
+    fs::create_dir_all(dir.join("packages/valid")).unwrap();
+    fs::create_dir_all(dir.join("packages/invalid")).unwrap();
+
+    let modify = dir.join("modify-composer.json");
+    fs::write(&modify, r#"{ "add": { "require": { "psr/log": "^3.0" } } }"#).unwrap();
+    fs::write(dir.join("packages/valid/composer.json"), r#"{ "name": "foo/valid", "require": {} }"#).unwrap();
+    fs::write(dir.join("packages/invalid/composer.json"), r#"{ "name": "foo/invalid", "require": "#).unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_composer-json-modifier"))
+        .args(["modify", "run-all"])
+        .arg(&modify)
+        .arg(dir.join("packages"))
+        .output()
+        .unwrap();
+    let valid = fs::read_to_string(dir.join("packages/valid/composer.json")).unwrap();
+
+    let stderr = String::from_utf8(output.stderr).unwrap();
+
+    assert_eq!(output.status.code(), Some(1));
+    assert!(valid.contains(r#""psr/log": "^3.0""#));
+    assert!(stderr.contains("packages/invalid/composer.json"));
+    assert!(stderr.contains("1 of 2 file(s) failed"));
+}
+
+#[test]
+fn parse_lock_prints_package_count() {
+    let dir = TempDir::new("parse-lock");
+
+    let file = dir.join("composer.lock");
+    fs::write(&file, r#"{
+        "content-hash": "d41d8cd98f00b204e9800998ecf8427e",
+        "packages": [{ "name": "psr/log", "version": "3.0.0" }, { "name": "symfony/yaml", "version": "v6.3.0" }],
+        "packages-dev": [{ "name": "phpunit/phpunit", "version": "10.2.0" }]
+    }"#).unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_composer-json-modifier"))
+        .args(["parse", "lock"])
+        .arg(&file)
+        .output()
+        .unwrap();
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    assert!(output.status.success());
+    assert!(stdout.contains("2 packages, 1 dev packages"));
+}
+
+#[test]
+fn quiet_suppresses_success_message() {
+    let dir = TempDir::new("quiet");
+
+    let file = dir.join("composer.json");
+    fs::write(&file, r#"{ "name": "foo/bar", "require": { "php": ">=8.1" } }"#).unwrap();
+
+    let parse = |quiet: bool| Command::new(env!("CARGO_BIN_EXE_composer-json-modifier"))
+        .args(if quiet { vec!["--quiet"] } else { vec![] })
+        .args(["parse", "composer-json", "--print"])
+        .arg(&file)
+        .output()
+        .unwrap();
+    let normal = parse(false);
+    let quiet = parse(true);
+
+    let normal_stdout = String::from_utf8(normal.stdout).unwrap();
+    let quiet_stdout = String::from_utf8(quiet.stdout).unwrap();
+
+    assert!(normal_stdout.contains("successfully parsed composer.json file"));
+    assert!(quiet.status.success());
+    assert!(!quiet_stdout.contains("successfully parsed"));
+    assert!(quiet_stdout.contains(r#""name": "foo/bar""#));
+}